@@ -0,0 +1,2482 @@
+//! Conversion logic for the Composite HKX Conversion Tool, independent of the GUI.
+//!
+//! Extract the bundled tools with [`ToolPaths::extract_to`], then run conversions with
+//! [`Converter`]. [`get_output_path`] computes where a converted file should be written.
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+
+/// Print to stdout unless the log level is [`LogLevel::Quiet`]: one line per batch or list change
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        if $crate::log_level() >= $crate::LogLevel::Normal {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Print to stdout at [`LogLevel::Verbose`] only: commands, temporary files and other per-file details
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        if $crate::log_level() >= $crate::LogLevel::Verbose {
+            println!($($arg)*);
+        }
+    };
+}
+
+const HKXCMD_EXE: &[u8] = include_bytes!("hkxcmd.exe");
+const HKXC_EXE: &[u8] = include_bytes!("hkxc.exe");
+const HKXCONV_EXE: &[u8] = include_bytes!("hkxconv.exe");
+const SSE_TO_LE_HKO: &[u8] = include_bytes!("_SSEtoLE.hko");
+const HAVOK_BEHAVIOR_POST_PROCESS_EXE: &[u8] = include_bytes!("HavokBehaviorPostProcess.exe");
+const HCT_STANDALONE_FILTER_MANAGER_EXE: &[u8] = include_bytes!("hctStandAloneFilterManager.exe");
+const HCT_FILTER_MANAGER_DLL: &[u8] = include_bytes!("hctFilterManager.dll");
+
+/// File name hctStandAloneFilterManager.exe loads its filter manager from
+const HCT_FILTER_MANAGER_DLL_NAME: &str = "hctFilterManager.dll";
+
+/// Working folders of the HCT conversions currently running. HCT always writes `filename.hkx`
+/// next to the .hko, so two conversions sharing a folder would overwrite each other's output.
+static ACTIVE_HCT_WORK_DIRS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Marks an HCT working folder as in use until dropped
+struct HctWorkDirClaim(PathBuf);
+
+impl HctWorkDirClaim {
+    /// Claim `dir` for one HCT conversion, failing if another conversion already uses it
+    fn new(dir: &Path) -> Result<Self> {
+        let mut active = ACTIVE_HCT_WORK_DIRS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if active.iter().any(|active_dir| active_dir == dir) {
+            anyhow::bail!("HCT working folder {:?} is already in use by another conversion", dir);
+        }
+        active.push(dir.to_path_buf());
+        Ok(Self(dir.to_path_buf()))
+    }
+}
+
+impl Drop for HctWorkDirClaim {
+    fn drop(&mut self) {
+        let mut active = ACTIVE_HCT_WORK_DIRS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        active.retain(|active_dir| *active_dir != self.0);
+    }
+}
+
+/// Windows exit code for a process whose DLL couldn't be found (STATUS_DLL_NOT_FOUND)
+const STATUS_DLL_NOT_FOUND: i32 = 0xC000_0135_u32 as i32;
+
+/// Windows exit code for a 32/64-bit mismatch or a broken DLL, usually a damaged VC++ runtime (STATUS_INVALID_IMAGE_FORMAT)
+const STATUS_INVALID_IMAGE_FORMAT: i32 = 0xC000_007B_u32 as i32;
+
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum ConverterTool {
+    HkxCmd,
+    Hct,
+    HavokBehaviorPostProcess,
+    HkxC,
+    HkxConv,
+}
+
+impl ConverterTool {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConverterTool::HkxCmd => "hkxcmd",
+            ConverterTool::Hct => "HavokContentTools",
+            ConverterTool::HavokBehaviorPostProcess => "HavokBehaviorPostProcess",
+            ConverterTool::HkxC => "hkxc",
+            ConverterTool::HkxConv => "hkxconv",
+        }
+    }
+
+    /// Name stored in the settings file
+    pub fn key(&self) -> &'static str {
+        match self {
+            ConverterTool::HkxCmd => "hkxcmd",
+            ConverterTool::Hct => "hct",
+            ConverterTool::HavokBehaviorPostProcess => "havok_behavior_post_process",
+            ConverterTool::HkxC => "hkxc",
+            ConverterTool::HkxConv => "hkxconv",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [
+            ConverterTool::HkxCmd,
+            ConverterTool::Hct,
+            ConverterTool::HavokBehaviorPostProcess,
+            ConverterTool::HkxC,
+            ConverterTool::HkxConv,
+        ]
+        .into_iter()
+        .find(|tool| tool.key() == key)
+    }
+
+    /// Get help text for this tool
+    pub fn help_text(&self) -> &'static str {
+        match self {
+            ConverterTool::HkxCmd => "LE animation HKX -> SE animation HKX || .kf || .xml (requires skeleton file)",
+            ConverterTool::Hct => "SE animation HKX || HKT tagfile -> LE animation HKX (.hko files are filter presets and can't be converted)",
+            ConverterTool::HavokBehaviorPostProcess => "LE animation HKX -> SE animation HKX",
+            ConverterTool::HkxC => "SE animation/behavior HKX <-> LE animation/behaviorHKX <-> .xml",
+            ConverterTool::HkxConv => "SE behavior HKX <-> .xml",
+        }
+    }
+
+    /// Get the input file extensions this tool can read
+    pub fn supported_extensions(&self) -> &'static [&'static str] {
+        match self {
+            ConverterTool::HkxCmd => &["hkx", "xml", "kf"],
+            ConverterTool::HkxC | ConverterTool::HkxConv => &["hkx", "xml"],
+            // HCT loads any Havok asset, including binary tagfiles
+            ConverterTool::Hct => &["hkx", "hkt"],
+            ConverterTool::HavokBehaviorPostProcess => &["hkx"],
+        }
+    }
+
+    /// Check if this tool supports a given file extension
+    pub fn supports_extension(&self, ext: &str) -> bool {
+        self.supported_extensions().contains(&ext)
+    }
+
+    /// Check if this tool supports a given file path
+    pub fn supports_file(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| self.supports_extension(ext))
+            .unwrap_or(false)
+    }
+
+    /// Get available input file extensions for this tool
+    pub fn available_input_extensions(&self) -> Vec<InputFileExtension> {
+        match self {
+            ConverterTool::HkxCmd => {
+                vec![
+                    InputFileExtension::All,
+                    InputFileExtension::Hkx,
+                    InputFileExtension::Xml,
+                    InputFileExtension::Kf,
+                ]
+            }
+            ConverterTool::HkxC | ConverterTool::HkxConv => {
+                vec![
+                    InputFileExtension::All,
+                    InputFileExtension::Hkx,
+                    InputFileExtension::Xml,
+                ]
+            }
+            ConverterTool::Hct => {
+                vec![
+                    InputFileExtension::All,
+                    InputFileExtension::Hkx,
+                    InputFileExtension::Hkt,
+                ]
+            }
+            ConverterTool::HavokBehaviorPostProcess => {
+                vec![
+                    InputFileExtension::All,
+                    InputFileExtension::Hkx,
+                ]
+            }
+        }
+    }
+
+    /// Get available output formats for this tool
+    pub fn available_output_formats(&self) -> Vec<OutputFormat> {
+        match self {
+            ConverterTool::HkxCmd => {
+                vec![
+                    OutputFormat::Xml,
+                    OutputFormat::SkyrimLE,
+                    OutputFormat::SkyrimSE,
+                    OutputFormat::Kf,
+                ]
+            }
+            ConverterTool::HkxC => {
+                vec![
+                    OutputFormat::Xml,
+                    OutputFormat::SkyrimLE,
+                    OutputFormat::SkyrimSE,
+                ]
+            }
+            ConverterTool::HkxConv => {
+                vec![
+                    OutputFormat::Xml,
+                    OutputFormat::SkyrimSE,
+                ]
+            }
+            ConverterTool::Hct => {
+                vec![OutputFormat::SkyrimLE]
+            }
+            ConverterTool::HavokBehaviorPostProcess => {
+                vec![OutputFormat::SkyrimSE]
+            }
+        }
+    }
+
+    /// Check that this tool can convert `input` to `output_format`, explaining why not otherwise
+    pub fn check_conversion(&self, input: &Path, output_format: OutputFormat) -> Result<()> {
+        if !self.available_output_formats().contains(&output_format) {
+            return Err(anyhow::anyhow!("{} can't convert to {}", self.label(), output_format.label()));
+        }
+        if !self.supports_file(input) {
+            let extension = input.extension().unwrap_or_default().to_string_lossy().to_uppercase();
+            return Err(anyhow::anyhow!("{} can't read {} files", self.label(), extension));
+        }
+        // hkxcmd only reads KF through ConvertKF, which the KF output format selects
+        let is_kf = input.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("kf"));
+        if is_kf && output_format != OutputFormat::Kf {
+            return Err(anyhow::anyhow!("KF files can only be converted with the KF output format"));
+        }
+        Ok(())
+    }
+
+    /// Check if this tool can open paths containing non-ASCII characters.
+    ///
+    /// hkxcmd, HCT and HavokBehaviorPostProcess read their command line through the ANSI Windows
+    /// APIs, so characters outside the system code page arrive as `?`. [`Converter::convert`] runs
+    /// these tools on ASCII-named copies in a temp folder instead. hkxc and hkxconv read the
+    /// command line as UTF-16 and take any path.
+    pub fn supports_unicode_paths(&self) -> bool {
+        matches!(self, ConverterTool::HkxC | ConverterTool::HkxConv)
+    }
+
+    /// Check if this tool takes a target version that [`Converter::with_version_override`] can replace
+    pub fn supports_version_override(&self) -> bool {
+        matches!(self, ConverterTool::HkxCmd | ConverterTool::HkxC)
+    }
+
+    /// Get supported formats description for drag & drop overlay
+    pub fn supported_formats_description(&self) -> &'static str {
+        match self {
+            ConverterTool::HkxCmd => "Supports: HKX, XML, KF files",
+            ConverterTool::HkxC | ConverterTool::HkxConv => "Supports: HKX, XML files",
+            ConverterTool::Hct => "Supports: HKX, HKT files",
+            ConverterTool::HavokBehaviorPostProcess => "Supports: HKX files",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum InputFileExtension {
+    All,
+    Hkx,
+    Xml,
+    Kf,
+    Hkt,
+}
+
+impl InputFileExtension {
+    pub fn label_for_tool(&self, tool: ConverterTool) -> &'static str {
+        match self {
+            InputFileExtension::All => match tool {
+                ConverterTool::HkxCmd => "All (HKX, XML, KF)",
+                ConverterTool::HkxC => "All (HKX, XML)",
+                ConverterTool::HkxConv => "All (HKX, XML)",
+                ConverterTool::Hct => "All (HKX, HKT)",
+                ConverterTool::HavokBehaviorPostProcess => "All (HKX only)",
+            },
+            InputFileExtension::Hkx => "HKX only",
+            InputFileExtension::Xml => "XML only",
+            InputFileExtension::Kf => "KF only",
+            InputFileExtension::Hkt => "HKT only",
+        }
+    }
+
+    /// Name stored in the settings file
+    pub fn key(&self) -> &'static str {
+        match self {
+            InputFileExtension::All => "all",
+            InputFileExtension::Hkx => "hkx",
+            InputFileExtension::Xml => "xml",
+            InputFileExtension::Kf => "kf",
+            InputFileExtension::Hkt => "hkt",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [
+            InputFileExtension::All,
+            InputFileExtension::Hkx,
+            InputFileExtension::Xml,
+            InputFileExtension::Kf,
+            InputFileExtension::Hkt,
+        ]
+        .into_iter()
+        .find(|extension| extension.key() == key)
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum OutputFormat {
+    Xml,
+    SkyrimLE,
+    SkyrimSE,
+    Kf,
+}
+
+impl OutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Xml => "xml",
+            OutputFormat::SkyrimLE | OutputFormat::SkyrimSE => "hkx",
+            OutputFormat::Kf => "kf",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OutputFormat::Xml => "XML",
+            OutputFormat::SkyrimLE => "Skyrim LE",
+            OutputFormat::SkyrimSE => "Skyrim SE",
+            OutputFormat::Kf => "KF",
+        }
+    }
+
+    /// Name stored in the settings file
+    pub fn key(&self) -> &'static str {
+        match self {
+            OutputFormat::Xml => "xml",
+            OutputFormat::SkyrimLE => "skyrim_le",
+            OutputFormat::SkyrimSE => "skyrim_se",
+            OutputFormat::Kf => "kf",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [OutputFormat::Xml, OutputFormat::SkyrimLE, OutputFormat::SkyrimSE, OutputFormat::Kf]
+            .into_iter()
+            .find(|format| format.key() == key)
+    }
+
+    /// Subfolder name used to keep outputs apart when converting to several formats at once
+    pub fn folder_name(&self) -> &'static str {
+        match self {
+            OutputFormat::Xml => "XML",
+            OutputFormat::SkyrimLE => "SkyrimLE",
+            OutputFormat::SkyrimSE => "SkyrimSE",
+            OutputFormat::Kf => "KF",
+        }
+    }
+
+    /// Check if this output format requires a skeleton file
+    pub fn requires_skeleton(&self) -> bool {
+        matches!(self, OutputFormat::Kf)
+    }
+
+    /// Detect the format of an existing file from its extension and, for HKX, its packfile header
+    pub fn detect_from_file(path: &Path) -> Option<OutputFormat> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "xml" => Some(OutputFormat::Xml),
+            "kf" => Some(OutputFormat::Kf),
+            "hkx" => {
+                // The packfile layout rules start at offset 16; the first byte is the pointer size
+                let mut header = [0u8; 17];
+                let mut file = fs::File::open(path).ok()?;
+                std::io::Read::read_exact(&mut file, &mut header).ok()?;
+                match header[16] {
+                    4 => Some(OutputFormat::SkyrimLE),
+                    8 => Some(OutputFormat::SkyrimSE),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Check that `path` exists and is an LE or SE HKX packfile with the .hkx extension that hkxcmd
+/// can use as a skeleton
+pub fn validate_skeleton_file(path: &Path) -> Result<()> {
+    if !path.is_file() {
+        return Err(anyhow::anyhow!("Skeleton file {:?} does not exist", path));
+    }
+    if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("hkx")) {
+        return Err(anyhow::anyhow!("Skeleton file {:?} is not an .hkx file. Choose the skeleton.hkx the animations were made for.", path));
+    }
+    match OutputFormat::detect_from_file(path) {
+        Some(OutputFormat::SkyrimLE | OutputFormat::SkyrimSE) => Ok(()),
+        _ => Err(anyhow::anyhow!("Skeleton file {:?} is not a valid HKX file", path)),
+    }
+}
+
+/// Skeleton used for the inputs inside one folder, for batches mixing several creatures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkeletonRule {
+    pub folder: PathBuf,
+    pub skeleton_file: PathBuf,
+}
+
+/// Skeleton for `input`: the rule with the deepest folder containing it, otherwise `fallback`
+pub fn skeleton_for_input<'a>(input: &Path, rules: &'a [SkeletonRule], fallback: Option<&'a Path>) -> Option<&'a Path> {
+    let input = ensure_absolute_path(input);
+    rules
+        .iter()
+        .map(|rule| (ensure_absolute_path(&rule.folder), rule))
+        .filter(|(folder, _)| input.starts_with(folder))
+        .max_by_key(|(folder, _)| folder.components().count())
+        .map(|(_, rule)| rule.skeleton_file.as_path())
+        .or(fallback)
+}
+
+/// Folders of OS and archive-tool clutter that folder scans skip along with hidden files. `__MACOSX`
+/// holds `._<name>.hkx` resource forks that match the input filter but aren't HKX files.
+pub const IGNORED_SCAN_FOLDERS: &[&str] = &["__MACOSX", "$RECYCLE.BIN", "System Volume Information"];
+
+/// Whether folder scans skip `path` unless hidden files are included: dot-files and dot-folders,
+/// anything with the Windows hidden or system attribute, and [`IGNORED_SCAN_FOLDERS`]
+pub fn is_hidden_file(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.starts_with('.')
+        || IGNORED_SCAN_FOLDERS.iter().any(|folder| name.eq_ignore_ascii_case(folder))
+        || has_hidden_attribute(path)
+}
+
+#[cfg(windows)]
+fn has_hidden_attribute(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0)
+}
+
+#[cfg(not(windows))]
+fn has_hidden_attribute(_path: &Path) -> bool {
+    false
+}
+
+/// Folders FNIS and Nemesis generate or patch: behavior graphs, character projects and the engine itself
+const FRAMEWORK_FOLDERS: &[&str] = &["behaviors", "behaviors wolf", "characters", "characterassets", "character assets", "nemesis_engine"];
+
+/// Whether `path`, found under `root`, is an animation in the FNIS/Nemesis layout
+/// (`meshes\actors\<creature>\animations\...`) rather than a file those frameworks generate.
+///
+/// Behavior and character folders, the Nemesis engine folder and FNIS-named files such as
+/// `FNIS_<mod>_List.txt` and the generated `FNIS_*_Behavior.hkx` are never animations. Only the
+/// folders below `root` are checked for those, so `root` may itself be inside an animations folder.
+pub fn is_framework_animation(path: &Path, root: &Path) -> bool {
+    let folder_name = |component: std::path::Component| component.as_os_str().to_string_lossy().to_lowercase();
+    let Some(parent) = path.parent() else {
+        return false;
+    };
+    if !parent.components().any(|component| folder_name(component) == "animations") {
+        return false;
+    }
+    let below_root = parent.strip_prefix(root).unwrap_or(parent);
+    if below_root.components().any(|component| FRAMEWORK_FOLDERS.contains(&folder_name(component).as_str())) {
+        return false;
+    }
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+    !file_name.starts_with("fnis_") && !file_name.starts_with("nemesis_")
+}
+
+/// Folders holding behavior graphs and character projects rather than animations
+const BEHAVIOR_FOLDERS: &[&str] = &["behaviors", "behaviors wolf", "characters"];
+
+/// Whether `path` is a behavior graph or character project: a file inside a [`BEHAVIOR_FOLDERS`]
+/// folder, or one named like the `FNIS_*_Behavior.hkx` files FNIS generates
+pub fn is_behavior_file(path: &Path) -> bool {
+    let in_behavior_folder = path.parent().is_some_and(|parent| {
+        parent
+            .components()
+            .any(|component| BEHAVIOR_FOLDERS.contains(&component.as_os_str().to_string_lossy().to_lowercase().as_str()))
+    });
+    let file_stem = path.file_stem().unwrap_or_default().to_string_lossy().to_lowercase();
+    in_behavior_folder || file_stem.ends_with("behavior")
+}
+
+/// Tool the Auto mode picks for converting `input` to `output_format`. The first matching rule wins:
+///
+/// 1. KF input or KF output: hkxcmd, the only tool that reads and writes KF
+/// 2. Behavior files (see [`is_behavior_file`]) that are SE HKX or XML, converted to SE or XML: hkxconv
+/// 3. Everything else, including LE behaviors and any LE output: hkxc
+pub fn auto_converter_tool(input: &Path, output_format: OutputFormat) -> ConverterTool {
+    auto_converter_tool_for_format(input, OutputFormat::detect_from_file(input), output_format)
+}
+
+/// [`auto_converter_tool`] for an input whose format [`OutputFormat::detect_from_file`] already read
+pub fn auto_converter_tool_for_format(input: &Path, input_format: Option<OutputFormat>, output_format: OutputFormat) -> ConverterTool {
+    if input_format == Some(OutputFormat::Kf) || output_format == OutputFormat::Kf {
+        return ConverterTool::HkxCmd;
+    }
+    let se_or_xml = |format| matches!(format, OutputFormat::SkyrimSE | OutputFormat::Xml);
+    if is_behavior_file(input) && input_format.is_some_and(se_or_xml) && se_or_xml(output_format) {
+        return ConverterTool::HkxConv;
+    }
+    ConverterTool::HkxC
+}
+
+/// Error returned when a tool runs longer than the converter's timeout and is killed
+#[derive(Debug, Clone, Copy)]
+pub struct ToolTimedOut(pub Duration);
+
+impl std::fmt::Display for ToolTimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Tool did not finish within {} seconds and was stopped", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for ToolTimedOut {}
+
+/// How much is printed to the console. Errors are always printed.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default)]
+pub enum LogLevel {
+    Quiet,
+    #[default]
+    Normal,
+    Verbose,
+}
+
+impl LogLevel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Quiet => "Quiet",
+            LogLevel::Normal => "Normal",
+            LogLevel::Verbose => "Verbose",
+        }
+    }
+
+    /// Name stored in the settings file
+    pub fn key(&self) -> &'static str {
+        match self {
+            LogLevel::Quiet => "quiet",
+            LogLevel::Normal => "normal",
+            LogLevel::Verbose => "verbose",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [LogLevel::Quiet, LogLevel::Normal, LogLevel::Verbose]
+            .into_iter()
+            .find(|level| level.key() == key)
+    }
+}
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Normal as u8);
+
+/// Set how much [`log_info!`] and [`log_debug!`] print, for the whole process
+pub fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn log_level() -> LogLevel {
+    match LOG_LEVEL.load(Ordering::Relaxed) {
+        0 => LogLevel::Quiet,
+        1 => LogLevel::Normal,
+        _ => LogLevel::Verbose,
+    }
+}
+
+/// Case applied to output file names
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum FilenameCase {
+    #[default]
+    Preserve,
+    Lowercase,
+    Uppercase,
+}
+
+impl FilenameCase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilenameCase::Preserve => "Preserve",
+            FilenameCase::Lowercase => "lowercase",
+            FilenameCase::Uppercase => "UPPERCASE",
+        }
+    }
+
+    /// Name stored in the settings file
+    pub fn key(&self) -> &'static str {
+        match self {
+            FilenameCase::Preserve => "preserve",
+            FilenameCase::Lowercase => "lowercase",
+            FilenameCase::Uppercase => "uppercase",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [FilenameCase::Preserve, FilenameCase::Lowercase, FilenameCase::Uppercase]
+            .into_iter()
+            .find(|case| case.key() == key)
+    }
+
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            FilenameCase::Preserve => name.to_string(),
+            FilenameCase::Lowercase => name.to_lowercase(),
+            FilenameCase::Uppercase => name.to_uppercase(),
+        }
+    }
+}
+
+/// Result of round-trip verification for one converted file
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum VerifyOutcome {
+    // Verification was off or not possible for this tool/format combination
+    Skipped,
+    Matched,
+    Mismatched,
+}
+
+/// Paths to the extracted conversion tools
+#[derive(Debug, Clone, Default)]
+pub struct ToolPaths {
+    pub hkxcmd: PathBuf,
+    pub hkxc: PathBuf,
+    pub hkxconv: PathBuf,
+    pub sse_to_le_hko: PathBuf,
+    pub havok_behavior_post_process: PathBuf,
+    pub hct_standalone_filter_manager: PathBuf,
+    pub hct_filter_manager_dll: PathBuf,
+}
+
+impl ToolPaths {
+    /// Extract the bundled tools into `dir`
+    pub fn extract_to(dir: &Path) -> Result<Self> {
+        Self::extract_with(dir, extract_embedded_file)
+    }
+
+    /// Extract the bundled tools into a cache folder kept across launches, creating it if needed.
+    ///
+    /// Files already there with the bundled contents are reused as they are, so only tools that
+    /// changed since the last launch (or were deleted, e.g. by antivirus) are written again.
+    pub fn extract_cached(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("Could not create {:?}", dir))?;
+        Self::extract_with(dir, reuse_or_extract_embedded_file)
+    }
+
+    fn extract_with(dir: &Path, extract: fn(&Path, &str, &[u8]) -> Result<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            hkxcmd: extract(dir, "hkxcmd.exe", HKXCMD_EXE)?,
+            hkxc: extract(dir, "hkxc.exe", HKXC_EXE)?,
+            hkxconv: extract(dir, "hkxconv.exe", HKXCONV_EXE)?,
+            sse_to_le_hko: extract(dir, "_SSEtoLE.hko", SSE_TO_LE_HKO)?,
+            havok_behavior_post_process: extract(dir, "HavokBehaviorPostProcess.exe", HAVOK_BEHAVIOR_POST_PROCESS_EXE)?,
+            hct_standalone_filter_manager: extract(dir, "hctStandAloneFilterManager.exe", HCT_STANDALONE_FILTER_MANAGER_EXE)?,
+            hct_filter_manager_dll: extract(dir, HCT_FILTER_MANAGER_DLL_NAME, HCT_FILTER_MANAGER_DLL)?,
+        })
+    }
+
+    /// Use tools the user keeps in `dir` (portable mode) instead of extracting the bundled ones.
+    ///
+    /// Every bundled file must be present under its bundled name.
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        let missing: Vec<&str> = BUNDLED_FILES
+            .iter()
+            .map(|(file_name, _)| *file_name)
+            .filter(|file_name| !dir.join(file_name).is_file())
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!("{:?} is missing {}", dir, missing.join(", ")));
+        }
+        Ok(Self {
+            hkxcmd: dir.join("hkxcmd.exe"),
+            hkxc: dir.join("hkxc.exe"),
+            hkxconv: dir.join("hkxconv.exe"),
+            sse_to_le_hko: dir.join("_SSEtoLE.hko"),
+            havok_behavior_post_process: dir.join("HavokBehaviorPostProcess.exe"),
+            hct_standalone_filter_manager: dir.join("hctStandAloneFilterManager.exe"),
+            hct_filter_manager_dll: dir.join(HCT_FILTER_MANAGER_DLL_NAME),
+        })
+    }
+
+    /// Get the executable for a tool
+    pub fn executable(&self, tool: ConverterTool) -> &Path {
+        match tool {
+            ConverterTool::HkxCmd => &self.hkxcmd,
+            ConverterTool::Hct => &self.hct_standalone_filter_manager,
+            ConverterTool::HavokBehaviorPostProcess => &self.havok_behavior_post_process,
+            ConverterTool::HkxC => &self.hkxc,
+            ConverterTool::HkxConv => &self.hkxconv,
+        }
+    }
+}
+
+/// Every file bundled into this build, by file name
+const BUNDLED_FILES: &[(&str, &[u8])] = &[
+    ("hkxcmd.exe", HKXCMD_EXE),
+    ("hkxc.exe", HKXC_EXE),
+    ("hkxconv.exe", HKXCONV_EXE),
+    ("_SSEtoLE.hko", SSE_TO_LE_HKO),
+    ("HavokBehaviorPostProcess.exe", HAVOK_BEHAVIOR_POST_PROCESS_EXE),
+    ("hctStandAloneFilterManager.exe", HCT_STANDALONE_FILTER_MANAGER_EXE),
+    (HCT_FILTER_MANAGER_DLL_NAME, HCT_FILTER_MANAGER_DLL),
+];
+
+/// Identity of one file bundled into this build
+#[derive(Debug, Clone)]
+pub struct BundledTool {
+    pub file_name: &'static str,
+    pub size: u64,
+    // Lowercase hex SHA-256 of the embedded bytes
+    pub sha256: String,
+}
+
+/// List the bundled tools with their sizes and hashes, so builds can be told apart.
+///
+/// This hashes every embedded binary, so call it once and keep the result.
+pub fn bundled_tools() -> Vec<BundledTool> {
+    BUNDLED_FILES
+        .iter()
+        .map(|(file_name, bytes)| BundledTool {
+            file_name,
+            size: bytes.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(bytes)),
+        })
+        .collect()
+}
+
+/// GitHub API endpoint for the latest published release
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/beefclot/composite-hkxtools/releases/latest";
+
+/// Page listing a release, given its tag
+const RELEASE_PAGE_URL: &str = "https://github.com/beefclot/composite-hkxtools/releases/tag/";
+
+/// A published release of the app
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseInfo {
+    pub version: String,
+    pub url: String,
+}
+
+/// Ask GitHub for the latest release and return it if it's newer than `current_version`.
+///
+/// This blocks on the network, so run it off the UI thread.
+pub fn check_for_update(current_version: &str) -> Result<Option<ReleaseInfo>> {
+    let body = ureq::get(LATEST_RELEASE_URL)
+        .set("Accept", "application/vnd.github+json")
+        .timeout(std::time::Duration::from_secs(10))
+        .call()
+        .context("Failed to fetch the latest release")?
+        .into_string()
+        .context("Failed to read the latest release")?;
+
+    let release = parse_latest_release(&body).context("Latest release has no tag")?;
+    Ok(is_newer_version(&release.version, current_version).then_some(release))
+}
+
+/// Read the release tag out of a GitHub "latest release" JSON response
+pub fn parse_latest_release(body: &str) -> Option<ReleaseInfo> {
+    let release: serde_json::Value = serde_json::from_str(body).ok()?;
+    let tag = release.get("tag_name")?.as_str()?;
+    if tag.is_empty() {
+        return None;
+    }
+
+    Some(ReleaseInfo {
+        version: tag.to_string(),
+        url: format!("{}{}", RELEASE_PAGE_URL, tag),
+    })
+}
+
+/// Compare dotted version numbers such as `v0.2.1`, ignoring a leading `v` and any
+/// `-prerelease` or `+build` suffix
+pub fn is_newer_version(candidate: &str, current: &str) -> bool {
+    fn parse(version: &str) -> Vec<u64> {
+        let version = version.trim().trim_start_matches(['v', 'V']);
+        let core = version.split(['-', '+']).next().unwrap_or_default();
+        core.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+    }
+
+    let mut candidate = parse(candidate);
+    let mut current = parse(current);
+    // Treat missing parts as zero so 0.2 and 0.2.0 are equal
+    let len = candidate.len().max(current.len());
+    candidate.resize(len, 0);
+    current.resize(len, 0);
+    candidate > current
+}
+
+/// Callback receiving each line of output from a running tool
+pub type OutputHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Converts files with one tool to one output format
+#[derive(Clone)]
+pub struct Converter {
+    converter_tool: ConverterTool,
+    output_format: OutputFormat,
+    tool_paths: ToolPaths,
+    skeleton_file: Option<PathBuf>,
+    // Receives live tool output for the file being converted
+    output_handler: Option<OutputHandler>,
+    // Base folder for per-conversion temp directories (system temp folder when unset)
+    temp_base_dir: Option<PathBuf>,
+    // Longest a single tool run may take before it's killed (no limit when unset)
+    timeout: Option<Duration>,
+    // Raw target passed to hkxcmd's `-v:` and hkxc's `--format` instead of the one for the output format
+    version_override: Option<String>,
+    // Reindent XML outputs consistently after the tool writes them
+    reformat_xml: bool,
+    // Runner for the Windows tools on other systems, usually `wine`
+    wine: Option<PathBuf>,
+    // Write to a temporary file next to the output and rename it into place once complete
+    atomic_output: bool,
+}
+
+impl Converter {
+    pub fn new(converter_tool: ConverterTool, output_format: OutputFormat, tool_paths: ToolPaths) -> Self {
+        Self {
+            converter_tool,
+            output_format,
+            tool_paths,
+            skeleton_file: None,
+            output_handler: None,
+            temp_base_dir: None,
+            timeout: None,
+            version_override: None,
+            reformat_xml: false,
+            wine: None,
+            atomic_output: false,
+        }
+    }
+
+    /// Set the skeleton file used for KF conversions
+    pub fn with_skeleton_file(mut self, skeleton_file: Option<PathBuf>) -> Self {
+        self.skeleton_file = skeleton_file;
+        self
+    }
+
+    /// Set the base folder for temporary files (system temp folder when `None`)
+    pub fn with_temp_dir(mut self, temp_base_dir: Option<PathBuf>) -> Self {
+        self.temp_base_dir = temp_base_dir;
+        self
+    }
+
+    /// Kill a tool that runs longer than `timeout`, failing with [`ToolTimedOut`] (no limit when `None`)
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Pass `version` to hkxcmd's `-v:` and hkxc's `--format` instead of the value mapped from the
+    /// output format, for targets without an [`OutputFormat`]. Blank values are ignored; other tools
+    /// don't take a version and ignore it too.
+    pub fn with_version_override(mut self, version: Option<String>) -> Self {
+        self.version_override = version
+            .map(|version| version.trim().to_string())
+            .filter(|version| !version.is_empty());
+        self
+    }
+
+    /// Reindent XML outputs with [`reformat_xml_file`], so every tool's XML diffs cleanly
+    pub fn with_reformat_xml(mut self, reformat_xml: bool) -> Self {
+        self.reformat_xml = reformat_xml;
+        self
+    }
+
+    /// Run the tools through Wine (or a compatible runner) at `wine` instead of directly. The tools
+    /// are Windows programs, so this is how they run on Linux and macOS; it's ignored on Windows.
+    pub fn with_wine(mut self, wine: Option<PathBuf>) -> Self {
+        self.wine = wine.filter(|_| !cfg!(windows));
+        self
+    }
+
+    /// Have the tool write a temporary file next to the output, renamed over the output only once
+    /// the conversion succeeded and deleted otherwise, so a crash or failed conversion never
+    /// leaves a truncated output behind. See [`partial_output_path`].
+    pub fn with_atomic_output(mut self, atomic_output: bool) -> Self {
+        self.atomic_output = atomic_output;
+        self
+    }
+
+    /// Receive each line the tool prints while it runs
+    pub fn with_output_handler(mut self, handler: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.output_handler = Some(Arc::new(handler));
+        self
+    }
+
+    pub fn converter_tool(&self) -> ConverterTool {
+        self.converter_tool
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
+    /// Convert `output` back to the format of `input` in a temp folder and compare it with `input`
+    pub async fn verify_round_trip(&self, input: &Path, output: &Path) -> Result<VerifyOutcome> {
+        let Some(original_format) = OutputFormat::detect_from_file(input) else {
+            return Ok(VerifyOutcome::Skipped);
+        };
+        // Same-format conversions and formats this tool can't write back can't be round-tripped
+        if original_format == self.output_format
+            || !self.converter_tool.available_output_formats().contains(&original_format)
+            || (original_format.requires_skeleton() && self.skeleton_file.is_none())
+        {
+            return Ok(VerifyOutcome::Skipped);
+        }
+
+        let temp_dir = create_temp_dir("hkx_verify_", self.temp_base_dir.as_deref())
+            .context("Failed to create temporary directory for round-trip verification")?;
+        let round_trip_path = temp_dir
+            .path()
+            .join(format!("round_trip.{}", original_format.extension()));
+
+        // The override targets the forward conversion only
+        let reverse = Converter {
+            output_format: original_format,
+            version_override: None,
+            ..self.clone()
+        };
+        reverse
+            .convert(output, &round_trip_path)
+            .await
+            .context("Round-trip conversion failed")?;
+
+        let original = fs::read(input).context("Failed to read input file for verification")?;
+        let round_trip = fs::read(&round_trip_path).context("Failed to read round-trip output")?;
+
+        let matches = if original_format == OutputFormat::Xml {
+            // Tools format XML differently, so compare the content line by line ignoring whitespace
+            Self::normalize_xml(&original) == Self::normalize_xml(&round_trip)
+        } else {
+            original == round_trip
+        };
+
+        Ok(if matches { VerifyOutcome::Matched } else { VerifyOutcome::Mismatched })
+    }
+
+    /// Normalize XML text for comparison by trimming lines and dropping blank ones
+    fn normalize_xml(bytes: &[u8]) -> Vec<String> {
+        String::from_utf8_lossy(bytes)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    /// Run a command with piped output, forwarding each line as it is produced
+    async fn execute_command(&self, command: &mut Command) -> Result<std::process::Output> {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped()).kill_on_drop(true);
+        let mut child = command.spawn().map_err(|e| match &self.wine {
+            Some(wine) => anyhow::anyhow!("Failed to run {:?}: {}", wine, e),
+            // A raw "exec format error" doesn't say why a Windows program won't start here
+            None if !cfg!(windows) => anyhow::anyhow!("{}. The conversion tools are Windows programs and require Windows or Wine", e),
+            None => e.into(),
+        })?;
+
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let handler = self.output_handler.as_ref();
+
+        let run = async {
+            tokio::join!(
+                Self::read_output_lines(stdout, handler),
+                Self::read_output_lines(stderr, handler),
+                child.wait(),
+            )
+        };
+        let (stdout, stderr, status) = match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, run).await {
+                Ok(finished) => finished,
+                Err(_) => {
+                    // A hung tool would otherwise hold its files open for the rest of the session
+                    let _ = child.kill().await;
+                    return Err(ToolTimedOut(timeout).into());
+                }
+            },
+            None => run.await,
+        };
+
+        Ok(std::process::Output {
+            status: status?,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Read a child output stream line by line, returning everything that was read
+    async fn read_output_lines<R: AsyncRead + Unpin>(
+        stream: Option<R>,
+        handler: Option<&OutputHandler>,
+    ) -> Vec<u8> {
+        let mut collected = Vec::new();
+        let Some(stream) = stream else {
+            return collected;
+        };
+
+        let mut reader = BufReader::new(stream);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if let Some(handler) = handler {
+                        handler(&String::from_utf8_lossy(&line));
+                    }
+                    collected.extend_from_slice(&line);
+                }
+            }
+        }
+        collected
+    }
+
+    /// Convert `input` to `output` with this converter's tool and output format.
+    ///
+    /// Paths may contain spaces and non-ASCII characters. Each path is passed to the tool as a
+    /// single argument, and tools that can't open non-ASCII paths work on ASCII-named copies.
+    pub async fn convert(&self, input: &Path, output: &Path) -> Result<()> {
+        let input = ensure_absolute_path(input);
+        let output = ensure_absolute_path(output);
+        if !self.atomic_output {
+            return self.write_output(&input, &output).await;
+        }
+
+        let partial = partial_output_path(&output);
+        // A leftover from a crashed run would otherwise be mistaken for this run's output
+        let _ = fs::remove_file(&partial);
+        let result = match self.write_output(&input, &partial).await {
+            Ok(()) => fs::rename(&partial, &output)
+                .with_context(|| format!("Failed to move the converted file {:?} to {:?}", partial, output)),
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+        result
+    }
+
+    /// Convert `input`, with the tool writing straight to `output`
+    async fn write_output(&self, input: &Path, output: &Path) -> Result<()> {
+        let skeleton = self.skeleton_file.as_deref().map(ensure_absolute_path);
+        let has_non_ascii_path = [Some(input), Some(output), skeleton.as_deref()]
+            .into_iter()
+            .flatten()
+            .any(|path| !is_ascii_path(path));
+
+        if has_non_ascii_path && !self.converter_tool.supports_unicode_paths() {
+            self.convert_via_ascii_temp_dir(input, output).await?;
+        } else {
+            self.run_tool(input, output).await?;
+        }
+
+        if self.reformat_xml && self.output_format == OutputFormat::Xml {
+            reformat_xml_file(output).context("Failed to reformat XML output")?;
+        }
+        Ok(())
+    }
+
+    /// Run the tool on ASCII-named copies of the files in a temp folder, then move the result to `output`
+    async fn convert_via_ascii_temp_dir(&self, input: &Path, output: &Path) -> Result<()> {
+        let temp_dir = create_temp_dir("hkx_ascii_", self.temp_base_dir.as_deref())
+            .context("Failed to create temporary directory for non-ASCII paths")?;
+        if !is_ascii_path(temp_dir.path()) {
+            return Err(anyhow::anyhow!(
+                "{} can't open paths with non-ASCII characters, and the temporary folder {:?} contains some too. Choose a temporary folder with an ASCII-only path.",
+                self.converter_tool.label(),
+                temp_dir.path()
+            ));
+        }
+        log_debug!("Converting {:?} via ASCII temp dir {:?}", input, temp_dir.path());
+
+        let staged_input = temp_dir.path().join(ascii_file_name("input", input));
+        fs::copy(input, &staged_input).context("Failed to copy input file to temporary directory")?;
+        make_writable(&staged_input)?;
+
+        let mut staged = self.clone();
+        if let Some(skeleton) = &self.skeleton_file {
+            let staged_skeleton = temp_dir.path().join(ascii_file_name("skeleton", skeleton));
+            fs::copy(skeleton, &staged_skeleton).context("Failed to copy skeleton file to temporary directory")?;
+            make_writable(&staged_skeleton)?;
+            staged.skeleton_file = Some(staged_skeleton);
+        }
+
+        let staged_output = temp_dir.path().join(ascii_file_name("output", output));
+        staged.run_tool(&staged_input, &staged_output).await?;
+
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent).context("Failed to create output directory")?;
+        }
+        // rename() fails across drives, so fall back to copying
+        if fs::rename(&staged_output, output).is_err() {
+            fs::copy(&staged_output, output).context("Failed to copy converted file to output location")?;
+        }
+        Ok(())
+    }
+
+    /// Make sure HCT can load hctFilterManager.dll, copying it into `work_dir` if it isn't next to the executable
+    fn prepare_hct_filter_manager(&self, work_dir: &Path) -> Result<()> {
+        let executable = &self.tool_paths.hct_standalone_filter_manager;
+        let dll = &self.tool_paths.hct_filter_manager_dll;
+        if !executable.is_file() {
+            return Err(anyhow::anyhow!("HCT executable is missing: {:?}", executable));
+        }
+        if !dll.is_file() {
+            return Err(anyhow::anyhow!(
+                "{} is missing from {:?}, and HCT can't convert without it. Antivirus software may have removed it; add an exclusion and restart the app.",
+                HCT_FILTER_MANAGER_DLL_NAME,
+                dll
+            ));
+        }
+
+        // With safe DLL search mode (the default), Windows looks in the executable's folder, then the
+        // system and Windows folders, and only then in the working directory. The copy in the working
+        // directory is therefore only found when no other hctFilterManager.dll is installed there.
+        let next_to_executable = executable.parent().map(|dir| dir.join(HCT_FILTER_MANAGER_DLL_NAME));
+        if next_to_executable.is_some_and(|path| path.is_file()) {
+            return Ok(());
+        }
+        fs::copy(dll, work_dir.join(HCT_FILTER_MANAGER_DLL_NAME))
+            .with_context(|| format!("Failed to copy {} into the HCT working directory", HCT_FILTER_MANAGER_DLL_NAME))?;
+        Ok(())
+    }
+
+    /// Command running the tool's executable, through Wine when one is set
+    fn tool_command(&self) -> Command {
+        let executable = self.tool_paths.executable(self.converter_tool);
+        match &self.wine {
+            Some(wine) => {
+                let mut command = Command::new(wine);
+                command.arg(executable);
+                command
+            }
+            None => Command::new(executable),
+        }
+    }
+
+    /// Run the tool with a help argument to check that it starts on this system, returning its exit
+    /// code and output. Fails when it can't be started, can't load its DLLs or times out; a non-zero
+    /// exit code on its own is fine, since some tools exit with one after printing their usage.
+    pub async fn check_tool(&self) -> Result<String> {
+        let mut command = self.tool_command();
+        command.arg(match self.converter_tool {
+            ConverterTool::HkxCmd => "help",
+            _ => "--help",
+        });
+        let output = self.execute_command(&mut command).await?;
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let text = text.trim();
+        match output.status.code() {
+            Some(STATUS_DLL_NOT_FOUND) => Err(anyhow::anyhow!(
+                "{} couldn't load one of its DLLs. Install the Visual C++ Redistributable (x86 and x64).",
+                self.converter_tool.label()
+            )),
+            Some(STATUS_INVALID_IMAGE_FORMAT) => Err(anyhow::anyhow!(
+                "{} or one of its DLLs is damaged or for the wrong architecture. Reinstall the Visual C++ Redistributable.",
+                self.converter_tool.label()
+            )),
+            code => Ok(format!(
+                "Exit code {}{}{}",
+                code.map_or("none".to_string(), |code| code.to_string()),
+                if text.is_empty() { "" } else { "\n" },
+                text
+            )),
+        }
+    }
+
+    /// The commands that convert `input` to `output`, each program first, for running them by hand
+    /// from a Windows batch file (`windows`) or a shell script.
+    ///
+    /// The tool is run on its own: ASCII-named copies, atomic writes and XML reformatting aren't
+    /// included. HavokBehaviorPostProcess is preceded by copying `input` to `output`, which it edits
+    /// in place, and HCT is followed by moving the `filename.hkx` it writes next to the .hko to `output`.
+    pub fn command_lines(&self, input: &Path, output: &Path, windows: bool) -> Result<Vec<Vec<OsString>>> {
+        let input = ensure_absolute_path(input);
+        let output = ensure_absolute_path(output);
+        let (copy, mv): (&[&str], &[&str]) = if windows { (&["copy", "/Y"], &["move", "/Y"]) } else { (&["cp"], &["mv"]) };
+        let file_command = |command: &[&str], from: &Path, to: &Path| {
+            let mut line: Vec<OsString> = command.iter().map(OsString::from).collect();
+            line.push(from.into());
+            line.push(to.into());
+            line
+        };
+
+        let mut tool_line = Vec::new();
+        if let Some(wine) = &self.wine {
+            tool_line.push(wine.clone().into_os_string());
+        }
+        tool_line.push(self.tool_paths.executable(self.converter_tool).into());
+        tool_line.extend(self.tool_arguments(&input, &output, &self.tool_paths.sse_to_le_hko)?);
+
+        Ok(match self.converter_tool {
+            ConverterTool::HavokBehaviorPostProcess => vec![file_command(copy, &input, &output), tool_line],
+            ConverterTool::Hct => {
+                let hct_output = self.tool_paths.sse_to_le_hko.with_file_name("filename.hkx");
+                vec![tool_line, file_command(mv, &hct_output, &output)]
+            }
+            _ => vec![tool_line],
+        })
+    }
+
+    /// Arguments passed to the tool's executable for one conversion, with `hko` as HCT's filter preset
+    fn tool_arguments(&self, input_absolute: &Path, output_absolute: &Path, hko: &Path) -> Result<Vec<OsString>> {
+        let mut args: Vec<OsString> = Vec::new();
+        let skeleton_absolute = self.skeleton_file.as_deref().map(ensure_absolute_path);
+        let input_ext = input_absolute.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+
+        // Set the command based on output format
+        if self.output_format == OutputFormat::Kf {
+            if self.converter_tool != ConverterTool::Hct {
+                // For KF output, the direction depends on the input file extension
+                if input_ext == "kf" {
+                    args.push("ConvertKF".into()); // KF -> HKX
+                } else {
+                    args.push("exportkf".into()); // HKX -> KF
+                }
+            }
+            // HCT doesn't support KF conversion
+        } else if self.converter_tool != ConverterTool::Hct && self.converter_tool != ConverterTool::HavokBehaviorPostProcess {
+            // HCT and HavokBehaviorPostProcess don't need a command argument
+            args.push("convert".into());
+        }
+
+        // Add arguments based on tool and output format
+        match self.converter_tool {
+            ConverterTool::HkxCmd => {
+                if self.output_format == OutputFormat::Kf {
+                    // KF conversion
+                    if let Some(skeleton) = skeleton_absolute {
+                        args.push(skeleton.into_os_string());
+                    }
+                    args.push(input_absolute.into());
+                    args.push(output_absolute.into());
+                    // KF -> HKX needs a version argument, HKX -> KF doesn't
+                    if input_ext == "kf" {
+                        args.push(format!("-v:{}", self.version_override.as_deref().unwrap_or("AMD64")).into());
+                    }
+                } else {
+                    // Regular HKX/XML conversion
+                    args.push("-i".into());
+                    args.push(input_absolute.into());
+                    args.push("-o".into());
+                    args.push(output_absolute.into());
+                    args.push(format!("-v:{}", self.version_override.as_deref().unwrap_or(match self.output_format {
+                        OutputFormat::Xml => "XML",
+                        OutputFormat::SkyrimLE => "WIN32",
+                        OutputFormat::SkyrimSE => "AMD64",
+                        OutputFormat::Kf => "AMD64", // This shouldn't happen in regular conversion
+                    })).into());
+                }
+            }
+            ConverterTool::HkxC => {
+                if self.output_format == OutputFormat::Kf {
+                    return Err(anyhow::anyhow!("hkxc does not support KF conversion"));
+                }
+                args.push("--input".into());
+                args.push(input_absolute.into());
+                args.push("--output".into());
+                args.push(output_absolute.into());
+                args.push("--format".into());
+                args.push(self.version_override.as_deref().unwrap_or(match self.output_format {
+                    OutputFormat::Xml => "xml",
+                    OutputFormat::SkyrimLE => "win32",
+                    OutputFormat::SkyrimSE => "amd64",
+                    OutputFormat::Kf => "amd64", // This shouldn't happen
+                }).into());
+            }
+            ConverterTool::HkxConv => {
+                // hkxconv's `-v hkx` always writes SE (64-bit) HKX and it has no LE target, so LE
+                // is refused rather than silently written as SE
+                let target = match self.output_format {
+                    OutputFormat::Xml => "xml",
+                    OutputFormat::SkyrimSE => "hkx",
+                    OutputFormat::SkyrimLE => return Err(anyhow::anyhow!("hkxconv can only write SE HKX, not LE")),
+                    OutputFormat::Kf => return Err(anyhow::anyhow!("hkxconv does not support KF conversion")),
+                };
+                args.push(input_absolute.into());
+                args.push(output_absolute.into());
+                args.push("-v".into());
+                args.push(target.into());
+            }
+            ConverterTool::Hct => {
+                if self.output_format == OutputFormat::Kf {
+                    return Err(anyhow::anyhow!("HCT does not support KF conversion"));
+                }
+                // HCT only supports SSE to LE conversion, with the settings in the .hko preset
+                args.push(input_absolute.into());
+                args.push("-s".into());
+                args.push(hko.into());
+            }
+            ConverterTool::HavokBehaviorPostProcess => {
+                if self.output_format == OutputFormat::Kf {
+                    return Err(anyhow::anyhow!("HavokBehaviorPostProcess does not support KF conversion"));
+                }
+                // It takes exactly one file pair per run and has no directory or list argument,
+                // so batches run one process per file, in parallel up to the worker limit.
+                // Both input and output are the output file, which is modified in place.
+                args.push("--platformAmd64".into());
+                args.push(output_absolute.into());
+                args.push(output_absolute.into());
+            }
+        }
+        Ok(args)
+    }
+
+    /// Run the tool itself with `input` and `output` as arguments
+    async fn run_tool(&self, input: &Path, output: &Path) -> Result<()> {
+        let mut command = self.tool_command();
+        
+        let tool_name = match self.converter_tool {
+            ConverterTool::HkxCmd => "hkxcmd",
+            ConverterTool::Hct => "hctStandAloneFilterManager",
+            ConverterTool::HavokBehaviorPostProcess => "HavokBehaviorPostProcess",
+            ConverterTool::HkxC => "hkxc",
+            ConverterTool::HkxConv => "hkxconv",
+        };
+
+        // Convert paths to absolute paths to avoid issues with paths starting with '-'
+        // Use absolute paths but avoid canonicalize() which can add \\?\ prefix on Windows
+        let input_absolute = ensure_absolute_path(input);
+        let output_absolute = ensure_absolute_path(output);
+
+        // HCT runs in a work dir holding a copy of the .hko, so it's passed by file name
+        let hko_filename = self.tool_paths.sse_to_le_hko.file_name().unwrap_or_default();
+        command.args(self.tool_arguments(&input_absolute, &output_absolute, Path::new(hko_filename))?);
+
+        match self.converter_tool {
+            ConverterTool::Hct => {
+                // For HCT, create a unique temporary directory for this conversion
+                let temp_dir = create_temp_dir("hct_conversion_", self.temp_base_dir.as_deref())
+                    .context("Failed to create temporary directory for HCT conversion")?;
+                let _work_dir_claim = HctWorkDirClaim::new(temp_dir.path())?;
+                self.prepare_hct_filter_manager(temp_dir.path())?;
+                
+                // HCT only supports SSE to LE conversion
+                let source_hko_path = &self.tool_paths.sse_to_le_hko;
+                
+                // Copy the .hko file to the temporary directory
+                let temp_hko_path = temp_dir.path().join(hko_filename);
+                fs::copy(source_hko_path, &temp_hko_path)
+                    .context("Failed to copy .hko file to temporary directory")?;
+                
+                log_debug!("HCT temp dir: {:?}, using .hko: {:?}", temp_dir.path(), hko_filename);
+                
+                // Set working directory to temp directory, where the relative .hko filename is found
+                command.current_dir(temp_dir.path());
+                
+                // Execute the command
+                let cmd_output = self.execute_command(&mut command).await.context("Failed to execute HCT converter tool")?;
+                let stderr = String::from_utf8_lossy(&cmd_output.stderr);
+
+                if cmd_output.status.code() == Some(STATUS_DLL_NOT_FOUND) {
+                    return Err(anyhow::anyhow!(
+                        "{} couldn't load {} or one of its dependencies from {:?}",
+                        tool_name,
+                        HCT_FILTER_MANAGER_DLL_NAME,
+                        self.tool_paths.hct_filter_manager_dll
+                    ));
+                }
+                if !cmd_output.status.success() {
+                    return Err(anyhow::anyhow!("{} failed: {}", tool_name, stderr));
+                }
+                
+                // HCT creates "filename.hkx" in the same directory as the .hko file
+                let hct_output_file = temp_dir.path().join("filename.hkx");
+                
+                // Debug: List all files in temp directory
+                log_debug!("Temp directory contents:");
+                if let Ok(entries) = fs::read_dir(temp_dir.path()) {
+                    for entry in entries.flatten() {
+                        log_debug!("  {:?}", entry.path());
+                    }
+                } else {
+                    log_debug!("  Failed to read temp directory");
+                }
+                
+                if !hct_output_file.exists() {
+                    return Err(anyhow::anyhow!("HCT did not produce expected output file: {:?}", hct_output_file));
+                }
+                
+                log_debug!("HCT output file exists: {:?}", hct_output_file);
+                log_debug!("Target output path: {:?}", output_absolute);
+                
+                // Create output directory if it doesn't exist
+                if let Some(parent) = output_absolute.parent() {
+                    log_debug!("Creating output directory: {:?}", parent);
+                    fs::create_dir_all(parent).context("Failed to create output directory")?;
+                }
+                
+                // Check if target file already exists and remove it if necessary
+                if output_absolute.exists() {
+                    log_debug!("Target file already exists, removing: {:?}", output_absolute);
+                    fs::remove_file(&output_absolute).context("Failed to remove existing target file")?;
+                }
+                
+                // Move the HCT output file directly to the final location
+                // The output_absolute path already includes any suffix/extension modifications
+                match fs::rename(&hct_output_file, &output_absolute) {
+                    Ok(_) => {
+                        log_debug!("Successfully moved HCT output to: {:?}", output_absolute);
+                    }
+                    Err(e) => {
+                        // If rename fails, try copy + delete as fallback
+                        log_debug!("Rename failed ({}), trying copy + delete fallback", e);
+                        fs::copy(&hct_output_file, &output_absolute)
+                            .context("Failed to copy HCT output file to final location")?;
+                        fs::remove_file(&hct_output_file)
+                            .context("Failed to remove temporary HCT output file after copy")?;
+                        log_debug!("Successfully copied HCT output to: {:?}", output_absolute);
+                    }
+                }
+                
+                log_debug!("HCT conversion complete: {:?} -> {:?}", input_absolute, output_absolute);
+                
+                // temp_dir will be automatically cleaned up when it goes out of scope
+                return Ok(());
+            }
+            ConverterTool::HavokBehaviorPostProcess => {
+                // HavokBehaviorPostProcess only supports HKX input files and SSE output
+                if input_absolute.extension().is_none_or(|ext| ext != "hkx") {
+                    return Err(anyhow::anyhow!("HavokBehaviorPostProcess requires an HKX input file."));
+                }
+                
+                // HavokBehaviorPostProcess modifies files in-place, so we need to copy the input to output first
+                log_debug!("Input path: {:?}", input_absolute);
+                log_debug!("Output path: {:?}", output_absolute);
+                log_debug!("Input exists: {}", input_absolute.exists());
+                log_debug!("Output parent exists: {}", output_absolute.parent().is_some_and(|p| p.exists()));
+                log_debug!("Copying input file to output location: {:?} -> {:?}", input_absolute, output_absolute);
+                
+                // Check if input and output are the same
+                if input_absolute == output_absolute {
+                    return Err(anyhow::anyhow!("Input and output paths are the same: {:?}", input_absolute));
+                }
+                
+                // Create output directory if it doesn't exist
+                if let Some(parent) = output_absolute.parent() {
+                    log_debug!("Creating output directory: {:?}", parent);
+                    fs::create_dir_all(parent).context("Failed to create output directory")?;
+                }
+                
+                // Copy input file to output location
+                match fs::copy(&input_absolute, &output_absolute) {
+                    Ok(bytes_copied) => {
+                        log_debug!("Successfully copied {} bytes", bytes_copied);
+                    }
+                    Err(e) => {
+                        log_debug!("Copy failed with error: {:?}", e);
+                        return Err(anyhow::anyhow!("Failed to copy input file to output location: {}", e));
+                    }
+                }
+                // A read-only source makes a read-only copy, which the tool can't modify in place
+                make_writable(&output_absolute)?;
+                
+                // Check file size before processing
+                let file_size_before = fs::metadata(&output_absolute)
+                    .context("Failed to get file metadata before processing")?
+                    .len();
+                log_debug!("File size before HavokBehaviorPostProcess: {} bytes", file_size_before);
+            }
+            ConverterTool::HkxCmd | ConverterTool::HkxC | ConverterTool::HkxConv => {}
+        }
+
+        // Print the command being executed for debugging
+        log_debug!("EXECUTING COMMAND: {:?} with input: {:?}, output: {:?}", tool_name, input_absolute, output_absolute);
+        
+        // For HavokBehaviorPostProcess, print the exact command with arguments
+        if self.converter_tool == ConverterTool::HavokBehaviorPostProcess {
+            log_debug!("HavokBehaviorPostProcess command: {:?}", command);
+        }
+
+        let output = self.execute_command(&mut command).await.context("Failed to execute converter tool")?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        
+        // For HavokBehaviorPostProcess, print all output for debugging
+        if self.converter_tool == ConverterTool::HavokBehaviorPostProcess {
+            log_debug!("HavokBehaviorPostProcess exit code: {:?}", output.status.code());
+            log_debug!("HavokBehaviorPostProcess stdout: {}", stdout);
+            log_debug!("HavokBehaviorPostProcess stderr: {}", stderr);
+        }
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("{} failed with exit code {:?}: stdout: {} stderr: {}", 
+                tool_name, output.status.code(), stdout, stderr));
+        }
+        
+        // For HavokBehaviorPostProcess, check if the file size changed
+        if self.converter_tool == ConverterTool::HavokBehaviorPostProcess {
+            let file_size_after = fs::metadata(&output_absolute)
+                .context("Failed to get file metadata after processing")?
+                .len();
+            log_debug!("File size after HavokBehaviorPostProcess: {} bytes", file_size_after);
+            
+            if file_size_after == fs::metadata(&input_absolute)
+                .context("Failed to get input file metadata")?
+                .len() {
+                log_debug!("WARNING: Output file size is the same as input file size - conversion may not have worked");
+            } else {
+                log_debug!("SUCCESS: File size changed, conversion appears to have worked");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Where and under which names a batch writes its outputs
+#[derive(Debug, Clone, Copy)]
+pub struct OutputLayout<'a> {
+    /// Folder the outputs are written to
+    pub output_folder: &'a Path,
+    /// Added to each file name after `suffix_separator`, nothing when empty
+    pub output_suffix: &'a str,
+    pub suffix_separator: &'a str,
+    /// Extensions used instead of a format's default
+    pub custom_extensions: &'a HashMap<OutputFormat, String>,
+    /// Folder whose subfolders are recreated under `output_folder`
+    pub base_folder: Option<&'a Path>,
+    /// Write every file directly into `output_folder`, ignoring `base_folder`
+    pub flatten_output: bool,
+    /// Applied to the file name only, never to the folders
+    pub filename_case: FilenameCase,
+}
+
+impl<'a> OutputLayout<'a> {
+    /// Files written directly into `output_folder` under their own names, with the default suffix
+    /// separator
+    pub fn new(output_folder: &'a Path, custom_extensions: &'a HashMap<OutputFormat, String>) -> Self {
+        Self {
+            output_folder,
+            output_suffix: "",
+            suffix_separator: DEFAULT_SUFFIX_SEPARATOR,
+            custom_extensions,
+            base_folder: None,
+            flatten_output: false,
+            filename_case: FilenameCase::default(),
+        }
+    }
+}
+
+/// Calculate where a converted file is written.
+///
+/// The file is named `<stem>.<ext>`, or `<stem><separator><suffix>.<ext>` when the layout has an
+/// output suffix. The separator is the layout's, or [`DEFAULT_SUFFIX_SEPARATOR`] when it fails
+/// [`validate_suffix_separator`]. The extension is the custom one set for `output_format` if any
+/// (a leading `.` is ignored), otherwise the format's default, which is also used when the custom
+/// one fails [`validate_custom_extension`].
+///
+/// With a base folder, the input's folder relative to it is recreated under the output folder, so
+/// `base/a/b/x.hkx` becomes `output_folder/a/b/x.xml`. The file goes directly into the output
+/// folder when there is no base folder, when the input is outside the base folder, or when
+/// flattening. Relative paths are resolved against the current directory before comparing, and on
+/// Windows the base folder is matched case-insensitively.
+///
+/// Returns `None` if the input has no file name or it isn't valid UTF-8.
+pub fn get_output_path(input_path: &Path, output_format: OutputFormat, layout: &OutputLayout) -> Option<PathBuf> {
+    let file_name = input_path.file_stem()?.to_str()?;
+    let extension = output_extension(output_format, layout.custom_extensions);
+
+    // Keep the folder structure below the base folder, unless flattening
+    let relative_path = match layout.base_folder {
+        Some(base_folder) if !layout.flatten_output => relative_input_dir(input_path, base_folder),
+        _ => PathBuf::new(),
+    };
+
+    let output_name = format!("{}{}.{}", file_name, joined_suffix(layout.output_suffix, layout.suffix_separator), extension);
+
+    Some(layout.output_folder.join(relative_path).join(layout.filename_case.apply(&output_name)))
+}
+
+/// Extension of an output file: the custom one set for `output_format`, unless it could change the
+/// folder or make an unwritable name, otherwise the format's default
+fn output_extension(output_format: OutputFormat, custom_extensions: &HashMap<OutputFormat, String>) -> &str {
+    custom_extensions
+        .get(&output_format)
+        .map(|ext| ext.trim_start_matches('.'))
+        .filter(|ext| !ext.is_empty() && validate_custom_extension(ext).is_ok())
+        .unwrap_or_else(|| output_format.extension())
+}
+
+/// Separator put between the file name and the output suffix unless another one is set
+pub const DEFAULT_SUFFIX_SEPARATOR: &str = "_";
+
+/// Check that a suffix separator can only end up in the file name. An empty separator is allowed and
+/// joins the suffix directly.
+pub fn validate_suffix_separator(separator: &str) -> Result<()> {
+    if let Some(c) = separator.chars().find(|c| INVALID_FILE_NAME_CHARS.contains(c) || c.is_control()) {
+        return Err(anyhow::anyhow!("Suffix separator can't contain {:?}", c));
+    }
+    Ok(())
+}
+
+/// `output_suffix` with its separator in front, or nothing when there's no suffix
+fn joined_suffix(output_suffix: &str, suffix_separator: &str) -> String {
+    if output_suffix.is_empty() {
+        return String::new();
+    }
+    let separator = if validate_suffix_separator(suffix_separator).is_ok() { suffix_separator } else { DEFAULT_SUFFIX_SEPARATOR };
+    format!("{}{}", separator, output_suffix)
+}
+
+/// Folder of `input_path` relative to `base_folder`, or empty when it's outside it
+fn relative_input_dir(input_path: &Path, base_folder: &Path) -> PathBuf {
+    let input_dir = ensure_absolute_path(input_path.parent().unwrap_or(Path::new("")));
+    relative_to_base(&input_dir, &ensure_absolute_path(base_folder)).unwrap_or_default()
+}
+
+/// Placeholders an output template can use, without their braces
+pub const OUTPUT_TEMPLATE_PLACEHOLDERS: &[&str] = &["stem", "ext", "format", "tool", "reldir", "suffix"];
+
+/// Output template equivalent to the default layout of [`get_output_path`]
+pub const DEFAULT_OUTPUT_TEMPLATE: &str = "{reldir}/{stem}{suffix}.{ext}";
+
+/// Replace each `{name}` in `template` with `value(name)`, failing on unknown names and unbalanced braces
+fn render_output_template(template: &str, value: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        rendered.push_str(&rest[..start]);
+        if rest[start..].starts_with('}') {
+            return Err(anyhow::anyhow!("Output template has a }} without a {{"));
+        }
+        let end = start + rest[start..].find('}').context("Output template has a { without a }")?;
+        let name = &rest[start + 1..end];
+        rendered.push_str(&value(name).ok_or_else(|| anyhow::anyhow!("Unknown placeholder {{{}}} in output template", name))?);
+        rest = &rest[end + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Check that an output template only uses known placeholders and can only produce file paths
+/// inside the output folder
+pub fn validate_output_template(template: &str) -> Result<()> {
+    // Stand-in values, so the literal text around the placeholders is what gets checked
+    let rendered = render_output_template(template, |name| OUTPUT_TEMPLATE_PLACEHOLDERS.contains(&name).then(|| "x".to_string()))?;
+    if !template.contains("{stem}") {
+        return Err(anyhow::anyhow!("Output template must contain {{stem}}, or every file gets the same name"));
+    }
+    if let Some(c) = rendered.chars().find(|c| (INVALID_FILE_NAME_CHARS.contains(c) && !matches!(c, '/' | '\\')) || c.is_control()) {
+        return Err(anyhow::anyhow!("Output template can't contain {:?}", c));
+    }
+    if rendered.starts_with(['/', '\\']) || rendered.split(['/', '\\']).any(|part| part.trim() == "..") {
+        return Err(anyhow::anyhow!("Output template must stay inside the output folder"));
+    }
+    if rendered.ends_with(['/', '\\']) {
+        return Err(anyhow::anyhow!("Output template must end with a file name"));
+    }
+    Ok(())
+}
+
+/// Calculate where a converted file is written when the layout comes from an output `template`,
+/// relative to the output folder, such as `{format}/{reldir}/{stem}{suffix}.{ext}`.
+///
+/// `{stem}` is the input's file name without its extension, `{ext}` the output extension as in
+/// [`get_output_path`], `{format}` the output format's folder name, `{tool}` the converter tool,
+/// `{reldir}` the input's folder relative to the base folder (empty without one), and `{suffix}`
+/// the separator and suffix as in [`get_output_path`], or nothing without a suffix. Empty folders
+/// left by blank placeholders are dropped. The template decides the folders, so the layout's
+/// flattening isn't used.
+///
+/// Returns `None` if the input has no UTF-8 file name or the template fails
+/// [`validate_output_template`].
+pub fn get_templated_output_path(
+    input_path: &Path,
+    template: &str,
+    output_format: OutputFormat,
+    converter_tool: ConverterTool,
+    layout: &OutputLayout,
+) -> Option<PathBuf> {
+    validate_output_template(template).ok()?;
+    let stem = input_path.file_stem()?.to_str()?;
+    let relative_dir = layout.base_folder.map(|base_folder| relative_input_dir(input_path, base_folder)).unwrap_or_default();
+    let rendered = render_output_template(template, |name| {
+        Some(match name {
+            "stem" => stem.to_string(),
+            "ext" => output_extension(output_format, layout.custom_extensions).to_string(),
+            "format" => output_format.folder_name().to_string(),
+            "tool" => converter_tool.label().to_string(),
+            "reldir" => relative_dir.to_string_lossy().to_string(),
+            "suffix" => joined_suffix(layout.output_suffix, layout.suffix_separator),
+            _ => return None,
+        })
+    })
+    .ok()?;
+
+    let mut parts: Vec<&str> = rendered.split(['/', '\\']).filter(|part| !part.is_empty() && *part != ".").collect();
+    let file_name = layout.filename_case.apply(parts.pop()?);
+    let mut output_path = layout.output_folder.to_path_buf();
+    output_path.extend(parts);
+    Some(output_path.join(file_name))
+}
+
+/// Characters Windows doesn't allow in file names
+const INVALID_FILE_NAME_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Check that a custom extension (with or without its leading `.`) can only end up in the file name
+pub fn validate_custom_extension(extension: &str) -> Result<()> {
+    let extension = extension.strip_prefix('.').unwrap_or(extension);
+    if extension.contains(['/', '\\']) {
+        return Err(anyhow::anyhow!("Extension can't contain / or \\"));
+    }
+    if let Some(c) = extension.chars().find(|c| INVALID_FILE_NAME_CHARS.contains(c) || c.is_control()) {
+        return Err(anyhow::anyhow!("Extension can't contain {:?}", c));
+    }
+    if extension.contains(char::is_whitespace) {
+        return Err(anyhow::anyhow!("Extension can't contain spaces"));
+    }
+    if extension.ends_with('.') {
+        return Err(anyhow::anyhow!("Extension can't end with a dot"));
+    }
+    Ok(())
+}
+
+/// Get `path` relative to `base`, or `None` if `path` isn't inside `base`
+fn relative_to_base(path: &Path, base: &Path) -> Option<PathBuf> {
+    let mut components = path.components();
+    for base_component in base.components() {
+        if !components_match(base_component, components.next()?) {
+            return None;
+        }
+    }
+    Some(components.as_path().to_path_buf())
+}
+
+/// Windows paths are case-insensitive, so `C:\Mods` and `c:\mods` are the same folder
+#[cfg(windows)]
+fn components_match(a: Component, b: Component) -> bool {
+    a.as_os_str().to_string_lossy().to_lowercase() == b.as_os_str().to_string_lossy().to_lowercase()
+}
+
+#[cfg(not(windows))]
+fn components_match(a: Component, b: Component) -> bool {
+    a == b
+}
+
+/// Path named by a line of a file list or dropped text, which may have been written on another
+/// system. Both `/` and `\\` are taken as separators, a relative path is joined to `relative_to`, and
+/// on Windows the existing part of the path is spelled as it is on disk, so the same file listed
+/// with different case isn't added twice.
+pub fn normalize_listed_path(text: &str, relative_to: &Path) -> PathBuf {
+    let path = PathBuf::from(text.replace(['/', '\\'], std::path::MAIN_SEPARATOR_STR));
+    let path = if path.is_absolute() { path } else { relative_to.join(path) };
+    disk_case(&path)
+}
+
+/// `path` with each component that exists renamed to its on-disk case
+#[cfg(windows)]
+fn disk_case(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        if !matches!(component, Component::Normal(_)) || result.as_os_str().is_empty() {
+            result.push(component);
+            continue;
+        }
+        // Kept as written when it doesn't exist, or is a short 8.3 name with no entry of its own
+        let on_disk = fs::read_dir(&result)
+            .ok()
+            .and_then(|entries| entries.flatten().map(|entry| entry.file_name()).find(|name| components_match(Component::Normal(name), component)));
+        match on_disk {
+            Some(name) => result.push(name),
+            None => result.push(component),
+        }
+    }
+    result
+}
+
+#[cfg(not(windows))]
+fn disk_case(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// Make a path absolute relative to the current directory, dropping `.` components, resolving
+/// `..` components and removing trailing separators.
+///
+/// This works on the path text only. Unlike `canonicalize()`, the path doesn't have to exist and
+/// no `\\?\` prefix is added on Windows, which some of the tools can't parse.
+pub fn ensure_absolute_path(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                // Never step above the root or drive
+                if matches!(normalized.components().next_back(), Some(Component::Normal(_))) {
+                    normalized.pop();
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Check if a path contains only ASCII characters
+fn is_ascii_path(path: &Path) -> bool {
+    path.to_str().is_some_and(|path| path.is_ascii())
+}
+
+/// Clear the read-only flag that `fs::copy` carries over from a protected source, so a tool can
+/// modify the copy and it can be deleted afterwards. The source itself is never changed.
+fn make_writable(path: &Path) -> Result<()> {
+    let mut permissions = fs::metadata(path).with_context(|| format!("Failed to read the attributes of {:?}", path))?.permissions();
+    if !permissions.readonly() {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    // On Windows this only clears the read-only attribute
+    #[cfg(not(unix))]
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions).with_context(|| format!("Failed to make {:?} writable", path))
+}
+
+/// Copy `from` over the existing file `to`, keeping the permissions `to` had, so a read-only
+/// source can be replaced and is read-only again afterwards
+pub fn overwrite_file(from: &Path, to: &Path) -> Result<()> {
+    let original = fs::metadata(to).with_context(|| format!("Failed to read the attributes of {:?}", to))?.permissions();
+    if original.readonly() {
+        make_writable(to)?;
+    }
+    let copied = fs::copy(from, to).with_context(|| format!("Failed to overwrite {:?}", to));
+    // fs::copy also gives `to` the permissions of `from`
+    fs::set_permissions(to, original).with_context(|| format!("Failed to restore the attributes of {:?}", to))?;
+    copied.map(|_| ())
+}
+
+/// Build an ASCII file name from `stem` and the extension of `path`, if that extension is ASCII
+fn ascii_file_name(stem: &str, path: &Path) -> String {
+    match path.extension().and_then(|ext| ext.to_str()).filter(|ext| ext.is_ascii()) {
+        Some(ext) => format!("{}.{}", stem, ext),
+        None => stem.to_string(),
+    }
+}
+
+/// Lock file inside every folder made by [`create_temp_dir`], locked for as long as the folder is in use
+pub const TEMP_DIR_LOCK_FILE: &str = ".owner.lock";
+
+/// A temporary directory holding its owner lock. Dropping it releases the lock and removes the folder.
+#[derive(Debug)]
+pub struct OwnedTempDir {
+    // Declared first so the lock file is closed before the folder is removed, which Windows requires
+    _lock: fs::File,
+    dir: tempfile::TempDir,
+}
+
+impl OwnedTempDir {
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+/// Create a temporary directory under the configured temp folder, or the system one, and lock it
+/// so [`temp_dir_in_use`] tells other instances it isn't stale
+pub fn create_temp_dir(prefix: &str, base: Option<&Path>) -> std::io::Result<OwnedTempDir> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(prefix);
+    let dir = match base {
+        Some(base) => {
+            fs::create_dir_all(base)?;
+            builder.tempdir_in(base)?
+        }
+        None => builder.tempdir()?,
+    };
+    let lock = fs::File::create(dir.path().join(TEMP_DIR_LOCK_FILE))?;
+    lock.lock()?;
+    Ok(OwnedTempDir { _lock: lock, dir })
+}
+
+/// Whether a running process still holds the lock of a folder made by [`create_temp_dir`]. The OS
+/// releases the lock when its owner exits, even after a crash. Folders without a lock file aren't
+/// considered in use.
+pub fn temp_dir_in_use(dir: &Path) -> bool {
+    let Ok(lock) = fs::File::open(dir.join(TEMP_DIR_LOCK_FILE)) else {
+        return false;
+    };
+    matches!(lock.try_lock(), Err(fs::TryLockError::WouldBlock))
+}
+
+/// Rewrite an XML file with one element per line, indented with tabs.
+///
+/// Multi-line values such as hkparam arrays get one entry per line, indented one level deeper than
+/// their element, with the closing tag on its own line. Text is otherwise left as it is.
+pub fn reformat_xml_file(path: &Path) -> Result<()> {
+    let content = fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let formatted = reformat_xml(&content).with_context(|| format!("{:?} isn't well-formed XML", path))?;
+    fs::write(path, formatted).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Reindent an XML document, see [`reformat_xml_file`]
+pub fn reformat_xml(content: &[u8]) -> Result<Vec<u8>> {
+    use quick_xml::events::{BytesText, Event};
+
+    let mut reader = quick_xml::Reader::from_reader(content);
+    reader.config_mut().trim_text(true);
+    let mut writer = quick_xml::Writer::new_with_indent(Vec::new(), b'\t', 1);
+    // Number of elements currently open, which is the indent of their content
+    let mut depth = 0;
+    // Keeps empty elements like `<hkparam ...></hkparam>` on one line
+    let mut element_is_empty = false;
+    let mut buf = Vec::new();
+    loop {
+        let event = reader.read_event_into(&mut buf)?;
+        let starts_element = matches!(event, Event::Start(_));
+        match event {
+            Event::Eof => break,
+            Event::Start(start) => {
+                writer.write_event(Event::Start(start))?;
+                depth += 1;
+            }
+            Event::End(end) => {
+                depth -= 1;
+                if element_is_empty {
+                    writer.write_event(Event::Text(BytesText::new("")))?;
+                }
+                writer.write_event(Event::End(end))?;
+            }
+            Event::Text(text) => {
+                let raw = String::from_utf8_lossy(&text).to_string();
+                let lines: Vec<&str> = raw.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+                let text = if lines.len() > 1 {
+                    let content_indent = format!("\n{}", "\t".repeat(depth));
+                    format!(
+                        "{}{}\n{}",
+                        content_indent,
+                        lines.join(&content_indent),
+                        "\t".repeat(depth.saturating_sub(1))
+                    )
+                } else {
+                    lines.concat()
+                };
+                writer.write_event(Event::Text(BytesText::from_escaped(text)))?;
+            }
+            event => writer.write_event(event)?,
+        }
+        element_is_empty = starts_element;
+        buf.clear();
+    }
+
+    let mut formatted = writer.into_inner();
+    formatted.push(b'\n');
+    Ok(formatted)
+}
+
+/// Kind of text in an XML document, for syntax highlighting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmlSpan {
+    Text,
+    // Brackets, slashes, `=` and whitespace inside tags
+    Markup,
+    TagName,
+    AttributeName,
+    AttributeValue,
+    Comment,
+}
+
+/// Split `xml` into consecutive byte ranges by [`XmlSpan`], covering all of it. Malformed markup is
+/// split as far as it goes rather than rejected, since a viewer shows whatever the tools wrote.
+pub fn xml_spans(xml: &str) -> Vec<(std::ops::Range<usize>, XmlSpan)> {
+    let bytes = xml.as_bytes();
+    let mut spans: Vec<(std::ops::Range<usize>, XmlSpan)> = Vec::new();
+    let mut push = |range: std::ops::Range<usize>, span: XmlSpan| {
+        if range.is_empty() {
+            return;
+        }
+        // Merge with the previous span of the same kind, so runs of text are one span
+        match spans.last_mut() {
+            Some((last, last_span)) if *last_span == span && last.end == range.start => last.end = range.end,
+            _ => spans.push((range, span)),
+        }
+    };
+    let find_from = |start: usize, pattern: &str| xml[start..].find(pattern).map(|offset| start + offset);
+    let name_end = |start: usize| {
+        (start..bytes.len())
+            .find(|&i| bytes[i].is_ascii_whitespace() || matches!(bytes[i], b'>' | b'/' | b'=' | b'?' | b'<'))
+            .unwrap_or(bytes.len())
+    };
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if xml[i..].starts_with("<!--") {
+            let end = find_from(i + 4, "-->").map_or(bytes.len(), |end| end + 3);
+            push(i..end, XmlSpan::Comment);
+            i = end;
+        } else if bytes[i] == b'<' {
+            // "<", "</", "<?" or "<!" then the tag name
+            let name_start = if matches!(bytes.get(i + 1), Some(b'/' | b'?' | b'!')) { i + 2 } else { i + 1 };
+            push(i..name_start, XmlSpan::Markup);
+            let mut j = name_end(name_start);
+            push(name_start..j, XmlSpan::TagName);
+            while j < bytes.len() && bytes[j] != b'>' && bytes[j] != b'<' {
+                let start = j;
+                match bytes[j] {
+                    b'"' | b'\'' => {
+                        let end = find_from(j + 1, if bytes[j] == b'"' { "\"" } else { "'" }).map_or(bytes.len(), |end| end + 1);
+                        push(start..end, XmlSpan::AttributeValue);
+                        j = end;
+                    }
+                    b'/' | b'?' | b'=' => {
+                        push(start..j + 1, XmlSpan::Markup);
+                        j += 1;
+                    }
+                    byte if byte.is_ascii_whitespace() => {
+                        push(start..j + 1, XmlSpan::Markup);
+                        j += 1;
+                    }
+                    _ => {
+                        j = name_end(j);
+                        push(start..j, XmlSpan::AttributeName);
+                    }
+                }
+            }
+            let close_start = j;
+            if bytes.get(j) == Some(&b'>') {
+                j += 1;
+            }
+            push(close_start..j, XmlSpan::Markup);
+            i = j;
+        } else {
+            let end = find_from(i, "<").unwrap_or(bytes.len());
+            push(i..end, XmlSpan::Text);
+            i = end;
+        }
+    }
+    spans
+}
+
+/// Make sure `folder` exists (creating it if needed) and that files can be written to it
+pub fn check_writable_folder(folder: &Path) -> Result<()> {
+    fs::create_dir_all(folder)
+        .with_context(|| format!("Output folder {:?} doesn't exist and couldn't be created. Is the drive connected?", folder))?;
+    // The probe file is deleted again when dropped
+    tempfile::Builder::new()
+        .prefix(".hkxtools_write_test")
+        .tempfile_in(folder)
+        .with_context(|| format!("Output folder {:?} isn't writable", folder))?;
+    Ok(())
+}
+
+/// Check that an input file can be converted: it isn't empty and can be opened for reading, which
+/// fails on Windows while another program (e.g. a mod manager) holds the file locked
+pub fn check_input_readable(path: &Path) -> Result<()> {
+    let metadata = fs::metadata(path).with_context(|| format!("{:?} can't be found", path))?;
+    if metadata.len() == 0 {
+        return Err(anyhow::anyhow!("{:?} is empty (0 bytes)", path));
+    }
+    fs::File::open(path).with_context(|| format!("{:?} can't be opened. Is another program using it?", path))?;
+    Ok(())
+}
+
+/// Lowercase hex SHA-256 of a file's contents, read in chunks so large outputs aren't held in memory
+pub fn file_sha256(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).with_context(|| format!("Failed to read {:?}", path))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Temporary file an atomic conversion writes before renaming it to `output`: `~<stem>.partial.<ext>`
+/// in the same folder, keeping the extension since some tools pick the format from it
+pub fn partial_output_path(output: &Path) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match output.extension() {
+        Some(extension) => format!("~{}.partial.{}", stem, extension.to_string_lossy()),
+        None => format!("~{}.partial", stem),
+    };
+    output.with_file_name(file_name)
+}
+
+/// Script running `command_lines` one after another, stopping at the first that fails: a Windows
+/// batch file when `windows` is set, otherwise a POSIX shell script.
+///
+/// The script is UTF-8, so the batch file switches the console to the UTF-8 codepage before any
+/// path is read, which cmd would otherwise take in the OEM codepage.
+pub fn command_script(command_lines: &[Vec<OsString>], windows: bool) -> String {
+    let mut script = String::from(if windows { "@echo off\r\nchcp 65001 >nul\r\n" } else { "#!/bin/sh\nset -e\n" });
+    for line in command_lines {
+        let quoted: Vec<String> = line.iter().map(|arg| quote_script_argument(&arg.to_string_lossy(), windows)).collect();
+        script.push_str(&quoted.join(" "));
+        script.push_str(if windows { " || exit /b 1\r\n" } else { "\n" });
+    }
+    script
+}
+
+/// Quote `arg` for a batch file or shell script, leaving plain words and paths as they are
+fn quote_script_argument(arg: &str, windows: bool) -> String {
+    if windows {
+        // Batch files expand %VAR% even inside quotes, so percent signs are doubled
+        let arg = arg.replace('%', "%%");
+        if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "&|<>^()\"".contains(c)) {
+            return arg;
+        }
+        return format!("\"{}\"", arg.replace('"', "\"\""));
+    }
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "/._-:=+,@".contains(c)) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// Extensions of the archives whose files can be converted
+pub const ARCHIVE_EXTENSIONS: &[&str] = &["bsa", "ba2"];
+
+/// Whether `path` is a BSA or BA2 archive, by its extension
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ARCHIVE_EXTENSIONS.iter().any(|archive_ext| ext.eq_ignore_ascii_case(archive_ext)))
+}
+
+/// How an archived file's data is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveData {
+    /// BSA record: optionally prefixed with the embedded full name and, when compressed, the unpacked size
+    Bsa { embedded_name: bool, compressed: bool, lz4: bool },
+    /// BA2 general record: zlib or LZ4 block data when `packed_size` isn't 0
+    Ba2 { packed_size: u32, unpacked_size: u32, lz4: bool },
+}
+
+/// A file stored in a BSA or BA2 archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    /// Path inside the archive with `/` separators, e.g. `meshes/actors/character/animations/walk.hkx`
+    pub path: String,
+    offset: u64,
+    size: u32,
+    data: ArchiveData,
+}
+
+/// A Bethesda BSA (Oblivion to Skyrim SE) or general BA2 (Fallout 4 and later) archive.
+///
+/// Only the file table is read when opening; file data is read on demand.
+#[derive(Debug)]
+pub struct Archive {
+    path: PathBuf,
+    entries: Vec<ArchiveEntry>,
+}
+
+impl Archive {
+    /// Read the file table of the archive at `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        let archive_len = file.metadata().with_context(|| format!("Failed to get metadata of {:?}", path))?.len();
+        let mut file = std::io::BufReader::new(file);
+        let magic: [u8; 4] = read_array(&mut file)?;
+        let entries = match &magic {
+            b"BSA\0" => read_bsa_entries(&mut file, archive_len),
+            b"BTDX" => read_ba2_entries(&mut file, archive_len),
+            _ => Err(anyhow::anyhow!("Not a BSA or BA2 archive")),
+        }
+        .with_context(|| format!("Failed to read archive {:?}", path))?;
+        Ok(Self { path: path.to_path_buf(), entries })
+    }
+
+    /// Files in the archive, in the order they are stored
+    pub fn entries(&self) -> &[ArchiveEntry] {
+        &self.entries
+    }
+
+    /// Read and decompress one file
+    pub fn read(&self, entry: &ArchiveEntry) -> Result<Vec<u8>> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut file = fs::File::open(&self.path).with_context(|| format!("Failed to open {:?}", self.path))?;
+        // Sizes come from the file table, so check them before allocating for them
+        let archive_len = file.metadata()?.len();
+        if entry.offset.saturating_add(entry.size as u64) > archive_len {
+            anyhow::bail!("{} runs past the end of the archive", entry.path);
+        }
+        file.seek(SeekFrom::Start(entry.offset))?;
+        let contents = match entry.data {
+            ArchiveData::Bsa { embedded_name, compressed, lz4 } => {
+                let mut remaining = entry.size as u64;
+                if embedded_name {
+                    let [name_len] = read_array(&mut file)?;
+                    file.seek(SeekFrom::Current(name_len as i64))?;
+                    remaining = remaining.saturating_sub(1 + name_len as u64);
+                }
+                if compressed {
+                    let unpacked_size = u32::from_le_bytes(read_array(&mut file)?) as usize;
+                    check_unpacked_size(entry, remaining.saturating_sub(4), unpacked_size as u64)?;
+                    let packed = (&mut file).take(remaining.saturating_sub(4));
+                    let mut contents = Vec::with_capacity(unpacked_size);
+                    if lz4 {
+                        lz4_flex::frame::FrameDecoder::new(packed).read_to_end(&mut contents)?;
+                    } else {
+                        flate2::read::ZlibDecoder::new(packed).read_to_end(&mut contents)?;
+                    }
+                    if contents.len() != unpacked_size {
+                        anyhow::bail!("{} unpacked to {} bytes instead of {}", entry.path, contents.len(), unpacked_size);
+                    }
+                    contents
+                } else {
+                    let mut contents = Vec::with_capacity(remaining as usize);
+                    (&mut file).take(remaining).read_to_end(&mut contents)?;
+                    contents
+                }
+            }
+            ArchiveData::Ba2 { packed_size: 0, unpacked_size, .. } => {
+                let mut contents = Vec::with_capacity(unpacked_size as usize);
+                (&mut file).take(unpacked_size as u64).read_to_end(&mut contents)?;
+                contents
+            }
+            ArchiveData::Ba2 { packed_size, unpacked_size, lz4 } => {
+                check_unpacked_size(entry, packed_size as u64, unpacked_size as u64)?;
+                let mut packed = Vec::with_capacity(packed_size as usize);
+                (&mut file).take(packed_size as u64).read_to_end(&mut packed)?;
+                let contents = if lz4 {
+                    lz4_flex::block::decompress(&packed, unpacked_size as usize)?
+                } else {
+                    let mut contents = Vec::with_capacity(unpacked_size as usize);
+                    flate2::read::ZlibDecoder::new(packed.as_slice()).read_to_end(&mut contents)?;
+                    contents
+                };
+                if contents.len() != unpacked_size as usize {
+                    anyhow::bail!("{} unpacked to {} bytes instead of {}", entry.path, contents.len(), unpacked_size);
+                }
+                contents
+            }
+        };
+        Ok(contents)
+    }
+
+    /// Write one file below `dir`, keeping its folders inside the archive, and return where it was written.
+    ///
+    /// `..` and other components that could leave `dir` are dropped from the archived path.
+    pub fn extract(&self, entry: &ArchiveEntry, dir: &Path) -> Result<PathBuf> {
+        let relative: PathBuf = Path::new(&entry.path)
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part),
+                _ => None,
+            })
+            .collect();
+        if relative.file_name().is_none() {
+            anyhow::bail!("Archived file {:?} has no name", entry.path);
+        }
+        let output_path = dir.join(relative);
+        let contents = self.read(entry).with_context(|| format!("Failed to read {} from {:?}", entry.path, self.path))?;
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create {:?}", parent))?;
+        }
+        fs::write(&output_path, contents).with_context(|| format!("Failed to write {:?}", output_path))?;
+        Ok(output_path)
+    }
+}
+
+/// Most a compressed file can grow by when unpacked (zlib's limit; LZ4's is lower)
+const MAX_COMPRESSION_RATIO: u64 = 1032;
+
+/// Fail when the unpacked size recorded for `entry` couldn't come from `packed_len` bytes
+fn check_unpacked_size(entry: &ArchiveEntry, packed_len: u64, unpacked_size: u64) -> Result<()> {
+    if unpacked_size > packed_len.saturating_mul(MAX_COMPRESSION_RATIO) {
+        anyhow::bail!("{} claims to unpack {} bytes to {}", entry.path, packed_len, unpacked_size);
+    }
+    Ok(())
+}
+
+/// Fail when a table of `count` items of `item_len` bytes, as given by an archive's header, can't
+/// fit in the archive
+fn check_table_fits(count: u32, item_len: u64, archive_len: u64, items: &str) -> Result<()> {
+    if count as u64 * item_len > archive_len {
+        anyhow::bail!("Archive header lists {} {}, more than fit in its {} bytes", count, items, archive_len);
+    }
+    Ok(())
+}
+
+fn read_array<const N: usize>(reader: &mut impl std::io::Read) -> std::io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u32(reader: &mut impl std::io::Read) -> std::io::Result<u32> {
+    read_array(reader).map(u32::from_le_bytes)
+}
+
+fn read_u64(reader: &mut impl std::io::Read) -> std::io::Result<u64> {
+    read_array(reader).map(u64::from_le_bytes)
+}
+
+/// Archived paths use `\`; keep them portable and comparable
+fn normalize_archive_path(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches('/').to_string()
+}
+
+/// File table of a BSA, read after the magic
+fn read_bsa_entries(file: &mut (impl std::io::Read + std::io::Seek), archive_len: u64) -> Result<Vec<ArchiveEntry>> {
+    const INCLUDE_DIRECTORY_NAMES: u32 = 0x1;
+    const INCLUDE_FILE_NAMES: u32 = 0x2;
+    const COMPRESSED_BY_DEFAULT: u32 = 0x4;
+    const EMBED_FILE_NAMES: u32 = 0x100;
+    const COMPRESSION_TOGGLE: u32 = 0x4000_0000;
+    const SIZE_MASK: u32 = 0x3FFF_FFFF;
+
+    let version = read_u32(file)?;
+    if !matches!(version, 103..=105) {
+        anyhow::bail!("Unsupported BSA version {}", version);
+    }
+    let folder_records_offset = read_u32(file)?;
+    let archive_flags = read_u32(file)?;
+    let folder_count = read_u32(file)?;
+    let _file_count = read_u32(file)?;
+    let _total_folder_name_length = read_u32(file)?;
+    let total_file_name_length = read_u32(file)?;
+    let _file_flags = read_u32(file)?;
+    if archive_flags & INCLUDE_DIRECTORY_NAMES == 0 || archive_flags & INCLUDE_FILE_NAMES == 0 {
+        anyhow::bail!("Archive doesn't store file names");
+    }
+    let compressed_by_default = archive_flags & COMPRESSED_BY_DEFAULT != 0;
+    // Oblivion archives use this flag for something else
+    let embedded_name = version != 103 && archive_flags & EMBED_FILE_NAMES != 0;
+
+    check_table_fits(folder_count, if version == 105 { 24 } else { 16 }, archive_len, "folders")?;
+    check_table_fits(total_file_name_length, 1, archive_len, "bytes of file names")?;
+
+    file.seek(std::io::SeekFrom::Start(folder_records_offset as u64))?;
+    let mut file_counts = Vec::with_capacity(folder_count as usize);
+    for _ in 0..folder_count {
+        let _hash = read_u64(file)?;
+        file_counts.push(read_u32(file)?);
+        // Skip the offset of the folder's file records, which follow the folder records anyway
+        if version == 105 {
+            read_array::<12>(file)?;
+        } else {
+            read_u32(file)?;
+        }
+    }
+
+    // Each folder's name, then its file records
+    let mut records = Vec::new();
+    for count in file_counts {
+        let [name_len] = read_array(file)?;
+        let mut name = vec![0; name_len as usize];
+        file.read_exact(&mut name)?;
+        let folder = String::from_utf8_lossy(&name).trim_end_matches('\0').to_string();
+        for _ in 0..count {
+            let _hash = read_u64(file)?;
+            let size = read_u32(file)?;
+            let offset = read_u32(file)?;
+            records.push((folder.clone(), size, offset));
+        }
+    }
+
+    let mut names = vec![0; total_file_name_length as usize];
+    file.read_exact(&mut names)?;
+    let mut names = names.split(|&byte| byte == 0);
+
+    records
+        .into_iter()
+        .map(|(folder, size, offset)| {
+            let name = names.next().context("File name table is shorter than the file records")?;
+            Ok(ArchiveEntry {
+                path: normalize_archive_path(&format!("{}\\{}", folder, String::from_utf8_lossy(name))),
+                offset: offset as u64,
+                size: size & SIZE_MASK,
+                data: ArchiveData::Bsa {
+                    embedded_name,
+                    compressed: compressed_by_default != (size & COMPRESSION_TOGGLE != 0),
+                    lz4: version == 105,
+                },
+            })
+        })
+        .collect()
+}
+
+/// File table of a BA2, read after the magic
+fn read_ba2_entries(file: &mut (impl std::io::Read + std::io::Seek), archive_len: u64) -> Result<Vec<ArchiveEntry>> {
+    const LZ4_BLOCK: u32 = 3;
+
+    let version = read_u32(file)?;
+    let archive_type: [u8; 4] = read_array(file)?;
+    if &archive_type != b"GNRL" {
+        anyhow::bail!("Only general BA2 archives are supported, not {}", String::from_utf8_lossy(&archive_type));
+    }
+    let file_count = read_u32(file)?;
+    let name_table_offset = read_u64(file)?;
+    let lz4 = match version {
+        1 | 7 | 8 => false,
+        2 => {
+            read_u64(file)?;
+            false
+        }
+        3 => {
+            read_u64(file)?;
+            read_u32(file)? == LZ4_BLOCK
+        }
+        _ => anyhow::bail!("Unsupported BA2 version {}", version),
+    };
+
+    check_table_fits(file_count, 36, archive_len, "files")?;
+    let mut records = Vec::with_capacity(file_count as usize);
+    for _ in 0..file_count {
+        // Name hash, extension, folder hash and flags
+        read_array::<16>(file)?;
+        let offset = read_u64(file)?;
+        let packed_size = read_u32(file)?;
+        let unpacked_size = read_u32(file)?;
+        let _align = read_u32(file)?;
+        records.push((offset, packed_size, unpacked_size));
+    }
+
+    file.seek(std::io::SeekFrom::Start(name_table_offset))?;
+    records
+        .into_iter()
+        .map(|(offset, packed_size, unpacked_size)| {
+            let name_len = u16::from_le_bytes(read_array(file)?);
+            let mut name = vec![0; name_len as usize];
+            file.read_exact(&mut name)?;
+            Ok(ArchiveEntry {
+                path: normalize_archive_path(&String::from_utf8_lossy(&name)),
+                offset,
+                size: if packed_size == 0 { unpacked_size } else { packed_size },
+                data: ArchiveData::Ba2 { packed_size, unpacked_size, lz4 },
+            })
+        })
+        .collect()
+}
+
+/// Keep an embedded file already in the tools directory when its SHA-256 matches, otherwise write it
+/// with [`extract_embedded_file`]
+pub fn reuse_or_extract_embedded_file(dir: &Path, file_name: &str, bytes: &[u8]) -> Result<PathBuf> {
+    let path = dir.join(file_name);
+    // Comparing sizes first skips hashing files that can't match
+    let unchanged = fs::metadata(&path).is_ok_and(|metadata| metadata.len() == bytes.len() as u64)
+        && file_sha256(&path).is_ok_and(|hash| hash == format!("{:x}", Sha256::digest(bytes)));
+    if unchanged {
+        log_debug!("Reusing {} in: {:?}", file_name, path);
+        return Ok(path);
+    }
+    extract_embedded_file(dir, file_name, bytes)
+}
+
+/// Write an embedded file into the tools directory and verify it landed intact
+pub fn extract_embedded_file(dir: &Path, file_name: &str, bytes: &[u8]) -> Result<PathBuf> {
+    let path = dir.join(file_name);
+    fs::write(&path, bytes).with_context(|| format!("Could not write {:?}", path))?;
+
+    // Antivirus may delete or truncate the file right after it is written
+    let written_len = fs::metadata(&path)
+        .with_context(|| format!("{:?} disappeared right after extraction", path))?
+        .len();
+    if written_len != bytes.len() as u64 {
+        return Err(anyhow::anyhow!(
+            "{:?} is {} bytes but should be {} bytes",
+            path,
+            written_len,
+            bytes.len()
+        ));
+    }
+
+    log_debug!("Extracted {} to: {:?}", file_name, path);
+    Ok(path)
+}
+
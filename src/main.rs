@@ -2,152 +2,268 @@ use anyhow::{Context as AnyhowContext, Result};
 use eframe::{egui, Frame};
 use egui::{Color32, Context as EguiContext, RichText, Ui};
 use rfd::FileDialog;
+use clap::Parser;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tempfile;
-use tokio::process::Command;
-use tokio::sync::{mpsc, oneshot};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::io::Write;
+use composite_hkx_conversion::{
+    auto_converter_tool, auto_converter_tool_for_format, bundled_tools, check_for_update, is_archive, is_hidden_file, overwrite_file, Archive, ARCHIVE_EXTENSIONS, check_input_readable, check_writable_folder, command_script, create_temp_dir, ensure_absolute_path, file_sha256, get_output_path, get_templated_output_path, is_framework_animation, OutputLayout, log_debug, normalize_listed_path, log_info, set_log_level, skeleton_for_input, FilenameCase, LogLevel, validate_custom_extension, validate_output_template, validate_skeleton_file, validate_suffix_separator, DEFAULT_SUFFIX_SEPARATOR, xml_spans, XmlSpan, DEFAULT_OUTPUT_TEMPLATE, OUTPUT_TEMPLATE_PLACEHOLDERS, BundledTool,
+    temp_dir_in_use, OwnedTempDir, Converter, ConverterTool, InputFileExtension, OutputFormat, ReleaseInfo, SkeletonRule, ToolPaths, ToolTimedOut, VerifyOutcome,
+};
+use tokio::sync::{mpsc, oneshot, Notify, Semaphore};
 use futures::future::join_all;
+use sha2::{Digest, Sha256};
 use walkdir;
 
-const HKXCMD_EXE: &[u8] = include_bytes!("hkxcmd.exe");
-const HKXC_EXE: &[u8] = include_bytes!("hkxc.exe");
-const HKXCONV_EXE: &[u8] = include_bytes!("hkxconv.exe");
-const SSE_TO_LE_HKO: &[u8] = include_bytes!("_SSEtoLE.hko");
-const HAVOK_BEHAVIOR_POST_PROCESS_EXE: &[u8] = include_bytes!("HavokBehaviorPostProcess.exe");
-const HCT_STANDALONE_FILTER_MANAGER_EXE: &[u8] = include_bytes!("hctStandAloneFilterManager.exe");
-const HCT_FILTER_MANAGER_DLL: &[u8] = include_bytes!("hctFilterManager.dll");
-
-#[derive(PartialEq, Clone, Copy, Debug)]
-enum ConverterTool {
-    HkxCmd,
-    Hct,
-    HavokBehaviorPostProcess,
-    HkxC,
-    HkxConv,
+/// Maximum number of tool output lines kept in the log pane
+const MAX_TOOL_LOG_LINES: usize = 1000;
+
+/// Batches of at most this many files show a spinner and the latest tool output instead of a progress bar
+const SPINNER_PROGRESS_MAX_FILES: usize = 2;
+
+/// Tool output lines shown under the spinner
+const SPINNER_LOG_TAIL_LINES: usize = 3;
+
+/// Folder in the temp folder the bundled tools are extracted to and reused from on later launches.
+/// It doesn't start with one of the `TEMP_DIR_PREFIXES`, so stale folder cleanup leaves it alone.
+const TOOLS_CACHE_FOLDER: &str = "composite-hkxtools-cache";
+
+/// Prefixes of the temp directories this app creates
+const TEMP_DIR_PREFIXES: &[&str] = &["hkxtools_", "hct_conversion_", "hkx_verify_", "hkx_ascii_", "hkx_edit_", "hkx_archive_", "hkx_view_"];
+
+/// Longest path shown in the status bar before it's shortened from the front
+const STATUS_BAR_PATH_CHARS: usize = 48;
+
+// How long the notice about skipped dropped files stays up
+const DROP_NOTICE_DURATION: std::time::Duration = std::time::Duration::from_secs(8);
+
+// Most of a file's XML shown in the viewer; the text widget gets slow on whole behavior graphs
+const XML_VIEWER_MAX_BYTES: usize = 1024 * 1024;
+
+// How long the window border flashes when a batch ends
+const BATCH_END_FLASH_DURATION: std::time::Duration = std::time::Duration::from_millis(1200);
+
+// Skipped file names listed in that notice
+const DROP_NOTICE_EXAMPLES: usize = 3;
+
+// How long a file in the watch folder must go unchanged before it's converted, so files still being written are left alone
+const WATCH_SETTLE_TIME: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Seconds a single tool run may take before it's killed, unless changed in settings
+const DEFAULT_CONVERSION_TIMEOUT_SECS: u64 = 120;
+
+// Matching files a background folder scan collects before sending them to the UI
+const FOLDER_SCAN_BATCH: usize = 256;
+
+/// Files a recursive folder scan may add before asking first, unless changed in settings
+const DEFAULT_MAX_FILES: usize = 5000;
+
+/// Window title, prefixed with the progress while a batch runs so it shows on the taskbar
+const WINDOW_TITLE: &str = "Composite HKX Conversion GUI";
+
+/// Longest "Test Tools" waits for one tool to print its help
+const TOOL_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Temp directories older than this are assumed to be left over from a crash
+const STALE_TEMP_DIR_AGE: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Command-line options for converting on startup, for scripts and automation
+#[derive(Debug, Parser)]
+#[clap(version, about = "Convert between SE HKX, LE HKX, XML and KF")]
+struct CliArgs {
+    /// Files, folders, .txt lists or BSA/BA2 archives to add to the input list
+    inputs: Vec<PathBuf>,
+    /// Include the subfolders of input folders
+    #[clap(long)]
+    recursive: bool,
+    /// Saved preset to apply before adding the inputs
+    #[clap(long)]
+    preset: Option<String>,
+    /// Output folder (the first input's folder when omitted)
+    #[clap(long)]
+    output: Option<PathBuf>,
+    /// Write over source files without asking, and start over instead of offering to resume
+    #[clap(long)]
+    overwrite: bool,
+    /// Start converting the inputs as soon as the window opens
+    #[clap(long)]
+    run: bool,
+    /// Convert the inputs, then close and exit with 0 if every file converted, otherwise the number of
+    /// failed files (at most 100), or 1 when the batch couldn't start
+    #[clap(long)]
+    exit_after: bool,
+    /// With --exit-after, print a JSON summary of the batch to stdout instead of the usual messages
+    #[clap(long, requires = "exit-after")]
+    json: bool,
 }
 
-impl ConverterTool {
-    fn label(&self) -> &'static str {
-        match self {
-            ConverterTool::HkxCmd => "hkxcmd",
-            ConverterTool::Hct => "HavokContentTools",
-            ConverterTool::HavokBehaviorPostProcess => "HavokBehaviorPostProcess",
-            ConverterTool::HkxC => "hkxc",
-            ConverterTool::HkxConv => "hkxconv",
-        }
+// Highest exit status --exit-after uses for a count of failed files
+const MAX_FAILURE_EXIT_CODE: usize = 100;
+
+/// A conversion started from the command line, and what to do when it finishes
+struct AutoRun {
+    overwrite: bool,
+    exit_after: bool,
+    // Print the result as JSON for scripts
+    json: bool,
+    started: bool,
+    // When the batch was started, for the JSON summary
+    started_at: Option<std::time::Instant>,
+    // Process exit status, read by `main` once the window closes
+    exit_code: Arc<AtomicI32>,
+}
+
+#[derive(Debug, Clone)]
+enum ConversionStatus {
+    Idle,
+    Running { current_file: String, progress: usize, total: usize },
+    Paused { progress: usize, total: usize },
+    // Cancel was pressed; files already converting are still finishing
+    Cancelling { progress: usize, total: usize },
+    Completed { message: String, stats: ConversionStats },
+    Error { message: String },
+}
+
+/// Holds back conversions that haven't started yet while a batch is paused
+#[derive(Debug, Default)]
+struct PauseGate {
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+    // Error of the file that stopped an "abort on first error" batch
+    abort_error: std::sync::Mutex<Option<String>>,
+    changed: Notify,
+}
+
+impl PauseGate {
+    fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::SeqCst);
+        self.changed.notify_waiters();
     }
 
-    /// Get help text for this tool
-    fn help_text(&self) -> &'static str {
-        match self {
-            ConverterTool::HkxCmd => "LE animation HKX -> SE animation HKX || .kf || .xml (requires skeleton file)",
-            ConverterTool::Hct => "SE animation HKX -> LE animation HKX",
-            ConverterTool::HavokBehaviorPostProcess => "LE animation HKX -> SE animation HKX",
-            ConverterTool::HkxC => "SE animation/behavior HKX <-> LE animation/behaviorHKX <-> .xml",
-            ConverterTool::HkxConv => "SE behavior HKX <-> .xml",
-        }
+    fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
     }
 
-    /// Check if this tool supports a given file extension
-    fn supports_extension(&self, ext: &str) -> bool {
-        match self {
-            ConverterTool::HkxCmd => {
-                matches!(ext, "hkx" | "xml" | "kf")
-            }
-            ConverterTool::HkxC | ConverterTool::HkxConv => {
-                matches!(ext, "hkx" | "xml")
-            }
-            ConverterTool::Hct | ConverterTool::HavokBehaviorPostProcess => {
-                matches!(ext, "hkx")
-            }
-        }
+    /// Release everything waiting at the gate without letting it convert
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.changed.notify_waiters();
     }
 
-    /// Check if this tool supports a given file path
-    fn supports_file(&self, path: &Path) -> bool {
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| self.supports_extension(ext))
-            .unwrap_or(false)
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
     }
 
-    /// Get available input file extensions for this tool
-    fn available_input_extensions(&self) -> Vec<InputFileExtension> {
-        match self {
-            ConverterTool::HkxCmd => {
-                vec![
-                    InputFileExtension::All,
-                    InputFileExtension::Hkx,
-                    InputFileExtension::Xml,
-                    InputFileExtension::Kf,
-                ]
-            }
-            ConverterTool::HkxC | ConverterTool::HkxConv => {
-                vec![
-                    InputFileExtension::All,
-                    InputFileExtension::Hkx,
-                    InputFileExtension::Xml,
-                ]
-            }
-            ConverterTool::Hct | ConverterTool::HavokBehaviorPostProcess => {
-                vec![
-                    InputFileExtension::All,
-                    InputFileExtension::Hkx,
-                ]
-            }
-        }
+    /// Cancel the rest of the batch because of `error`, keeping only the first one reported
+    fn abort(&self, error: String) {
+        self.abort_error.lock().unwrap().get_or_insert(error);
+        self.cancel();
     }
 
-    /// Get available output formats for this tool
-    fn available_output_formats(&self) -> Vec<OutputFormat> {
-        match self {
-            ConverterTool::HkxCmd => {
-                vec![
-                    OutputFormat::Xml,
-                    OutputFormat::SkyrimLE,
-                    OutputFormat::SkyrimSE,
-                    OutputFormat::Kf,
-                ]
-            }
-            ConverterTool::HkxC => {
-                vec![
-                    OutputFormat::Xml,
-                    OutputFormat::SkyrimLE,
-                    OutputFormat::SkyrimSE,
-                ]
-            }
-            ConverterTool::HkxConv => {
-                vec![
-                    OutputFormat::Xml,
-                    OutputFormat::SkyrimSE,
-                ]
-            }
-            ConverterTool::Hct => {
-                vec![OutputFormat::SkyrimLE]
+    fn abort_error(&self) -> Option<String> {
+        self.abort_error.lock().unwrap().clone()
+    }
+
+    /// Wait until the batch isn't paused, returning `false` if it was cancelled instead
+    async fn wait_while_paused(&self) -> bool {
+        loop {
+            // Register for the wakeup before checking, so a resume in between isn't missed
+            let changed = self.changed.notified();
+            if self.is_cancelled() {
+                return false;
             }
-            ConverterTool::HavokBehaviorPostProcess => {
-                vec![OutputFormat::SkyrimSE]
+            if !self.is_paused() {
+                return true;
             }
+            changed.await;
         }
     }
+}
 
-    /// Get supported formats description for drag & drop overlay
-    fn supported_formats_description(&self) -> &'static str {
-        match self {
-            ConverterTool::HkxCmd => "Supports: HKX, XML, KF files",
-            ConverterTool::HkxC | ConverterTool::HkxConv => "Supports: HKX, XML files",
-            ConverterTool::Hct | ConverterTool::HavokBehaviorPostProcess => "Supports: HKX files",
-        }
+/// Live counts of the files in a running batch, shown next to the progress bar
+#[derive(Debug, Default)]
+struct WorkerCounts {
+    // Converting right now
+    running: AtomicUsize,
+    // Waiting for a free worker or for the batch to resume
+    queued: AtomicUsize,
+    // Finished, whether or not they succeeded
+    done: AtomicUsize,
+}
+
+impl WorkerCounts {
+    fn start(&self) {
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        self.running.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn finish(&self) {
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        self.done.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "Running: {} | Queued: {} | Done: {}",
+            self.running.load(Ordering::SeqCst),
+            self.queued.load(Ordering::SeqCst),
+            self.done.load(Ordering::SeqCst)
+        )
     }
 }
 
-#[derive(Debug, Clone)]
-enum ConversionStatus {
-    Idle,
-    Running { current_file: String, progress: usize, total: usize },
-    Completed { message: String },
-    Error { message: String },
+/// Aggregate statistics for a finished batch
+#[derive(Debug, Clone, Default)]
+struct ConversionStats {
+    input_bytes: u64,
+    output_bytes: u64,
+    duration: std::time::Duration,
+}
+
+impl ConversionStats {
+    /// One-line summary for the completion message
+    fn summary(&self) -> String {
+        format!(
+            "Read {}, wrote {} in {:.1}s",
+            format_bytes(self.input_bytes),
+            format_bytes(self.output_bytes),
+            self.duration.as_secs_f64()
+        )
+    }
+}
+
+/// The timeout a conversion error was caused by, if the tool hung and was killed
+fn timed_out(error: &anyhow::Error) -> Option<std::time::Duration> {
+    error.chain().find_map(|cause| cause.downcast_ref::<ToolTimedOut>()).map(|timed_out| timed_out.0)
+}
+
+/// Shorten `path` to its last `max_chars` characters, keeping the end where the folder name is
+fn truncate_path_display(path: &str, max_chars: usize) -> String {
+    let char_count = path.chars().count();
+    if char_count <= max_chars {
+        return path.to_string();
+    }
+    let tail: String = path.chars().skip(char_count - (max_chars - 1)).collect();
+    format!("…{}", tail)
+}
+
+/// Format a byte count with a human-readable unit
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
 }
 
 #[derive(Debug)]
@@ -156,1542 +272,5726 @@ struct ConversionProgress {
     file_index: usize,
     total_files: usize,
     status: ConversionStatus,
+    // Jobs narrowed to the inputs that failed to convert (only filled in on batch completion)
+    failed_jobs: Vec<ConversionJob>,
+    // Output files that were written successfully (only filled in on batch completion)
+    completed_outputs: Vec<CompletedOutput>,
+    // A line of live output from the running tool, if this update carries one
+    log_line: Option<String>,
+    // Full error of each failed file, with the tool's output (only filled in on batch completion)
+    error_details: Vec<String>,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-enum InputFileExtension {
-    All,
-    Hkx,
-    Xml,
-    Kf,
-}
-
-impl InputFileExtension {
-    fn label_for_tool(&self, tool: ConverterTool) -> &'static str {
-        match self {
-            InputFileExtension::All => match tool {
-                ConverterTool::HkxCmd => "All (HKX, XML, KF)",
-                ConverterTool::HkxC => "All (HKX, XML)",
-                ConverterTool::HkxConv => "All (HKX, XML)",
-                ConverterTool::Hct => "All (HKX only)",
-                ConverterTool::HavokBehaviorPostProcess => "All (HKX only)",
-            },
-            InputFileExtension::Hkx => "HKX only",
-            InputFileExtension::Xml => "XML only",
-            InputFileExtension::Kf => "KF only",
+impl ConversionProgress {
+    /// Update for `current_file`, the `file_index`th of `total_files` outputs, without results or log output
+    fn new(current_file: impl Into<String>, file_index: usize, total_files: usize, status: ConversionStatus) -> Self {
+        Self {
+            current_file: current_file.into(),
+            file_index,
+            total_files,
+            status,
+            failed_jobs: Vec::new(),
+            completed_outputs: Vec::new(),
+            log_line: None,
+            error_details: Vec::new(),
         }
     }
+
+    /// `current_file` started or is still converting
+    fn item_running(current_file: &str, file_index: usize, total_files: usize) -> Self {
+        let status = ConversionStatus::Running { current_file: current_file.to_string(), progress: file_index, total: total_files };
+        Self::new(current_file, file_index, total_files, status)
+    }
+
+    /// `current_file` failed with `message`; the rest of the batch goes on
+    fn item_failed(current_file: &str, file_index: usize, total_files: usize, message: String) -> Self {
+        Self::new(current_file, file_index, total_files, ConversionStatus::Error { message })
+    }
+
+    /// Last update of a batch, carrying its results, after `converted` of `total_files` outputs were written
+    fn batch_done(
+        current_file: &str,
+        converted: usize,
+        total_files: usize,
+        status: ConversionStatus,
+        failed_jobs: Vec<ConversionJob>,
+        completed_outputs: Vec<CompletedOutput>,
+        error_details: Vec<String>,
+    ) -> Self {
+        Self { failed_jobs, completed_outputs, error_details, ..Self::new(current_file, converted, total_files, status) }
+    }
+
+    fn with_log_line(mut self, line: String) -> Self {
+        self.log_line = Some(line);
+        self
+    }
 }
 
-struct HkxToolsApp {
-    input_paths: Vec<PathBuf>,
-    output_folder: Option<PathBuf>,
-    skeleton_file: Option<PathBuf>,
-    output_suffix: String,
+/// Every converter tool, in the order used by the command palette and Test Tools
+const ALL_TOOLS: [ConverterTool; 5] = [
+    ConverterTool::HkxCmd,
+    ConverterTool::Hct,
+    ConverterTool::HavokBehaviorPostProcess,
+    ConverterTool::HkxC,
+    ConverterTool::HkxConv,
+];
+
+/// Tool selector groups by the kind of file each tool handles. hkxc converts both, so it is listed twice
+const TOOL_CATEGORIES: [(&str, &[ConverterTool]); 2] = [
+    (
+        "Animation",
+        &[ConverterTool::HkxCmd, ConverterTool::Hct, ConverterTool::HavokBehaviorPostProcess, ConverterTool::HkxC],
+    ),
+    ("Behavior", &[ConverterTool::HkxC, ConverterTool::HkxConv]),
+];
+
+/// Tool picker entry that picks each file's tool with `auto_converter_tool`
+const AUTO_TOOL_LABEL: &str = "Auto";
+
+const AUTO_TOOL_HELP: &str = "Pick a tool for each file: hkxcmd for KF input or output, hkxconv for SE behaviors and their XML, \
+    hkxc for everything else (animations, LE behaviors and LE output)";
+
+/// "Test Tools" outcome for one tool: its help output, or why it didn't run
+type ToolCheck = (ConverterTool, Result<String, String>);
+
+/// Input that can't be converted as it is, with the reason
+type UnreadableInput = (PathBuf, String);
+
+/// A named tool/format/filter setup that can be applied in one click
+#[derive(Debug, Clone)]
+struct ConversionPreset {
+    name: String,
+    converter_tool: ConverterTool,
     output_format: OutputFormat,
-    custom_extension: Option<String>,
     input_file_extension: InputFileExtension,
-    converter_tool: ConverterTool,
-    hkxcmd_path: PathBuf,
-    hkxc_path: PathBuf,
-    hkxconv_path: PathBuf,
-    sse_to_le_hko_path: PathBuf,
-    havok_behavior_post_process_path: PathBuf,
-    hct_standalone_filter_manager_path: PathBuf,
-    hct_filter_manager_dll_path: PathBuf,
-    // Track base folder for relative path calculations
-    base_folder: Option<PathBuf>,
-    // Track if output folder was manually set by user
-    output_folder_manually_set: bool,
-    // Bookmarked output folders
-    bookmarked_folders: Vec<PathBuf>,
-    // Async operation fields
-    conversion_status: ConversionStatus,
-    progress_rx: Option<mpsc::UnboundedReceiver<ConversionProgress>>,
-    cancel_tx: Option<oneshot::Sender<()>>,
-    tokio_handle: tokio::runtime::Handle,
+    output_suffix: String,
+    custom_extension: Option<String>,
+    skeleton_file: Option<PathBuf>,
+}
+
+impl ConversionPreset {
+    /// Settings file lines, one `preset.<index>.<field>=value` line per field so a value can hold any character
+    fn to_settings(&self, index: usize) -> Vec<String> {
+        let mut lines = vec![
+            format!("preset.{}.name={}", index, self.name),
+            format!("preset.{}.tool={}", index, self.converter_tool.key()),
+            format!("preset.{}.format={}", index, self.output_format.key()),
+            format!("preset.{}.filter={}", index, self.input_file_extension.key()),
+            format!("preset.{}.suffix={}", index, self.output_suffix),
+        ];
+        if let Some(extension) = &self.custom_extension {
+            lines.push(format!("preset.{}.extension={}", index, extension));
+        }
+        if let Some(skeleton_file) = &self.skeleton_file {
+            lines.push(format!("preset.{}.skeleton={}", index, skeleton_file.to_string_lossy()));
+        }
+        lines
+    }
+
+    /// Build a preset from its fields, or `None` if one is missing or doesn't fit the preset's tool
+    fn from_settings(fields: &HashMap<String, String>) -> Option<Self> {
+        let field = |key: &str| fields.get(key).map(|value| value.trim()).filter(|value| !value.is_empty());
+        let converter_tool = ConverterTool::from_key(field("tool")?)?;
+        let output_format = OutputFormat::from_key(field("format")?)
+            .filter(|format| converter_tool.available_output_formats().contains(format))?;
+        let input_file_extension = InputFileExtension::from_key(field("filter")?)
+            .filter(|filter| converter_tool.available_input_extensions().contains(filter))?;
+        Some(Self {
+            name: field("name")?.to_string(),
+            converter_tool,
+            output_format,
+            input_file_extension,
+            // Not trimmed, like the suffix separator
+            output_suffix: fields.get("suffix").cloned().unwrap_or_default(),
+            custom_extension: field("extension").map(str::to_string),
+            skeleton_file: field("skeleton").map(PathBuf::from),
+        })
+    }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-enum OutputFormat {
-    Xml,
-    SkyrimLE,
-    SkyrimSE,
-    Kf,
+/// Persistent settings stored next to the executable as simple `key=value` lines
+#[derive(Debug, Clone, Default)]
+struct AppSettings {
+    window_size: Option<egui::Vec2>,
+    window_position: Option<egui::Pos2>,
+    // Physical pixels per point when the window geometry was saved, to compare it with the screen size
+    window_pixels_per_point: Option<f32>,
+    // Base folder for temporary files (system temp folder when unset)
+    temp_dir: Option<PathBuf>,
+    // Look for a newer release on startup (off by default, since it uses the network)
+    check_for_updates: bool,
+    // Program used to edit XML in the "Edit as XML" workflow (system default when unset)
+    xml_editor: Option<PathBuf>,
+    // Seconds before a hung tool is killed; 0 disables the limit (default when unset)
+    conversion_timeout_secs: Option<u64>,
+    // Files a recursive folder scan adds without asking; 0 disables the limit (default when unset)
+    max_files: Option<usize>,
+    // Case of output file names, for mod frameworks that expect lowercase names
+    filename_case: FilenameCase,
+    // Put between the file name and the output suffix (underscore when unset); may be empty
+    suffix_separator: Option<String>,
+    // Layout of output paths with placeholders, replacing the base folder/flatten layout when set
+    output_template: String,
+    // Re-indent XML outputs so diffs between conversions stay small
+    reformat_xml: bool,
+    // Runs the Windows tools on other systems (unused on Windows)
+    wine: Option<PathBuf>,
+    // Smaller layout for small screens: dropdowns, tighter spacing and fewer hints
+    compact_layout: bool,
+    // Recursive folder scans descend into symlinked/junctioned folders (off by default)
+    follow_links: bool,
+    // Folder scans also add hidden and system files and look in hidden folders (off by default)
+    include_hidden_files: bool,
+    // Select Folder clears the input list before adding the folder's files
+    folder_replaces_list: bool,
+    // Tools write a temporary file that is renamed over the output once the conversion succeeded
+    atomic_output: bool,
+    // How much is printed to the console
+    log_level: LogLevel,
+    // Signals when a batch ends, both off by default
+    chime_on_batch_end: bool,
+    flash_on_batch_end: bool,
+    // Named setups, stored as `preset.<index>.<field>=` lines
+    presets: Vec<ConversionPreset>,
 }
 
-impl OutputFormat {
-    fn extension(&self) -> &'static str {
-        match self {
-            OutputFormat::Xml => "xml",
-            OutputFormat::SkyrimLE | OutputFormat::SkyrimSE => "hkx",
-            OutputFormat::Kf => "kf",
+impl AppSettings {
+    /// Get the path to the settings file (relative to the executable)
+    fn get_settings_file_path() -> PathBuf {
+        if let Ok(exe_path) = std::env::current_exe() {
+            if let Some(exe_dir) = exe_path.parent() {
+                return exe_dir.join("settings.txt");
+            }
         }
+        // Fallback to current directory
+        PathBuf::from("settings.txt")
     }
 
-    fn label(&self) -> &'static str {
-        match self {
-            OutputFormat::Xml => "XML",
-            OutputFormat::SkyrimLE => "Skyrim LE",
-            OutputFormat::SkyrimSE => "Skyrim SE",
-            OutputFormat::Kf => "KF",
+    /// Per-file tool timeout, or `None` when the limit is disabled
+    fn conversion_timeout(&self) -> Option<std::time::Duration> {
+        match self.conversion_timeout_secs.unwrap_or(DEFAULT_CONVERSION_TIMEOUT_SECS) {
+            0 => None,
+            secs => Some(std::time::Duration::from_secs(secs)),
         }
     }
 
-    /// Check if this output format requires a skeleton file
-    fn requires_skeleton(&self) -> bool {
-        matches!(self, OutputFormat::Kf)
+    /// Most files a recursive folder scan adds without confirmation, or `None` when the limit is disabled
+    fn max_files(&self) -> Option<usize> {
+        match self.max_files.unwrap_or(DEFAULT_MAX_FILES) {
+            0 => None,
+            max_files => Some(max_files),
+        }
     }
-}
 
-impl Default for HkxToolsApp {
-    fn default() -> Self {
-        Self {
-            input_paths: Vec::new(),
-            output_folder: None,
-            skeleton_file: None,
-            output_suffix: String::new(),
-            output_format: OutputFormat::Xml,
-            custom_extension: None,
-            input_file_extension: InputFileExtension::All,
-            converter_tool: ConverterTool::HkxCmd,
-            hkxcmd_path: PathBuf::new(),
-            hkxc_path: PathBuf::new(),
-            hkxconv_path: PathBuf::new(),
-            sse_to_le_hko_path: PathBuf::new(),
-            havok_behavior_post_process_path: PathBuf::new(),
-            hct_standalone_filter_manager_path: PathBuf::new(),
-            hct_filter_manager_dll_path: PathBuf::new(),
-            base_folder: None,
-            output_folder_manually_set: false,
-            bookmarked_folders: Vec::new(),
-            conversion_status: ConversionStatus::Idle,
-            progress_rx: None,
-            cancel_tx: None,
-            tokio_handle: tokio::runtime::Handle::current(),
-        }
+    /// Separator between the file name and the output suffix
+    fn suffix_separator(&self) -> &str {
+        self.suffix_separator.as_deref().unwrap_or(DEFAULT_SUFFIX_SEPARATOR)
     }
-}
 
-// Temporary context for async conversion operations
-struct TempConversionContext {
-    converter_tool: ConverterTool,
-    output_format: OutputFormat,
-    skeleton_file: Option<PathBuf>,
-    hkxcmd_path: PathBuf,
-    hkxc_path: PathBuf,
-    hkxconv_path: PathBuf,
-    sse_to_le_hko_path: PathBuf,
-    havok_behavior_post_process_path: PathBuf,
-    hct_standalone_filter_manager_path: PathBuf,
-    hct_filter_manager_dll_path: PathBuf,
-}
+    /// Load settings from file, ignoring unknown keys and malformed values
+    fn load() -> Result<Self> {
+        let settings_file = Self::get_settings_file_path();
+        let mut settings = Self::default();
 
-impl TempConversionContext {
-    async fn run_conversion_tool(&self, input: &Path, output: &Path) -> Result<()> {
-        let mut command = match self.converter_tool {
-            ConverterTool::HkxCmd => Command::new(&self.hkxcmd_path),
-            ConverterTool::Hct => Command::new(&self.hct_standalone_filter_manager_path),
-            ConverterTool::HavokBehaviorPostProcess => Command::new(&self.havok_behavior_post_process_path),
-            ConverterTool::HkxC => Command::new(&self.hkxc_path),
-            ConverterTool::HkxConv => Command::new(&self.hkxconv_path),
-        };
-        
-        let tool_name = match self.converter_tool {
-            ConverterTool::HkxCmd => "hkxcmd",
-            ConverterTool::Hct => "hctStandAloneFilterManager",
-            ConverterTool::HavokBehaviorPostProcess => "HavokBehaviorPostProcess",
-            ConverterTool::HkxC => "hkxc",
-            ConverterTool::HkxConv => "hkxconv",
-        };
+        if !settings_file.exists() {
+            return Ok(settings);
+        }
 
-        // Convert paths to absolute paths to avoid issues with paths starting with '-'
-        // Use absolute paths but avoid canonicalize() which can add \\?\ prefix on Windows
-        let input_absolute = HkxToolsApp::ensure_absolute_path(input);
-        let output_absolute = HkxToolsApp::ensure_absolute_path(output);
-        
-        // Also handle skeleton file if it exists
-        let skeleton_absolute = self.skeleton_file.as_ref().map(|skeleton| {
-            HkxToolsApp::ensure_absolute_path(skeleton)
-        });
-        
-        // Set the command based on output format
-        if self.output_format == OutputFormat::Kf {
-            if self.converter_tool != ConverterTool::Hct {
-                // For KF output, we need to determine direction based on input file extension
-                let input_ext = input_absolute.extension().and_then(|ext| ext.to_str()).unwrap_or("");
-                if input_ext == "kf" {
-                    command.arg("ConvertKF"); // KF -> HKX
-                } else {
-                    command.arg("exportkf"); // HKX -> KF
+        let content = fs::read_to_string(&settings_file)
+            .context("Failed to read settings file")?;
+
+        // Preset fields by preset index, turned into presets once every line is read
+        let mut preset_fields: std::collections::BTreeMap<usize, HashMap<String, String>> = std::collections::BTreeMap::new();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key.trim() {
+                "window_size" => {
+                    settings.window_size = Self::parse_pair(value).map(|(w, h)| egui::vec2(w, h));
                 }
-            }
-            // HCT doesn't support KF conversion
-        } else {
-            if self.converter_tool != ConverterTool::Hct && self.converter_tool != ConverterTool::HavokBehaviorPostProcess {
-                command.arg("convert");
-            }
-            // HCT and HavokBehaviorPostProcess don't need a command argument
-        }
-
-        // Add arguments based on tool and output format
-        match self.converter_tool {
-            ConverterTool::HkxCmd => {
-                if self.output_format == OutputFormat::Kf {
-                    // KF conversion
-                    if let Some(skeleton) = &skeleton_absolute {
-                        command.arg(skeleton);
-                    }
-                    command.arg(&input_absolute);
-                    command.arg(&output_absolute);
-                    // For HKX <> KF, determine if we need version argument based on direction
-                    let input_ext = input_absolute.extension().and_then(|ext| ext.to_str()).unwrap_or("");
-                    if input_ext == "kf" {
-                        // KF -> HKX conversion
-                        command.arg(format!("-v:{}", match self.output_format {
-                            OutputFormat::Xml => "XML",
-                            OutputFormat::SkyrimLE => "WIN32",
-                            OutputFormat::SkyrimSE => "AMD64",
-                            OutputFormat::Kf => "AMD64",
-                        }));
-                    }
-                    // HKX -> KF doesn't need version argument
-                } else {
-                    // Regular HKX/XML conversion
-                    command.arg("-i").arg(&input_absolute);
-                    command.arg("-o").arg(&output_absolute);
-                    command.arg(format!("-v:{}", match self.output_format {
-                        OutputFormat::Xml => "XML",
-                        OutputFormat::SkyrimLE => "WIN32",
-                        OutputFormat::SkyrimSE => "AMD64",
-                        OutputFormat::Kf => "AMD64", // This shouldn't happen in regular conversion
-                    }));
-                }
-            }
-            ConverterTool::HkxC => {
-                if self.output_format == OutputFormat::Kf {
-                    return Err(anyhow::anyhow!("hkxc does not support KF conversion"));
-                }
-                command.arg("--input").arg(&input_absolute);
-                command.arg("--output").arg(&output_absolute);
-                command.arg("--format").arg(match self.output_format {
-                    OutputFormat::Xml => "xml",
-                    OutputFormat::SkyrimLE => "win32",
-                    OutputFormat::SkyrimSE => "amd64",
-                    OutputFormat::Kf => "amd64", // This shouldn't happen
-                });
-            }
-            ConverterTool::HkxConv => {
-                if self.output_format == OutputFormat::Kf {
-                    return Err(anyhow::anyhow!("hkxconv does not support KF conversion"));
-                }
-                command.arg(&input_absolute);
-                command.arg(&output_absolute);
-                command.arg("-v").arg(match self.output_format {
-                    OutputFormat::Xml => "xml",
-                    OutputFormat::SkyrimLE => "hkx",
-                    OutputFormat::SkyrimSE => "hkx",
-                    OutputFormat::Kf => "hkx", // This shouldn't happen
-                });
-            }
-            ConverterTool::Hct => {
-                if self.output_format == OutputFormat::Kf {
-                    return Err(anyhow::anyhow!("HCT does not support KF conversion"));
+                "window_position" => {
+                    settings.window_position = Self::parse_pair(value).map(|(x, y)| egui::pos2(x, y));
                 }
-                
-                // For HCT, create a unique temporary directory for this conversion
-                let temp_dir = tempfile::Builder::new()
-                    .prefix("hct_conversion_")
-                    .tempdir()
-                    .context("Failed to create temporary directory for HCT conversion")?;
-                
-                // HCT only supports SSE to LE conversion
-                let source_hko_path = &self.sse_to_le_hko_path;
-                
-                // Copy the .hko file to the temporary directory
-                let hko_filename = source_hko_path.file_name().unwrap();
-                let temp_hko_path = temp_dir.path().join(hko_filename);
-                fs::copy(source_hko_path, &temp_hko_path)
-                    .context("Failed to copy .hko file to temporary directory")?;
-                
-                println!("HCT temp dir: {:?}, using .hko: {:?}", temp_dir.path(), hko_filename);
-                
-                // Set working directory to temp directory and use relative .hko filename
-                command.current_dir(temp_dir.path());
-                command.arg(&input_absolute);
-                command.arg("-s");
-                command.arg(hko_filename);  // Just the filename, not full path
-                
-                // Execute the command
-                let cmd_output = command.output().await.context("Failed to execute HCT converter tool")?;
-                let stderr = String::from_utf8_lossy(&cmd_output.stderr);
-
-                if !cmd_output.status.success() {
-                    return Err(anyhow::anyhow!("{} failed: {}", tool_name, stderr));
+                "window_pixels_per_point" => {
+                    settings.window_pixels_per_point = value.trim().parse().ok().filter(|scale: &f32| scale.is_finite() && *scale > 0.0);
                 }
-                
-                // HCT creates "filename.hkx" in the same directory as the .hko file
-                let hct_output_file = temp_dir.path().join("filename.hkx");
-                
-                // Debug: List all files in temp directory
-                println!("Temp directory contents:");
-                if let Ok(entries) = fs::read_dir(temp_dir.path()) {
-                    for entry in entries.flatten() {
-                        println!("  {:?}", entry.path());
-                    }
-                } else {
-                    println!("  Failed to read temp directory");
+                "temp_dir" => {
+                    let value = value.trim();
+                    settings.temp_dir = (!value.is_empty()).then(|| PathBuf::from(value));
                 }
-                
-                if !hct_output_file.exists() {
-                    return Err(anyhow::anyhow!("HCT did not produce expected output file: {:?}", hct_output_file));
+                "check_for_updates" => {
+                    settings.check_for_updates = value.trim() == "true";
                 }
-                
-                println!("HCT output file exists: {:?}", hct_output_file);
-                println!("Target output path: {:?}", output_absolute);
-                
-                // Create output directory if it doesn't exist
-                if let Some(parent) = output_absolute.parent() {
-                    println!("Creating output directory: {:?}", parent);
-                    fs::create_dir_all(parent).context("Failed to create output directory")?;
+                "xml_editor" => {
+                    let value = value.trim();
+                    settings.xml_editor = (!value.is_empty()).then(|| PathBuf::from(value));
                 }
-                
-                // Check if target file already exists and remove it if necessary
-                if output_absolute.exists() {
-                    println!("Target file already exists, removing: {:?}", output_absolute);
-                    fs::remove_file(&output_absolute).context("Failed to remove existing target file")?;
+                "conversion_timeout" => {
+                    settings.conversion_timeout_secs = value.trim().parse().ok();
                 }
-                
-                // Move the HCT output file directly to the final location
-                // The output_absolute path already includes any suffix/extension modifications
-                match fs::rename(&hct_output_file, &output_absolute) {
-                    Ok(_) => {
-                        println!("Successfully moved HCT output to: {:?}", output_absolute);
-                    }
-                    Err(e) => {
-                        // If rename fails, try copy + delete as fallback
-                        println!("Rename failed ({}), trying copy + delete fallback", e);
-                        fs::copy(&hct_output_file, &output_absolute)
-                            .context("Failed to copy HCT output file to final location")?;
-                        fs::remove_file(&hct_output_file)
-                            .context("Failed to remove temporary HCT output file after copy")?;
-                        println!("Successfully copied HCT output to: {:?}", output_absolute);
-                    }
+                "max_files" => {
+                    settings.max_files = value.trim().parse().ok();
                 }
-                
-                println!("HCT conversion complete: {:?} -> {:?}", input_absolute, output_absolute);
-                
-                // temp_dir will be automatically cleaned up when it goes out of scope
-                return Ok(());
-            }
-            ConverterTool::HavokBehaviorPostProcess => {
-                if self.output_format == OutputFormat::Kf {
-                    return Err(anyhow::anyhow!("HavokBehaviorPostProcess does not support KF conversion"));
+                "filename_case" => {
+                    settings.filename_case = FilenameCase::from_key(value.trim()).unwrap_or_default();
                 }
-                
-                // HavokBehaviorPostProcess only supports HKX input files and SSE output
-                if input_absolute.extension().map_or(true, |ext| ext != "hkx") {
-                    return Err(anyhow::anyhow!("HavokBehaviorPostProcess requires an HKX input file."));
+                "suffix_separator" => {
+                    // Not trimmed, since a space is a valid separator
+                    settings.suffix_separator = Some(value.to_string());
                 }
-                
-                // HavokBehaviorPostProcess modifies files in-place, so we need to copy the input to output first
-                println!("Input path: {:?}", input_absolute);
-                println!("Output path: {:?}", output_absolute);
-                println!("Input exists: {}", input_absolute.exists());
-                println!("Output parent exists: {}", output_absolute.parent().map_or(false, |p| p.exists()));
-                println!("Copying input file to output location: {:?} -> {:?}", input_absolute, output_absolute);
-                
-                // Check if input and output are the same
-                if input_absolute == output_absolute {
-                    return Err(anyhow::anyhow!("Input and output paths are the same: {:?}", input_absolute));
+                "output_template" => {
+                    settings.output_template = value.trim().to_string();
                 }
-                
-                // Create output directory if it doesn't exist
-                if let Some(parent) = output_absolute.parent() {
-                    println!("Creating output directory: {:?}", parent);
-                    fs::create_dir_all(parent).context("Failed to create output directory")?;
+                "reformat_xml" => {
+                    settings.reformat_xml = value.trim() == "true";
                 }
-                
-                // Copy input file to output location
-                match fs::copy(&input_absolute, &output_absolute) {
-                    Ok(bytes_copied) => {
-                        println!("Successfully copied {} bytes", bytes_copied);
-                    }
-                    Err(e) => {
-                        println!("Copy failed with error: {:?}", e);
-                        return Err(anyhow::anyhow!("Failed to copy input file to output location: {}", e));
+                "compact_layout" => {
+                    settings.compact_layout = value.trim() == "true";
+                }
+                "follow_links" => {
+                    settings.follow_links = value.trim() == "true";
+                }
+                "include_hidden_files" => {
+                    settings.include_hidden_files = value.trim() == "true";
+                }
+                "folder_replaces_list" => {
+                    settings.folder_replaces_list = value.trim() == "true";
+                }
+                "atomic_output" => {
+                    settings.atomic_output = value.trim() == "true";
+                }
+                "chime_on_batch_end" => {
+                    settings.chime_on_batch_end = value.trim() == "true";
+                }
+                "flash_on_batch_end" => {
+                    settings.flash_on_batch_end = value.trim() == "true";
+                }
+                "log_level" => {
+                    settings.log_level = LogLevel::from_key(value.trim()).unwrap_or_default();
+                }
+                "wine" => {
+                    let value = value.trim();
+                    settings.wine = (!value.is_empty()).then(|| PathBuf::from(value));
+                }
+                key => {
+                    let preset_field = key
+                        .strip_prefix("preset.")
+                        .and_then(|rest| rest.split_once('.'))
+                        .and_then(|(index, field)| Some((index.parse::<usize>().ok()?, field)));
+                    if let Some((index, field)) = preset_field {
+                        preset_fields.entry(index).or_default().insert(field.to_string(), value.to_string());
                     }
                 }
-                
-                // Check file size before processing
-                let file_size_before = fs::metadata(&output_absolute)
-                    .context("Failed to get file metadata before processing")?
-                    .len();
-                println!("File size before HavokBehaviorPostProcess: {} bytes", file_size_before);
-                
-                // Run HavokBehaviorPostProcess on the output file (modifies in-place)
-                command.arg("--platformAmd64");
-                // Both input and output are the same file (in-place modification)
-                // Don't manually add quotes - let Command handle it
-                command.arg(&output_absolute);
-                command.arg(&output_absolute);
             }
         }
+        settings.presets = preset_fields.values().filter_map(ConversionPreset::from_settings).collect();
 
-        // Print the command being executed for debugging
-        println!("EXECUTING COMMAND: {:?} with input: {:?}, output: {:?}", tool_name, input_absolute, output_absolute);
-        
-        // For HavokBehaviorPostProcess, print the exact command with arguments
-        if self.converter_tool == ConverterTool::HavokBehaviorPostProcess {
-            println!("HavokBehaviorPostProcess command: {:?}", command);
-        }
+        Ok(settings)
+    }
 
-        let output = command.output().await.context("Failed to execute converter tool")?;
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        
-        // For HavokBehaviorPostProcess, print all output for debugging
-        if self.converter_tool == ConverterTool::HavokBehaviorPostProcess {
-            println!("HavokBehaviorPostProcess exit code: {:?}", output.status.code());
-            println!("HavokBehaviorPostProcess stdout: {}", stdout);
-            println!("HavokBehaviorPostProcess stderr: {}", stderr);
-        }
+    /// Save settings to file
+    fn save(&self) -> Result<()> {
+        let settings_file = Self::get_settings_file_path();
 
-        if !output.status.success() {
-            return Err(anyhow::anyhow!("{} failed with exit code {:?}: stdout: {} stderr: {}", 
-                tool_name, output.status.code(), stdout, stderr));
+        let mut lines = Vec::new();
+        if let Some(size) = self.window_size {
+            lines.push(format!("window_size={},{}", size.x, size.y));
         }
-        
-        // For HavokBehaviorPostProcess, check if the file size changed
-        if self.converter_tool == ConverterTool::HavokBehaviorPostProcess {
-            let file_size_after = fs::metadata(&output_absolute)
-                .context("Failed to get file metadata after processing")?
-                .len();
-            println!("File size after HavokBehaviorPostProcess: {} bytes", file_size_after);
-            
-            if file_size_after == fs::metadata(&input_absolute)
-                .context("Failed to get input file metadata")?
-                .len() {
-                println!("WARNING: Output file size is the same as input file size - conversion may not have worked");
-            } else {
-                println!("SUCCESS: File size changed, conversion appears to have worked");
-            }
+        if let Some(pos) = self.window_position {
+            lines.push(format!("window_position={},{}", pos.x, pos.y));
+        }
+        if let Some(scale) = self.window_pixels_per_point {
+            lines.push(format!("window_pixels_per_point={}", scale));
+        }
+        if let Some(temp_dir) = &self.temp_dir {
+            lines.push(format!("temp_dir={}", temp_dir.to_string_lossy()));
+        }
+        if self.check_for_updates {
+            lines.push("check_for_updates=true".to_string());
+        }
+        if let Some(xml_editor) = &self.xml_editor {
+            lines.push(format!("xml_editor={}", xml_editor.to_string_lossy()));
+        }
+        if let Some(timeout) = self.conversion_timeout_secs {
+            lines.push(format!("conversion_timeout={}", timeout));
+        }
+        if let Some(max_files) = self.max_files {
+            lines.push(format!("max_files={}", max_files));
+        }
+        if self.filename_case != FilenameCase::Preserve {
+            lines.push(format!("filename_case={}", self.filename_case.key()));
+        }
+        if let Some(separator) = self.suffix_separator.as_deref().filter(|&separator| separator != DEFAULT_SUFFIX_SEPARATOR) {
+            lines.push(format!("suffix_separator={}", separator));
+        }
+        if !self.output_template.is_empty() {
+            lines.push(format!("output_template={}", self.output_template));
+        }
+        if self.reformat_xml {
+            lines.push("reformat_xml=true".to_string());
+        }
+        if let Some(wine) = &self.wine {
+            lines.push(format!("wine={}", wine.to_string_lossy()));
+        }
+        if self.compact_layout {
+            lines.push("compact_layout=true".to_string());
+        }
+        if self.follow_links {
+            lines.push("follow_links=true".to_string());
+        }
+        if self.include_hidden_files {
+            lines.push("include_hidden_files=true".to_string());
+        }
+        if self.folder_replaces_list {
+            lines.push("folder_replaces_list=true".to_string());
+        }
+        if self.atomic_output {
+            lines.push("atomic_output=true".to_string());
+        }
+        if self.chime_on_batch_end {
+            lines.push("chime_on_batch_end=true".to_string());
+        }
+        if self.flash_on_batch_end {
+            lines.push("flash_on_batch_end=true".to_string());
+        }
+        if self.log_level != LogLevel::Normal {
+            lines.push(format!("log_level={}", self.log_level.key()));
+        }
+        for (index, preset) in self.presets.iter().enumerate() {
+            lines.extend(preset.to_settings(index));
         }
 
+        fs::write(&settings_file, lines.join("\n"))
+            .context("Failed to save settings file")?;
+
         Ok(())
     }
-}
 
-impl HkxToolsApp {
-    fn new(hkxcmd_path: PathBuf, hkxc_path: PathBuf, hkxconv_path: PathBuf, sse_to_le_hko_path: PathBuf, havok_behavior_post_process_path: PathBuf, hct_standalone_filter_manager_path: PathBuf, hct_filter_manager_dll_path: PathBuf, tokio_handle: tokio::runtime::Handle) -> Self {
-        let bookmarked_folders = Self::load_bookmarks().unwrap_or_default();
-        
-        Self {
-            input_paths: Vec::new(),
-            output_folder: None,
-            skeleton_file: None,
-            output_suffix: String::new(),
-            output_format: OutputFormat::Xml,
-            custom_extension: None,
-            input_file_extension: InputFileExtension::All,
-            converter_tool: ConverterTool::HkxCmd,
-            hkxcmd_path,
-            hkxc_path,
-            hkxconv_path,
-            sse_to_le_hko_path,
-            havok_behavior_post_process_path,
-            hct_standalone_filter_manager_path,
-            hct_filter_manager_dll_path,
-            base_folder: None,
-            output_folder_manually_set: false,
-            bookmarked_folders,
-            conversion_status: ConversionStatus::Idle,
-            progress_rx: None,
-            cancel_tx: None,
-            tokio_handle,
+    /// Parse a `a,b` pair of floats
+    fn parse_pair(value: &str) -> Option<(f32, f32)> {
+        let (a, b) = value.trim().split_once(',')?;
+        let a = a.trim().parse::<f32>().ok()?;
+        let b = b.trim().parse::<f32>().ok()?;
+        if a.is_finite() && b.is_finite() {
+            Some((a, b))
+        } else {
+            None
         }
     }
 
-    /// Check if a file matches the current input filter and tool capabilities
-    fn file_matches_filter(&self, path: &Path) -> bool {
-        if !path.is_file() {
-            return false;
+    /// Build the initial viewport, restoring the saved window size and position
+    fn initial_viewport(&self) -> egui::ViewportBuilder {
+        let default_size = egui::vec2(600.0, 600.0);
+        let size = self
+            .window_size
+            .filter(|size| size.x >= 200.0 && size.y >= 200.0)
+            .unwrap_or(default_size);
+
+        let mut viewport = egui::ViewportBuilder::default().with_inner_size(size);
+        let pixels_per_point = self.window_pixels_per_point.unwrap_or(1.0);
+        if let Some(position) = self.window_position.and_then(|pos| Self::clamp_to_screen(pos, size, pixels_per_point)) {
+            viewport = viewport.with_position(position);
         }
+        viewport
+    }
 
-        match self.input_file_extension {
-            InputFileExtension::All => self.converter_tool.supports_file(path),
-            InputFileExtension::Hkx => {
-                path.extension().map_or(false, |ext| ext == "hkx")
-            }
-            InputFileExtension::Xml => {
-                path.extension().map_or(false, |ext| ext == "xml")
-            }
-            InputFileExtension::Kf => {
-                path.extension().map_or(false, |ext| ext == "kf")
-            }
+    /// Clamp a saved window position so the window is fully on the virtual desktop.
+    ///
+    /// The position and size are in points, the screen metrics in physical pixels, so the metrics are
+    /// divided by the `pixels_per_point` the position was saved with.
+    #[cfg(target_os = "windows")]
+    fn clamp_to_screen(position: egui::Pos2, size: egui::Vec2, pixels_per_point: f32) -> Option<egui::Pos2> {
+        use winapi::um::winuser::{
+            GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
+        };
+
+        // SAFETY: GetSystemMetrics has no preconditions
+        let (left, top, width, height) = unsafe {
+            (
+                GetSystemMetrics(SM_XVIRTUALSCREEN) as f32 / pixels_per_point,
+                GetSystemMetrics(SM_YVIRTUALSCREEN) as f32 / pixels_per_point,
+                GetSystemMetrics(SM_CXVIRTUALSCREEN) as f32 / pixels_per_point,
+                GetSystemMetrics(SM_CYVIRTUALSCREEN) as f32 / pixels_per_point,
+            )
+        };
+        if width <= 0.0 || height <= 0.0 {
+            return None;
         }
+
+        let max_x = (left + width - size.x).max(left);
+        let max_y = (top + height - size.y).max(top);
+        Some(egui::pos2(position.x.clamp(left, max_x), position.y.clamp(top, max_y)))
     }
 
-    /// Create absolute path from relative path
-    fn ensure_absolute_path(path: &Path) -> PathBuf {
-        if path.is_absolute() {
-            path.to_path_buf()
-        } else {
-            std::env::current_dir().unwrap_or_default().join(path)
+    /// Monitor bounds aren't known before the window exists, so let the OS place it
+    #[cfg(not(target_os = "windows"))]
+    fn clamp_to_screen(_position: egui::Pos2, _size: egui::Vec2, _pixels_per_point: f32) -> Option<egui::Pos2> {
+        None
+    }
+}
+
+struct HkxToolsApp {
+    input_paths: Vec<PathBuf>,
+    // Input files checked in the file list for bulk removal
+    selected_input_paths: HashSet<PathBuf>,
+    output_folder: Option<PathBuf>,
+    skeleton_file: Option<PathBuf>,
+    // Per-folder skeletons for mixed-creature KF batches, overriding `skeleton_file`
+    skeleton_rules: Vec<SkeletonRule>,
+    output_suffix: String,
+    output_format: OutputFormat,
+    // Convert each input to several output formats in one run
+    multi_target: bool,
+    multi_target_formats: Vec<OutputFormat>,
+    // Custom extension overrides, keyed by the output format they apply to
+    custom_extensions: HashMap<OutputFormat, String>,
+    input_file_extension: InputFileExtension,
+    converter_tool: ConverterTool,
+    // Each file's tool is picked by `auto_converter_tool`; `converter_tool` is then hkxcmd, whose inputs
+    // and output formats cover every tool Auto routes to
+    auto_tool: bool,
+    // Last output format used with each tool, restored when switching back to it
+    tool_output_formats: HashMap<ConverterTool, OutputFormat>,
+    tool_paths: ToolPaths,
+    // Track base folder for relative path calculations
+    base_folder: Option<PathBuf>,
+    // Track if output folder was manually set by user
+    output_folder_manually_set: bool,
+    // Write all outputs directly into the output folder, ignoring subfolders
+    flatten_output: bool,
+    // Ignore the output folder and write each input's outputs into format subfolders beside it
+    output_next_to_inputs: bool,
+    // Folder selections only add animations in the FNIS/Nemesis layout, and outputs keep their subfolders
+    framework_layout: bool,
+    // Convert each output back to the source format and compare
+    verify_round_trip: bool,
+    // Stop the batch at the first failed file instead of converting the rest
+    abort_on_error: bool,
+    // Remove each input once all of its outputs were written (off by default, enabled only after confirming)
+    delete_sources: bool,
+    // The delete-sources confirmation is showing
+    confirm_delete_sources: bool,
+    // Recursive folder scan adding files to the list, while it runs or waits for confirmation
+    folder_scan: Option<FolderScan>,
+    // Raw hkxcmd `-v:` / hkxc `--format` value replacing the one mapped from the output format
+    version_override: String,
+    // Preset last applied or saved, shown in the preset picker
+    selected_preset: Option<String>,
+    // Name being typed for "Save as Preset", while that field is open
+    new_preset_name: Option<String>,
+    // Bookmarked output folders
+    bookmarked_folders: Vec<PathBuf>,
+    // Persistent settings (window geometry, etc.)
+    settings: AppSettings,
+    // Jobs narrowed to the inputs that failed in the last batch, for "Retry Failed"
+    failed_jobs: Vec<ConversionJob>,
+    // Full error of each file that failed in the last batch, for the error details window
+    error_details: Vec<String>,
+    // Error details window is open
+    show_error_details: bool,
+    // Jobs waiting to be run together with "Run Queue"
+    job_queue: Vec<ConversionJob>,
+    // Live output lines from the conversion tools for the current batch
+    tool_log: Vec<String>,
+    // Output files written by the last batch, listed in the results panel
+    completed_outputs: Vec<CompletedOutput>,
+    // Jobs waiting for the user to skip inputs that are empty or can't be opened (jobs, inputs with the reason)
+    pending_unreadable_inputs: Option<(Vec<ConversionJob>, Vec<UnreadableInput>)>,
+    // Jobs waiting for the user to confirm overwriting source files (jobs, overwrite count)
+    pending_overwrite_confirmation: Option<(Vec<ConversionJob>, usize)>,
+    // Jobs waiting for the user to choose whether to skip outputs of an interrupted run
+    pending_resume_confirmation: Option<(Vec<ConversionJob>, HashSet<PathBuf>)>,
+    // Newer release found by the update check, until the banner is dismissed
+    available_update: Option<ReleaseInfo>,
+    update_rx: Option<oneshot::Receiver<Option<ReleaseInfo>>>,
+    // Sizes and hashes of the bundled tools, computed the first time they're shown
+    bundled_tools: Option<Vec<BundledTool>>,
+    // Names of tools in use whose contents differ from the bundled ones (portable mode), computed with them
+    modified_tools: Vec<&'static str>,
+    // About window with the app version and tool hashes is open
+    show_about: bool,
+    // Result of running each tool with a help argument ("Test Tools"), with the output or error
+    tool_checks: Vec<ToolCheck>,
+    tool_check_rx: Option<oneshot::Receiver<Vec<ToolCheck>>>,
+    // Open "Edit as XML" session, if any
+    xml_edit: Option<XmlEditSession>,
+    // Side panel showing the selected file as XML, while open
+    xml_viewer: Option<XmlViewer>,
+    // Ctrl+P action list, while it's open
+    command_palette: Option<CommandPalette>,
+    // Notice about dropped files the tool can't take, and when it was shown
+    drop_notice: Option<(String, std::time::Instant)>,
+    // Hot folder converted automatically, while watching
+    watch_folder: Option<WatchFolder>,
+    // BSA/BA2 archives whose matching files were extracted into the input list
+    archive_extractions: Vec<ArchiveExtraction>,
+    // Archives still being extracted, their files added once done
+    pending_archives: Vec<PendingArchive>,
+    // Input lists from before the last few removals, newest last
+    input_undo: Vec<InputListSnapshot>,
+    // Size and format of listed input files, filled in as rows are shown
+    input_file_info: HashMap<PathBuf, InputFileInfo>,
+    // Output format picked for single files in the list, used instead of the selected formats
+    format_overrides: HashMap<PathBuf, OutputFormat>,
+    // Column and direction (ascending) of the last sort applied to the input list
+    input_sort: Option<(FileSortColumn, bool)>,
+    // Text the file list is narrowed to (case-insensitive, on the displayed path); hidden rows stay in `input_paths`
+    input_filter: String,
+    // Conversion requested on the command line, until it has started (or finished, with --exit-after)
+    auto_run: Option<AutoRun>,
+    // Percent shown in the window title while a batch runs
+    title_progress: Option<usize>,
+    // When the last batch ended and whether it failed, while the window border flashes
+    batch_end_flash: Option<(std::time::Instant, bool)>,
+    // Async operation fields
+    conversion_status: ConversionStatus,
+    progress_rx: Option<mpsc::UnboundedReceiver<ConversionProgress>>,
+    cancel_tx: Option<oneshot::Sender<()>>,
+    // Final status of a cancelled batch, shown once every file has stopped
+    cancel_outcome: Option<ConversionStatus>,
+    // Shared with the running batch to pause and resume it
+    pause_gate: Option<Arc<PauseGate>>,
+    // Running/queued/done counts of the current batch
+    worker_counts: Option<Arc<WorkerCounts>>,
+    tokio_handle: tokio::runtime::Handle,
+}
+
+/// Size and detected format of an input file, read once and cached for the file list
+#[derive(Debug, Clone)]
+struct InputFileInfo {
+    size: Option<u64>,
+    // Format read from the header, which picks the tool in Auto mode
+    detected: Option<OutputFormat>,
+    format: String,
+}
+
+impl InputFileInfo {
+    fn read(path: &Path) -> Self {
+        let detected = OutputFormat::detect_from_file(path);
+        let format = match detected {
+            Some(format) => format.label().to_string(),
+            // Tagfile HKX and HKT aren't detected from the header; show the extension instead
+            None => path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_uppercase())
+                .unwrap_or_default(),
+        };
+        Self {
+            size: fs::metadata(path).map(|metadata| metadata.len()).ok(),
+            detected,
+            format,
         }
     }
+}
 
-    /// Open a folder in the system file explorer
-    fn open_folder_in_explorer(folder_path: &Path) {
-        #[cfg(target_os = "windows")]
-        {
-            if let Err(e) = std::process::Command::new("explorer")
-                .arg(folder_path)
-                .spawn()
-            {
-                eprintln!("Failed to open folder in explorer: {}", e);
-            }
+/// Column the input list is sorted by
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileSortColumn {
+    Name,
+    Size,
+    Format,
+}
+
+/// Stage of the "Edit as XML" workflow
+#[derive(Debug, Clone, PartialEq)]
+enum XmlEditStage {
+    Decompiling,
+    Editing,
+    Recompiling,
+    Recompiled,
+    Failed(String),
+}
+
+/// A source file being edited as XML in a temp folder, then compiled back over the source
+struct XmlEditSession {
+    source: PathBuf,
+    original_format: OutputFormat,
+    converter_tool: ConverterTool,
+    xml_path: PathBuf,
+    // Holds the working folder for as long as the session is open
+    work_dir: OwnedTempDir,
+    stage: XmlEditStage,
+    // Modification time of the XML when it was last checked
+    last_modified: Option<std::time::SystemTime>,
+    // The XML was saved since it was decompiled or last recompiled
+    saved: bool,
+    result_rx: Option<oneshot::Receiver<Result<(), String>>>,
+}
+
+/// Read-only XML view of the selected input, converted from HKX in a temp folder when needed
+struct XmlViewer {
+    source: PathBuf,
+    // The XML once loaded, cut to XML_VIEWER_MAX_BYTES, or why it couldn't be
+    content: Option<Result<String, String>>,
+    truncated: bool,
+    result_rx: Option<oneshot::Receiver<Result<String, String>>>,
+    search: String,
+    // Byte ranges of the search matches, for the search text they were found for
+    matches: Vec<std::ops::Range<usize>>,
+    matches_for: String,
+    current_match: usize,
+    scroll_to_match: bool,
+    // Highlighted text, laid out again only when it no longer fits what is shown
+    layout: Option<XmlViewerLayout>,
+}
+
+/// The XML viewer's laid-out text, with the search, current match and theme it was built for
+struct XmlViewerLayout {
+    key: (String, usize, bool),
+    // Font atlas holding the glyphs, which egui replaces when the scale changes or it fills up
+    atlas: std::sync::Weak<egui::mutex::Mutex<egui::epaint::TextureAtlas>>,
+    galley: Arc<egui::Galley>,
+}
+
+/// A recursive folder scan running on the tokio runtime, streaming matching files back to the input list
+struct FolderScan {
+    folder: PathBuf,
+    // Batches of matching files, closed when the scan ends
+    files_rx: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+    cancelled: Arc<AtomicBool>,
+    finished: bool,
+    // Paths already listed or found, so each is added once
+    seen: HashSet<PathBuf>,
+    // Files this scan added to the list
+    added: Vec<PathBuf>,
+    // Files found past the Max Files limit, held back until the user confirms
+    held: Vec<PathBuf>,
+    // The user agreed to add more files than the limit
+    confirmed: bool,
+    // Base folder before the scan, restored if its files are discarded
+    previous_base_folder: Option<PathBuf>,
+}
+
+/// Files extracted from a BSA/BA2 archive so they can be converted like any other input.
+///
+/// The extraction folder is the base folder of its files, so their outputs mirror the archive's
+/// layout (e.g. `meshes/actors/...`) whatever the base folder of the other inputs is.
+struct ArchiveExtraction {
+    archive: PathBuf,
+    // Holds the extracted files until the input list is cleared
+    dir: OwnedTempDir,
+}
+
+/// Extraction folder and files of an archive, or why none were extracted
+type ArchiveExtractResult = Result<(OwnedTempDir, Vec<PathBuf>), String>;
+
+/// A BSA/BA2 archive being extracted on the tokio runtime
+struct PendingArchive {
+    archive: PathBuf,
+    cancelled: Arc<AtomicBool>,
+    result_rx: oneshot::Receiver<ArchiveExtractResult>,
+}
+
+/// What happened to a file offered to the input list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AddFileOutcome {
+    Added,
+    // Already in the list
+    DuplicateSkipped,
+    // Missing, or not taken by the input filter and tool
+    FilterSkipped,
+}
+
+/// How many files one action added to the input list, and how many it skipped for each reason
+#[derive(Debug, Default, Clone, Copy)]
+struct AddCounts {
+    added: usize,
+    duplicates: usize,
+    filtered: usize,
+}
+
+impl AddCounts {
+    fn count(&mut self, outcome: AddFileOutcome) {
+        match outcome {
+            AddFileOutcome::Added => self.added += 1,
+            AddFileOutcome::DuplicateSkipped => self.duplicates += 1,
+            AddFileOutcome::FilterSkipped => self.filtered += 1,
         }
-        
-        #[cfg(target_os = "macos")]
-        {
-            if let Err(e) = std::process::Command::new("open")
-                .arg(folder_path)
-                .spawn()
-            {
-                eprintln!("Failed to open folder in Finder: {}", e);
+    }
+
+    fn add(&mut self, other: AddCounts) {
+        self.added += other.added;
+        self.duplicates += other.duplicates;
+        self.filtered += other.filtered;
+    }
+}
+
+/// Input list as it was before a Clear All or removal, for Ctrl+Z
+struct InputListSnapshot {
+    input_paths: Vec<PathBuf>,
+    base_folder: Option<PathBuf>,
+    output_folder_manually_set: bool,
+    format_overrides: HashMap<PathBuf, OutputFormat>,
+    // Extractions dropped by Clear All, kept so their files still exist when undone
+    archive_extractions: Vec<ArchiveExtraction>,
+}
+
+/// Number of input list changes Ctrl+Z can go back through
+const INPUT_UNDO_LIMIT: usize = 10;
+
+/// Something the Ctrl+P command palette can do, each running the same code as its button
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PaletteAction {
+    BrowseFiles,
+    ImportList,
+    SelectFolder,
+    SelectFolderRecursive,
+    ClearAll,
+    UndoListChange,
+    SwitchTool(ConverterTool),
+    RunConversion,
+    AddToQueue,
+    ExportCommands,
+    RunQueue,
+    RetryFailed,
+    OpenOutputFolder,
+    About,
+}
+
+impl PaletteAction {
+    fn label(&self) -> String {
+        match self {
+            Self::BrowseFiles => "Browse Files".to_string(),
+            Self::ImportList => "Import List".to_string(),
+            Self::SelectFolder => "Select Folder".to_string(),
+            Self::SelectFolderRecursive => "Select Folder (+ Subfolders)".to_string(),
+            Self::ClearAll => "Clear All Files".to_string(),
+            Self::UndoListChange => "Undo List Change".to_string(),
+            Self::SwitchTool(tool) => format!("Switch to {}", tool.label()),
+            Self::RunConversion => "Run Conversion".to_string(),
+            Self::AddToQueue => "Add to Queue".to_string(),
+            Self::ExportCommands => "Export Commands".to_string(),
+            Self::RunQueue => "Run Queue".to_string(),
+            Self::RetryFailed => "Retry Failed".to_string(),
+            Self::OpenOutputFolder => "Open Output Folder".to_string(),
+            Self::About => "About / Tool Versions".to_string(),
+        }
+    }
+}
+
+/// A folder whose new and modified files are converted automatically with the current settings
+struct WatchFolder {
+    folder: PathBuf,
+    // Dropping the watcher stops watching
+    _watcher: notify::RecommendedWatcher,
+    events_rx: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+    // Changed files waiting to settle: when they last changed and their size at the last check
+    pending: HashMap<PathBuf, (std::time::Instant, Option<u64>)>,
+    // Settled files waiting for the running batch to finish
+    ready: Vec<PathBuf>,
+    // Outputs of watch batches until they're written and settle, so writing them doesn't trigger
+    // another conversion
+    produced: HashSet<PathBuf>,
+}
+
+impl WatchFolder {
+    fn start(folder: PathBuf) -> notify::Result<Self> {
+        use notify::Watcher;
+
+        let (events_tx, events_rx) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(events_tx)?;
+        watcher.watch(&folder, notify::RecursiveMode::NonRecursive)?;
+        Ok(Self {
+            folder,
+            _watcher: watcher,
+            events_rx,
+            pending: HashMap::new(),
+            ready: Vec::new(),
+            produced: HashSet::new(),
+        })
+    }
+
+    /// Note changed files, and move the ones that stopped changing to `ready`
+    fn poll(&mut self) {
+        while let Ok(event) = self.events_rx.try_recv() {
+            match event {
+                Ok(event) if matches!(event.kind, notify::EventKind::Create(_) | notify::EventKind::Modify(_)) => {
+                    for path in event.paths {
+                        if !self.ready.contains(&path) {
+                            self.pending.insert(path, (std::time::Instant::now(), None));
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Watch folder error: {}", e),
             }
         }
-        
-        #[cfg(target_os = "linux")]
-        {
-            if let Err(e) = std::process::Command::new("xdg-open")
-                .arg(folder_path)
-                .spawn()
-            {
-                eprintln!("Failed to open folder in file manager: {}", e);
+
+        // A file has settled once it went a while without events and its size stayed the same
+        let mut settled = Vec::new();
+        self.pending.retain(|path, (last_change, last_size)| {
+            if last_change.elapsed() < WATCH_SETTLE_TIME {
+                return true;
+            }
+            // Deleted or moved away before it settled
+            let Ok(metadata) = fs::metadata(path) else {
+                self.produced.remove(path);
+                return false;
+            };
+            if *last_size == Some(metadata.len()) {
+                settled.push(path.clone());
+                return false;
+            }
+            *last_change = std::time::Instant::now();
+            *last_size = Some(metadata.len());
+            true
+        });
+        for path in settled {
+            // A watch batch's output is only ignored until it has been written
+            if !self.produced.remove(&path) {
+                self.ready.push(path);
             }
         }
     }
+}
 
-    /// Show a tooltip for a converter tool
-    fn show_tool_tooltip(&self, ui: &mut Ui, tool: ConverterTool, hover_pos: egui::Pos2) {
-        let tooltip_text = tool.help_text();
-        
-        // Get screen bounds to ensure tooltip doesn't go off-screen
-        let screen_rect = ui.ctx().screen_rect();
-        
-        // Calculate dynamic tooltip width based on available space
-        let max_tooltip_width = 300.0; // Maximum width
-        let min_tooltip_width = 200.0; // Minimum width
-        let available_width = screen_rect.width() - 40.0; // Leave 20px margin on each side
-        
-        // Choose the best width: available space, but not less than minimum or more than maximum
-        let tooltip_width = available_width
-            .max(min_tooltip_width)
-            .min(max_tooltip_width);
-        
-        // Calculate position for the tooltip with better positioning logic
-        let mut tooltip_pos = egui::Pos2::new(hover_pos.x + 20.0, hover_pos.y - 10.0);
-        
-        // Ensure tooltip doesn't go off the right edge of the screen
-        if tooltip_pos.x + tooltip_width > screen_rect.right() {
-            tooltip_pos.x = hover_pos.x - tooltip_width - 20.0;
+/// Open command palette: the filter text and the highlighted match
+#[derive(Debug, Default)]
+struct CommandPalette {
+    query: String,
+    selected: usize,
+}
+
+/// A set of input files converted with one tool and one snapshot of the output settings
+#[derive(Debug, Clone)]
+struct ConversionJob {
+    converter_tool: ConverterTool,
+    // Each file is converted with `auto_converter_tool`'s pick instead of `converter_tool`
+    auto_tool: bool,
+    output_formats: Vec<OutputFormat>,
+    // Inputs converted to one other format instead of `output_formats`, picked in the file list
+    format_overrides: HashMap<PathBuf, OutputFormat>,
+    input_paths: Vec<PathBuf>,
+    output_folder: PathBuf,
+    skeleton_file: Option<PathBuf>,
+    skeleton_rules: Vec<SkeletonRule>,
+    output_suffix: String,
+    suffix_separator: String,
+    custom_extensions: HashMap<OutputFormat, String>,
+    base_folder: Option<PathBuf>,
+    // Extraction folders of archives, the base folder of the inputs inside them
+    archive_dirs: Vec<PathBuf>,
+    flatten_output: bool,
+    // Outputs go into a format subfolder of each input's own folder; `output_folder` is then only the
+    // folder the batch started from
+    next_to_inputs: bool,
+    verify_round_trip: bool,
+    abort_on_error: bool,
+    delete_sources: bool,
+    version_override: Option<String>,
+    filename_case: FilenameCase,
+    // Output path layout with placeholders, used instead of the base folder/flatten layout
+    output_template: Option<String>,
+    reformat_xml: bool,
+    wine: Option<PathBuf>,
+    atomic_output: bool,
+    // Outputs an interrupted run of this batch already wrote, left alone when resuming
+    skip_outputs: HashSet<PathBuf>,
+    // Longest a single tool run may take before it's killed
+    timeout: Option<std::time::Duration>,
+}
+
+impl ConversionJob {
+    /// Short description for the queue list and the tool log
+    fn label(&self) -> String {
+        let formats: Vec<&str> = self.output_formats.iter().map(|format| format.label()).collect();
+        format!(
+            "{} → {} ({} file{})",
+            if self.auto_tool { AUTO_TOOL_LABEL } else { self.converter_tool.label() },
+            formats.join(", "),
+            self.input_paths.len(),
+            if self.input_paths.len() == 1 { "" } else { "s" }
+        )
+    }
+
+    /// Number of files this job writes (one per input per output format)
+    fn output_count(&self) -> usize {
+        self.outputs().count()
+    }
+
+    /// Output formats written for `input_path`: its override from the file list, or the job's formats
+    fn formats_for(&self, input_path: &Path) -> &[OutputFormat] {
+        match self.format_overrides.get(input_path) {
+            Some(format) => std::slice::from_ref(format),
+            None => &self.output_formats,
         }
-        
-        // Calculate available vertical space above and below the hover position
-        let space_above = hover_pos.y - screen_rect.top();
-        let space_below = screen_rect.bottom() - hover_pos.y;
-        
-        // Estimate tooltip height (will be calculated more accurately by the layout)
-        let estimated_height = 120.0;
-        
-        // Choose the best vertical position based on available space
-        if space_below >= estimated_height {
-            // Position below the hover point (default)
-            tooltip_pos.y = hover_pos.y + 10.0;
-        } else if space_above >= estimated_height {
-            // Position above the hover point
-            tooltip_pos.y = hover_pos.y - estimated_height - 10.0;
+    }
+
+    /// Tool converting `input_path` to `output_format`
+    fn tool_for(&self, input_path: &Path, output_format: OutputFormat) -> ConverterTool {
+        if self.auto_tool {
+            auto_converter_tool(input_path, output_format)
         } else {
-            // Not enough space in either direction, position to maximize visibility
-            if space_below > space_above {
-                // More space below, position at bottom edge
-                tooltip_pos.y = screen_rect.bottom() - estimated_height - 10.0;
-            } else {
-                // More space above, position at top edge
-                tooltip_pos.y = screen_rect.top() + 10.0;
-            }
+            self.converter_tool
         }
-        
-        // Final bounds checking to ensure tooltip is fully visible
-        if tooltip_pos.y < screen_rect.top() {
-            tooltip_pos.y = screen_rect.top() + 10.0;
+    }
+
+    /// Every input with each format it's converted to, in conversion order
+    fn outputs(&self) -> impl Iterator<Item = (&PathBuf, OutputFormat)> {
+        self.input_paths
+            .iter()
+            .flat_map(move |input_path| self.formats_for(input_path).iter().map(move |&format| (input_path, format)))
+    }
+
+    /// Folder whose layout `input_path` keeps in the output: its archive's, or the base folder
+    fn base_folder_for(&self, input_path: &Path) -> Option<&Path> {
+        self.archive_dirs.iter().find(|dir| input_path.starts_with(dir)).or(self.base_folder.as_ref()).map(PathBuf::as_path)
+    }
+
+    /// Output path for one input and format, putting each format in its own subfolder when producing
+    /// more than one (unless the output template places them with `{format}`)
+    fn output_path(&self, input_path: &Path, output_format: OutputFormat) -> Option<PathBuf> {
+        let mut layout = OutputLayout {
+            output_suffix: &self.output_suffix,
+            suffix_separator: &self.suffix_separator,
+            base_folder: self.base_folder_for(input_path),
+            flatten_output: self.flatten_output,
+            filename_case: self.filename_case,
+            ..OutputLayout::new(&self.output_folder, &self.custom_extensions)
+        };
+        let input_folder;
+        let format_output_folder;
+        if self.next_to_inputs {
+            input_folder = ensure_absolute_path(input_path).parent()?.to_path_buf();
+            format_output_folder = input_folder.join(output_format.folder_name());
+            layout.output_folder = &format_output_folder;
+            // The base folder only matters to {reldir}, which is empty next to the input
+            layout.base_folder = Some(&input_folder);
+            layout.flatten_output = true;
+        } else if self.output_formats.len() > 1
+            && !self.output_template.as_ref().is_some_and(|template| template.contains("{format}"))
+        {
+            format_output_folder = self.output_folder.join(output_format.folder_name());
+            layout.output_folder = &format_output_folder;
         }
-        if tooltip_pos.y + estimated_height > screen_rect.bottom() {
-            tooltip_pos.y = screen_rect.bottom() - estimated_height - 10.0;
+        match &self.output_template {
+            Some(template) => {
+                get_templated_output_path(input_path, template, output_format, self.tool_for(input_path, output_format), &layout)
+            }
+            None => get_output_path(input_path, output_format, &layout),
         }
-        
-        // Create a tooltip area
-        egui::Area::new("tool_tooltip".into())
-            .fixed_pos(tooltip_pos)
-            .order(egui::Order::Tooltip)
-            .show(ui.ctx(), |ui| {
-                // Background panel with dynamic width for proper wrapping
-                egui::Frame::none()
-                    .fill(ui.visuals().extreme_bg_color)
-                    .stroke(egui::Stroke::new(1.0, ui.visuals().strong_text_color()))
-                    .rounding(4.0)
-                    .show(ui, |ui| {
-                        ui.allocate_ui_with_layout(
-                            egui::Vec2::new(tooltip_width, 0.0),
-                            egui::Layout::top_down(egui::Align::LEFT),
-                            |ui| {
-                                ui.add_space(8.0);
-                                
-                                // Tool name with left and right margins
-                                ui.horizontal(|ui| {
-                                    ui.add_space(8.0);
-                                    ui.label(
-                                        RichText::new(tool.label())
-                                            .size(14.0)
-                                            .strong()
-                                            .color(ui.visuals().strong_text_color())
-                                    );
-                                    ui.add_space(8.0);
-                                });
-                                
-                                ui.add_space(4.0);
-                                
-                                // Help text with left and right margins and wrapping
-                                ui.horizontal(|ui| {
-                                    ui.add_space(8.0);
-                                    // Constrain the label width to force text wrapping
-                                    ui.allocate_ui_with_layout(
-                                        egui::Vec2::new(tooltip_width - 16.0, 0.0), // Subtract margins
-                                        egui::Layout::top_down(egui::Align::LEFT),
-                                        |ui| {
-                                            ui.label(
-                                                RichText::new(tooltip_text)
-                                                    .size(12.0)
-                                                    .color(ui.visuals().text_color())
-                                            );
-                                        }
-                                    );
-                                    ui.add_space(8.0);
-                                });
-                                
-                                ui.add_space(8.0);
-                            }
-                        );
-                    });
-            });
     }
 
-    /// Get available output formats for the current tool
-    fn available_output_formats(&self) -> Vec<OutputFormat> {
-        self.converter_tool.available_output_formats()
+    /// Copy of this job restricted to some of its inputs
+    fn with_inputs(&self, input_paths: Vec<PathBuf>) -> Self {
+        Self {
+            input_paths,
+            ..self.clone()
+        }
     }
+}
 
-    /// Get the path to the bookmarks file (relative to the executable)
-    fn get_bookmarks_file_path() -> PathBuf {
-        // Get the directory where the executable is located
+/// Outputs finished by the running batch, kept next to the executable so an interrupted batch can resume.
+///
+/// The first line identifies the batch (`batch=<hash of its tools, settings, inputs and outputs>`),
+/// then each output that converted (and verified, when checked) is appended as `done=<path>`. The file is removed when a batch runs to the end.
+struct BatchJournal {
+    file: std::sync::Mutex<fs::File>,
+}
+
+impl BatchJournal {
+    fn get_journal_file_path() -> PathBuf {
         if let Ok(exe_path) = std::env::current_exe() {
             if let Some(exe_dir) = exe_path.parent() {
-                return exe_dir.join("bookmarked_output_folders.txt");
+                return exe_dir.join("batch_journal.txt");
             }
         }
         // Fallback to current directory
-        PathBuf::from("bookmarked_output_folders.txt")
+        PathBuf::from("batch_journal.txt")
     }
 
-    /// Load bookmarks from file
-    fn load_bookmarks() -> Result<Vec<PathBuf>> {
-        let bookmark_file = Self::get_bookmarks_file_path();
-        
-        if !bookmark_file.exists() {
-            return Ok(Vec::new());
+    /// Identifies a batch by what it converts, how and where to, ignoring outputs skipped on resume.
+    /// Inputs edited since the last run change it, so their outputs aren't taken as done.
+    fn fingerprint(jobs: &[ConversionJob]) -> String {
+        let mut hasher = Sha256::new();
+        for job in jobs {
+            hasher.update(job.label().as_bytes());
+            hasher.update(format!("\0{:?}\0{}\n", job.version_override, job.reformat_xml).as_bytes());
+            for (input_path, output_format) in job.outputs() {
+                hasher.update(input_path.to_string_lossy().as_bytes());
+                hasher.update(b"\0");
+                let modified = fs::metadata(input_path).and_then(|metadata| metadata.modified()).ok();
+                if let Some(since_epoch) = modified.and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok()) {
+                    hasher.update(since_epoch.as_nanos().to_string().as_bytes());
+                }
+                hasher.update(b"\0");
+                if let Some(skeleton) = skeleton_for_input(input_path, &job.skeleton_rules, job.skeleton_file.as_deref()) {
+                    hasher.update(skeleton.to_string_lossy().as_bytes());
+                }
+                hasher.update(b"\0");
+                if let Some(output_path) = job.output_path(input_path, output_format) {
+                    hasher.update(output_path.to_string_lossy().as_bytes());
+                }
+                hasher.update(b"\n");
+            }
         }
-        
-        let content = fs::read_to_string(&bookmark_file)
-            .context("Failed to read bookmarks file")?;
-        
-        let bookmarks: Vec<PathBuf> = content
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(|line| PathBuf::from(line.trim()))
-            .filter(|path| path.exists()) // Only keep existing paths
-            .collect();
-        
-        Ok(bookmarks)
+        format!("{:x}", hasher.finalize())
     }
 
-    /// Save bookmarks to file
-    fn save_bookmarks(&self) -> Result<()> {
-        let bookmark_file = Self::get_bookmarks_file_path();
-        
-        let content: String = self.bookmarked_folders
-            .iter()
-            .map(|path| path.to_string_lossy().to_string())
-            .collect::<Vec<_>>()
-            .join("\n");
-        
-        fs::write(&bookmark_file, content)
-            .context("Failed to save bookmarks file")?;
-        
-        Ok(())
+    /// Outputs an earlier, unfinished run of the same batch already wrote, if they're still there
+    fn completed_outputs(jobs: &[ConversionJob]) -> HashSet<PathBuf> {
+        let Ok(content) = fs::read_to_string(Self::get_journal_file_path()) else {
+            return HashSet::new();
+        };
+        let mut lines = content.lines();
+        if lines.next() != Some(format!("batch={}", Self::fingerprint(jobs)).as_str()) {
+            return HashSet::new();
+        }
+        lines
+            .filter_map(|line| line.strip_prefix("done="))
+            .map(PathBuf::from)
+            .filter(|path| path.is_file())
+            .collect()
     }
 
-    /// Add current output folder to bookmarks
-    fn bookmark_current_folder(&mut self) {
-        if let Some(ref folder) = self.output_folder {
-            if !self.bookmarked_folders.contains(folder) {
-                self.bookmarked_folders.push(folder.clone());
-                if let Err(e) = self.save_bookmarks() {
-                    eprintln!("Failed to save bookmarks: {}", e);
-                }
-            }
+    /// Continue the journal of the same batch, or start a new one in its place
+    fn start(jobs: &[ConversionJob]) -> Result<Self> {
+        let path = Self::get_journal_file_path();
+        let header = format!("batch={}", Self::fingerprint(jobs));
+        let same_batch = fs::read_to_string(&path).is_ok_and(|content| content.lines().next() == Some(header.as_str()));
+        let file = if same_batch {
+            fs::OpenOptions::new().append(true).open(&path)
+        } else {
+            fs::write(&path, format!("{}\n", header)).and_then(|_| fs::OpenOptions::new().append(true).open(&path))
         }
+        .context("Failed to open the batch journal")?;
+        Ok(Self {
+            file: std::sync::Mutex::new(file),
+        })
     }
 
-    /// Remove current output folder from bookmarks
-    fn unbookmark_current_folder(&mut self) {
-        if let Some(ref folder) = self.output_folder {
-            self.bookmarked_folders.retain(|f| f != folder);
-            if let Err(e) = self.save_bookmarks() {
-                eprintln!("Failed to save bookmarks: {}", e);
+    fn record(&self, output_path: &Path) {
+        let mut file = self.file.lock().unwrap();
+        if let Err(e) = writeln!(file, "done={}", output_path.to_string_lossy()) {
+            eprintln!("Failed to write the batch journal: {}", e);
+        }
+    }
+
+    /// Forget the batch once it has run to the end
+    fn remove() {
+        let path = Self::get_journal_file_path();
+        if path.exists() {
+            if let Err(e) = fs::remove_file(&path) {
+                eprintln!("Failed to remove the batch journal: {}", e);
             }
         }
     }
+}
 
-    /// Check if current output folder is bookmarked
-    fn is_current_folder_bookmarked(&self) -> bool {
-        if let Some(ref folder) = self.output_folder {
-            self.bookmarked_folders.contains(folder)
-        } else {
-            false
+/// Result of successfully converting one file
+#[derive(Debug, Clone)]
+struct ConvertedFile {
+    input_bytes: u64,
+    output_bytes: u64,
+    verify: VerifyOutcome,
+    sha256: Option<String>,
+}
+
+/// An output written by a batch, with its hash for checking conversions are reproducible
+#[derive(Debug, Clone)]
+struct CompletedOutput {
+    /// Position of the file in the batch, so results list in input order
+    index: usize,
+    path: PathBuf,
+    sha256: Option<String>,
+    /// Left by an interrupted run of the batch and skipped this time, so nothing vouches for it
+    resumed: bool,
+}
+
+impl Default for HkxToolsApp {
+    fn default() -> Self {
+        Self {
+            input_paths: Vec::new(),
+            selected_input_paths: HashSet::new(),
+            output_folder: None,
+            skeleton_file: None,
+            skeleton_rules: Vec::new(),
+            output_suffix: String::new(),
+            output_format: OutputFormat::Xml,
+            multi_target: false,
+            multi_target_formats: Vec::new(),
+            custom_extensions: HashMap::new(),
+            input_file_extension: InputFileExtension::All,
+            converter_tool: ConverterTool::HkxCmd,
+            auto_tool: false,
+            tool_output_formats: HashMap::new(),
+            tool_paths: ToolPaths::default(),
+            base_folder: None,
+            output_folder_manually_set: false,
+            flatten_output: false,
+            output_next_to_inputs: false,
+            framework_layout: false,
+            verify_round_trip: false,
+            abort_on_error: false,
+            delete_sources: false,
+            confirm_delete_sources: false,
+            folder_scan: None,
+            version_override: String::new(),
+            selected_preset: None,
+            new_preset_name: None,
+            bookmarked_folders: Vec::new(),
+            settings: AppSettings::default(),
+            failed_jobs: Vec::new(),
+            job_queue: Vec::new(),
+            tool_log: Vec::new(),
+            completed_outputs: Vec::new(),
+            pending_unreadable_inputs: None,
+            pending_overwrite_confirmation: None,
+            pending_resume_confirmation: None,
+            available_update: None,
+            update_rx: None,
+            bundled_tools: None,
+            modified_tools: Vec::new(),
+            show_about: false,
+            tool_checks: Vec::new(),
+            tool_check_rx: None,
+            error_details: Vec::new(),
+            show_error_details: false,
+            xml_edit: None,
+            xml_viewer: None,
+            command_palette: None,
+            drop_notice: None,
+            watch_folder: None,
+            archive_extractions: Vec::new(),
+            pending_archives: Vec::new(),
+            input_undo: Vec::new(),
+            input_file_info: HashMap::new(),
+            format_overrides: HashMap::new(),
+            input_sort: None,
+            input_filter: String::new(),
+            auto_run: None,
+            title_progress: None,
+            batch_end_flash: None,
+            conversion_status: ConversionStatus::Idle,
+            progress_rx: None,
+            cancel_tx: None,
+            cancel_outcome: None,
+            pause_gate: None,
+            worker_counts: None,
+            tokio_handle: tokio::runtime::Handle::current(),
         }
     }
+}
 
-    fn add_files_from_folder(&mut self, folder: &Path, recursive: bool) -> Result<()> {
-        // Set the base folder for relative path calculations
-        self.base_folder = Some(folder.to_path_buf());
-        
-        if recursive {
-            self.add_files_recursive(folder)
-        } else {
-            self.add_files_non_recursive(folder)
+/// Sends lines of tool output for one file through the progress channel
+#[derive(Clone)]
+struct ToolOutputForwarder {
+    progress_tx: mpsc::UnboundedSender<ConversionProgress>,
+    file_name: String,
+    file_index: usize,
+    total_files: usize,
+}
+
+impl ToolOutputForwarder {
+    fn forward(&self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
         }
+        let _ = self.progress_tx.send(
+            ConversionProgress::item_running(&self.file_name, self.file_index, self.total_files)
+                .with_log_line(format!("[{}/{} {}] {}", self.file_index + 1, self.total_files, self.file_name, line)),
+        );
     }
+}
 
-    fn add_files_non_recursive(&mut self, folder: &Path) -> Result<()> {
-        let entries = fs::read_dir(folder).context("Failed to read directory")?;
+impl HkxToolsApp {
+    fn new(tool_paths: ToolPaths, tokio_handle: tokio::runtime::Handle) -> Self {
+        let bookmarked_folders = Self::load_bookmarks().unwrap_or_default();
+        let settings = AppSettings::load().unwrap_or_default();
+        let check_for_updates = settings.check_for_updates;
 
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-            if self.file_matches_filter(&path) && !self.input_paths.contains(&path) {
-                self.input_paths.push(path);
+        let mut app = Self {
+            input_paths: Vec::new(),
+            selected_input_paths: HashSet::new(),
+            output_folder: None,
+            skeleton_file: None,
+            skeleton_rules: Vec::new(),
+            output_suffix: String::new(),
+            output_format: OutputFormat::Xml,
+            multi_target: false,
+            multi_target_formats: Vec::new(),
+            custom_extensions: HashMap::new(),
+            input_file_extension: InputFileExtension::All,
+            converter_tool: ConverterTool::HkxCmd,
+            auto_tool: false,
+            tool_output_formats: HashMap::new(),
+            tool_paths,
+            base_folder: None,
+            output_folder_manually_set: false,
+            flatten_output: false,
+            output_next_to_inputs: false,
+            framework_layout: false,
+            verify_round_trip: false,
+            abort_on_error: false,
+            delete_sources: false,
+            confirm_delete_sources: false,
+            folder_scan: None,
+            version_override: String::new(),
+            selected_preset: None,
+            new_preset_name: None,
+            bookmarked_folders,
+            settings,
+            failed_jobs: Vec::new(),
+            job_queue: Vec::new(),
+            tool_log: Vec::new(),
+            completed_outputs: Vec::new(),
+            pending_unreadable_inputs: None,
+            pending_overwrite_confirmation: None,
+            pending_resume_confirmation: None,
+            available_update: None,
+            update_rx: None,
+            bundled_tools: None,
+            modified_tools: Vec::new(),
+            show_about: false,
+            tool_checks: Vec::new(),
+            tool_check_rx: None,
+            error_details: Vec::new(),
+            show_error_details: false,
+            xml_edit: None,
+            xml_viewer: None,
+            command_palette: None,
+            drop_notice: None,
+            watch_folder: None,
+            archive_extractions: Vec::new(),
+            pending_archives: Vec::new(),
+            input_undo: Vec::new(),
+            input_file_info: HashMap::new(),
+            format_overrides: HashMap::new(),
+            input_sort: None,
+            input_filter: String::new(),
+            auto_run: None,
+            title_progress: None,
+            batch_end_flash: None,
+            conversion_status: ConversionStatus::Idle,
+            progress_rx: None,
+            cancel_tx: None,
+            cancel_outcome: None,
+            pause_gate: None,
+            worker_counts: None,
+            tokio_handle,
+        };
+        if check_for_updates {
+            app.start_update_check();
+        }
+        app
+    }
+
+    /// Look for a newer release in the background
+    fn start_update_check(&mut self) {
+        let (update_tx, update_rx) = oneshot::channel();
+        self.update_rx = Some(update_rx);
+        self.tokio_handle.spawn_blocking(move || {
+            let update = match check_for_update(env!("CARGO_PKG_VERSION")) {
+                Ok(update) => update,
+                Err(e) => {
+                    // Being offline is normal, so just log it
+                    eprintln!("Update check failed: {:#}", e);
+                    None
+                }
+            };
+            let _ = update_tx.send(update);
+        });
+    }
+
+    /// Run every tool with a help argument in the background, to tell a tool that won't start
+    /// (blocked by antivirus, missing VC++ runtime, no Wine) from a file it can't convert
+    fn start_tool_check(&mut self) {
+        let (check_tx, check_rx) = oneshot::channel();
+        self.tool_check_rx = Some(check_rx);
+        self.tool_checks.clear();
+        let tool_paths = self.tool_paths.clone();
+        let wine = self.settings.wine.clone();
+        self.tokio_handle.spawn(async move {
+            let mut checks = Vec::new();
+            for tool in ALL_TOOLS {
+                let result = Converter::new(tool, OutputFormat::Xml, tool_paths.clone())
+                    .with_wine(wine.clone())
+                    .with_timeout(Some(TOOL_CHECK_TIMEOUT))
+                    .check_tool()
+                    .await
+                    .map_err(|e| format!("{:#}", e));
+                checks.push((tool, result));
+            }
+            let _ = check_tx.send(checks);
+        });
+    }
+
+    /// Pick up the results of a finished "Test Tools" run
+    fn poll_tool_check(&mut self) {
+        if let Some(check_rx) = &mut self.tool_check_rx {
+            match check_rx.try_recv() {
+                Ok(checks) => {
+                    self.tool_checks = checks;
+                    self.tool_check_rx = None;
+                }
+                Err(oneshot::error::TryRecvError::Empty) => {}
+                Err(oneshot::error::TryRecvError::Closed) => self.tool_check_rx = None,
             }
         }
-        Ok(())
     }
 
-    fn add_files_recursive(&mut self, folder: &Path) -> Result<()> {
-        for entry in walkdir::WalkDir::new(folder).follow_links(true) {
-            let entry = entry?;
-            let path = entry.path().to_path_buf();
-            if self.file_matches_filter(&path) && !self.input_paths.contains(&path) {
-                self.input_paths.push(path);
+    /// Pick up the result of a finished update check
+    fn poll_update_check(&mut self) {
+        if let Some(update_rx) = &mut self.update_rx {
+            match update_rx.try_recv() {
+                Ok(update) => {
+                    self.available_update = update;
+                    self.update_rx = None;
+                }
+                Err(oneshot::error::TryRecvError::Empty) => {}
+                Err(oneshot::error::TryRecvError::Closed) => self.update_rx = None,
             }
         }
-        Ok(())
     }
 
-    fn update_output_folder(&mut self) {
-        // Only update output folder if it hasn't been manually set by the user
-        if !self.output_folder_manually_set {
-            if let Some(input_path) = self.input_paths.first() {
-                self.output_folder = Some(input_path.parent().unwrap_or(Path::new("")).to_path_buf());
+    /// Check if a file matches the current input filter and tool capabilities
+    fn file_matches_filter(&self, path: &Path) -> bool {
+        path.is_file() && self.name_matches_filter(path)
+    }
+
+    /// Check if a file name matches the current input filter and tool capabilities, without looking at the file
+    fn name_matches_filter(&self, path: &Path) -> bool {
+        Self::input_filter_matches(self.converter_tool, self.input_file_extension, path)
+    }
+
+    /// Check if a file name matches an input filter and tool, for use away from the UI thread
+    fn input_filter_matches(converter_tool: ConverterTool, input_file_extension: InputFileExtension, path: &Path) -> bool {
+        match input_file_extension {
+            InputFileExtension::All => converter_tool.supports_file(path),
+            InputFileExtension::Hkx => {
+                path.extension().map_or(false, |ext| ext == "hkx")
+            }
+            InputFileExtension::Xml => {
+                path.extension().map_or(false, |ext| ext == "xml")
+            }
+            InputFileExtension::Kf => {
+                path.extension().map_or(false, |ext| ext == "kf")
+            }
+            InputFileExtension::Hkt => {
+                path.extension().is_some_and(|ext| ext == "hkt")
             }
         }
     }
 
-    /// Add a single file to the input files list, checking if it matches the current extension filter
-    fn add_file(&mut self, file_path: PathBuf) -> bool {
-        if self.file_matches_filter(&file_path) && !self.input_paths.contains(&file_path) {
-            self.input_paths.push(file_path);
-            true
-        } else {
-            false
+    /// Open a folder in the system file explorer
+    fn open_folder_in_explorer(folder_path: &Path) {
+        #[cfg(target_os = "windows")]
+        {
+            if let Err(e) = std::process::Command::new("explorer")
+                .arg(folder_path)
+                .spawn()
+            {
+                eprintln!("Failed to open folder in explorer: {}", e);
+            }
+        }
+        
+        #[cfg(target_os = "macos")]
+        {
+            if let Err(e) = std::process::Command::new("open")
+                .arg(folder_path)
+                .spawn()
+            {
+                eprintln!("Failed to open folder in Finder: {}", e);
+            }
+        }
+        
+        #[cfg(target_os = "linux")]
+        {
+            if let Err(e) = std::process::Command::new("xdg-open")
+                .arg(folder_path)
+                .spawn()
+            {
+                eprintln!("Failed to open folder in file manager: {}", e);
+            }
         }
     }
 
-    /// Process dropped files and add valid ones to the input files list
-    fn handle_dropped_files(&mut self, dropped_files: Vec<egui::DroppedFile>) {
-        let mut files_added = 0;
-        let mut files_skipped = 0;
+    /// Open the system file explorer with a specific file selected
+    fn reveal_file_in_explorer(file_path: &Path) {
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            // explorer expects `/select,"path"` as a single raw argument
+            if let Err(e) = std::process::Command::new("explorer")
+                .raw_arg(format!("/select,\"{}\"", file_path.display()))
+                .spawn()
+            {
+                eprintln!("Failed to reveal file in explorer: {}", e);
+            }
+        }
 
-        for dropped_file in dropped_files {
-            if let Some(path) = dropped_file.path {
-                if path.is_file() {
-                    if self.add_file(path) {
-                        files_added += 1;
-                    } else {
-                        files_skipped += 1;
+        #[cfg(target_os = "macos")]
+        {
+            if let Err(e) = std::process::Command::new("open")
+                .arg("-R")
+                .arg(file_path)
+                .spawn()
+            {
+                eprintln!("Failed to reveal file in Finder: {}", e);
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            // There's no portable way to select a file, so open its folder instead
+            if let Some(parent) = file_path.parent() {
+                Self::open_folder_in_explorer(parent);
+            }
+        }
+    }
+
+    /// Open a file in the configured program, or the system default for its type
+    fn open_file_in_editor(file_path: &Path, editor: Option<&Path>) {
+        let result = match editor {
+            Some(editor) => std::process::Command::new(editor).arg(file_path).spawn(),
+            #[cfg(target_os = "windows")]
+            None => std::process::Command::new("explorer").arg(file_path).spawn(),
+            #[cfg(target_os = "macos")]
+            None => std::process::Command::new("open").arg(file_path).spawn(),
+            #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+            None => std::process::Command::new("xdg-open").arg(file_path).spawn(),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to open {:?} in editor: {}", file_path, e);
+        }
+    }
+
+    /// Decompile `source` to XML in a temp folder and open it for editing
+    fn start_xml_edit(&mut self, source: PathBuf) {
+        let Some(original_format) = OutputFormat::detect_from_file(&source).filter(|format| {
+            matches!(format, OutputFormat::SkyrimLE | OutputFormat::SkyrimSE)
+        }) else {
+            self.conversion_status = ConversionStatus::Error {
+                message: format!("{} is not a Skyrim LE or SE HKX file", source.file_name().unwrap_or_default().to_string_lossy()),
+            };
+            return;
+        };
+
+        let converter_tool = self.xml_round_trip_tool(original_format);
+
+        let work_dir = match create_temp_dir("hkx_edit_", self.settings.temp_dir.as_deref()) {
+            Ok(work_dir) => work_dir,
+            Err(e) => {
+                self.conversion_status = ConversionStatus::Error {
+                    message: format!("Failed to create a working folder for XML editing: {}", e),
+                };
+                return;
+            }
+        };
+        let xml_path = work_dir
+            .path()
+            .join(format!("{}.xml", source.file_stem().unwrap_or_default().to_string_lossy()));
+
+        let converter = Converter::new(converter_tool, OutputFormat::Xml, self.tool_paths.clone())
+            .with_temp_dir(self.settings.temp_dir.clone())
+            .with_wine(self.settings.wine.clone());
+        let (result_tx, result_rx) = oneshot::channel();
+        let (input, output) = (source.clone(), xml_path.clone());
+        self.tokio_handle.spawn(async move {
+            let result = converter.convert(&input, &output).await.map_err(|e| format!("{:#}", e));
+            let _ = result_tx.send(result);
+        });
+
+        self.xml_edit = Some(XmlEditSession {
+            source,
+            original_format,
+            converter_tool,
+            xml_path,
+            work_dir,
+            stage: XmlEditStage::Decompiling,
+            last_modified: None,
+            saved: false,
+            result_rx: Some(result_rx),
+        });
+    }
+
+    /// The selected tool if it can convert `format` to XML and back, otherwise hkxc, which handles both LE and SE
+    fn xml_round_trip_tool(&self, format: OutputFormat) -> ConverterTool {
+        let formats = self.converter_tool.available_output_formats();
+        if !self.auto_tool && formats.contains(&OutputFormat::Xml) && formats.contains(&format) {
+            self.converter_tool
+        } else {
+            ConverterTool::HkxC
+        }
+    }
+
+    /// Show `source` in the XML viewer, decompiling it first when it's an HKX
+    fn open_xml_viewer(&mut self, source: PathBuf) {
+        let format = OutputFormat::detect_from_file(&source);
+        let converter = format
+            .filter(|format| matches!(format, OutputFormat::SkyrimLE | OutputFormat::SkyrimSE))
+            .map(|format| {
+                Converter::new(self.xml_round_trip_tool(format), OutputFormat::Xml, self.tool_paths.clone())
+                    .with_temp_dir(self.settings.temp_dir.clone())
+                    .with_wine(self.settings.wine.clone())
+            });
+        let temp_base_dir = self.settings.temp_dir.clone();
+        let (result_tx, result_rx) = oneshot::channel();
+        let input = source.clone();
+        self.tokio_handle.spawn(async move {
+            let result = async {
+                let xml_path = match converter {
+                    Some(converter) => {
+                        let work_dir = create_temp_dir("hkx_view_", temp_base_dir.as_deref())
+                            .map_err(|e| format!("Failed to create a working folder: {}", e))?;
+                        let xml_path = work_dir.path().join("view.xml");
+                        converter.convert(&input, &xml_path).await.map_err(|e| format!("{:#}", e))?;
+                        let bytes = fs::read(&xml_path).map_err(|e| format!("Failed to read the converted XML: {}", e))?;
+                        return Ok(String::from_utf8_lossy(&bytes).into_owned());
                     }
-                } else if path.is_dir() {
-                    // If a directory is dropped, add all files from it (non-recursive)
-                    // Set the base folder for relative path calculations
-                    self.base_folder = Some(path.clone());
-                    if let Ok(entries) = std::fs::read_dir(&path) {
-                        for entry in entries.flatten() {
-                            let entry_path = entry.path();
-                            if entry_path.is_file() {
-                                if self.add_file(entry_path) {
-                                    files_added += 1;
-                                } else {
-                                    files_skipped += 1;
-                                }
-                            }
+                    None if format == Some(OutputFormat::Xml) => input,
+                    None => return Err("Only HKX and XML files can be shown as XML".to_string()),
+                };
+                let bytes = fs::read(&xml_path).map_err(|e| format!("Failed to read {}: {}", xml_path.display(), e))?;
+                Ok(String::from_utf8_lossy(&bytes).into_owned())
+            }
+            .await;
+            let _ = result_tx.send(result);
+        });
+
+        let search = self.xml_viewer.take().map(|viewer| viewer.search).unwrap_or_default();
+        self.xml_viewer = Some(XmlViewer {
+            source,
+            content: None,
+            truncated: false,
+            result_rx: Some(result_rx),
+            search,
+            matches: Vec::new(),
+            matches_for: String::new(),
+            current_match: 0,
+            scroll_to_match: false,
+            layout: None,
+        });
+    }
+
+    /// Side panel with the XML of the selected file, following the selection while open
+    fn render_xml_viewer(&mut self, ctx: &EguiContext) {
+        let Some(viewer) = &self.xml_viewer else {
+            return;
+        };
+        if let [path] = self.selected_input_paths.iter().collect::<Vec<_>>().as_slice() {
+            if **path != viewer.source && is_xml_viewable(path) {
+                self.open_xml_viewer((*path).clone());
+            }
+        }
+        let Some(viewer) = &mut self.xml_viewer else {
+            return;
+        };
+
+        if let Some(result_rx) = &mut viewer.result_rx {
+            match result_rx.try_recv() {
+                Ok(mut result) => {
+                    if let Ok(text) = &mut result {
+                        if text.len() > XML_VIEWER_MAX_BYTES {
+                            let end = (0..=XML_VIEWER_MAX_BYTES).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+                            text.truncate(end);
+                            viewer.truncated = true;
                         }
                     }
+                    viewer.content = Some(result);
+                    viewer.result_rx = None;
+                    // Search and lay out the new text again
+                    viewer.matches_for = String::new();
+                    viewer.layout = None;
+                }
+                Err(oneshot::error::TryRecvError::Empty) => ctx.request_repaint_after(std::time::Duration::from_millis(200)),
+                Err(oneshot::error::TryRecvError::Closed) => {
+                    viewer.content = Some(Err("The conversion stopped unexpectedly".to_string()));
+                    viewer.result_rx = None;
                 }
             }
         }
 
-        // Update output folder if files were added
-        if files_added > 0 {
-            self.update_output_folder();
-        }
+        let mut close = false;
+        egui::SidePanel::right("xml_viewer").resizable(true).default_width(520.0).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.strong(viewer.source.file_name().unwrap_or_default().to_string_lossy());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    close = ui.small_button("✖").on_hover_text("Close the XML view").clicked();
+                });
+            });
+            ui.separator();
 
-        // Print feedback for debugging
-        if files_added > 0 || files_skipped > 0 {
-            println!("Drag & Drop: Added {} files, skipped {} files", files_added, files_skipped);
+            let XmlViewer {
+                content,
+                truncated,
+                search,
+                matches,
+                matches_for,
+                current_match,
+                scroll_to_match,
+                layout,
+                ..
+            } = viewer;
+            let text = match content {
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Converting to XML…");
+                    });
+                    return;
+                }
+                Some(Err(e)) => {
+                    ui.label(RichText::new(format!("✖ {}", e)).color(Color32::from_rgb(255, 120, 120)));
+                    return;
+                }
+                Some(Ok(text)) => text,
+            };
+
+            ui.horizontal(|ui| {
+                let response = ui.add(egui::TextEdit::singleline(search).hint_text("Search").desired_width(200.0));
+                let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if *matches_for != *search {
+                    // ASCII case folding keeps byte offsets, so the ranges index the original text
+                    let needle = search.to_ascii_lowercase();
+                    *matches = if needle.is_empty() {
+                        Vec::new()
+                    } else {
+                        text.to_ascii_lowercase()
+                            .match_indices(&needle)
+                            .map(|(start, found)| start..start + found.len())
+                            .collect()
+                    };
+                    *matches_for = search.clone();
+                    *current_match = 0;
+                    *scroll_to_match = !matches.is_empty();
+                }
+                if !matches.is_empty() {
+                    ui.label(format!("{}/{}", *current_match + 1, matches.len()));
+                    if ui.small_button("▲").on_hover_text("Previous match").clicked() {
+                        *current_match = (*current_match + matches.len() - 1) % matches.len();
+                        *scroll_to_match = true;
+                    }
+                    if ui.small_button("▼").on_hover_text("Next match (Enter)").clicked() || enter {
+                        *current_match = (*current_match + 1) % matches.len();
+                        *scroll_to_match = true;
+                    }
+                    if enter {
+                        response.request_focus();
+                    }
+                } else if !search.is_empty() {
+                    ui.label(RichText::new("No matches").color(Color32::from_rgb(150, 150, 150)));
+                }
+            });
+            if *truncated {
+                ui.label(
+                    RichText::new(format!("Showing the first {} KB only", XML_VIEWER_MAX_BYTES / 1024))
+                        .color(Color32::from_rgb(255, 180, 80)),
+                );
+            }
+
+            // Highlighting up to XML_VIEWER_MAX_BYTES is too slow to redo every frame
+            let key = (matches_for.clone(), *current_match, ui.visuals().dark_mode);
+            let atlas = ui.fonts(|fonts| fonts.texture_atlas());
+            let up_to_date = layout.as_ref().is_some_and(|layout| {
+                layout.key == key && layout.atlas.upgrade().is_some_and(|laid_out_in| Arc::ptr_eq(&laid_out_in, &atlas))
+            });
+            if !up_to_date {
+                *layout = None;
+            }
+            let galley = layout
+                .get_or_insert_with(|| {
+                    let job = xml_layout_job(text, matches, *current_match, ui.visuals());
+                    XmlViewerLayout {
+                        key,
+                        atlas: Arc::downgrade(&atlas),
+                        galley: ui.fonts(|fonts| fonts.layout_job(job)),
+                    }
+                })
+                .galley
+                .clone();
+            let mut layouter = |_ui: &Ui, _text: &str, _wrap_width: f32| galley.clone();
+            egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+                let output = egui::TextEdit::multiline(&mut text.as_str())
+                    .code_editor()
+                    .desired_width(f32::INFINITY)
+                    .layouter(&mut layouter)
+                    .show(ui);
+                if std::mem::take(scroll_to_match) {
+                    if let Some(found) = matches.get(*current_match) {
+                        let char_index = text[..found.start].chars().count();
+                        let rect = output
+                            .galley
+                            .pos_from_ccursor(egui::text::CCursor::new(char_index))
+                            .translate(output.galley_pos.to_vec2());
+                        ui.scroll_to_rect(rect, Some(egui::Align::Center));
+                    }
+                }
+            });
+        });
+        if close {
+            self.xml_viewer = None;
         }
     }
 
-    /// Render a visual overlay when files are being dragged over the window
-    fn render_drag_drop_overlay(&self, ctx: &EguiContext, hovered_files_count: usize) {
-        // Create a semi-transparent overlay covering the entire window
-        egui::Area::new("drag_drop_overlay".into())
-            .fixed_pos(egui::Pos2::ZERO)
+    /// Compile the edited XML back to the source format and overwrite the source file
+    fn recompile_xml_edit(&mut self) {
+        let Some(session) = &mut self.xml_edit else {
+            return;
+        };
+
+        let recompiled_path = session
+            .work_dir
+            .path()
+            .join(format!("recompiled.{}", session.original_format.extension()));
+        let converter = Converter::new(session.converter_tool, session.original_format, self.tool_paths.clone())
+            .with_temp_dir(self.settings.temp_dir.clone())
+            .with_wine(self.settings.wine.clone());
+        let (result_tx, result_rx) = oneshot::channel();
+        let (xml_path, source) = (session.xml_path.clone(), session.source.clone());
+        self.tokio_handle.spawn(async move {
+            // Only touch the source once the recompile has succeeded
+            let result = async {
+                converter.convert(&xml_path, &recompiled_path).await?;
+                overwrite_file(&recompiled_path, &source).context("Failed to overwrite the source file")?;
+                Ok::<(), anyhow::Error>(())
+            }
+            .await
+            .map_err(|e| format!("{:#}", e));
+            let _ = result_tx.send(result);
+        });
+
+        session.stage = XmlEditStage::Recompiling;
+        session.result_rx = Some(result_rx);
+    }
+
+    /// Pick up finished conversions and notice when the XML has been saved
+    fn poll_xml_edit(&mut self) {
+        let editor = self.settings.xml_editor.clone();
+        let Some(session) = &mut self.xml_edit else {
+            return;
+        };
+
+        if let Some(result_rx) = &mut session.result_rx {
+            let result = match result_rx.try_recv() {
+                Ok(result) => result,
+                Err(oneshot::error::TryRecvError::Empty) => return,
+                Err(oneshot::error::TryRecvError::Closed) => Err("Conversion task stopped unexpectedly".to_string()),
+            };
+            session.result_rx = None;
+            session.stage = match (result, &session.stage) {
+                (Err(message), _) => XmlEditStage::Failed(message),
+                (Ok(()), XmlEditStage::Decompiling) => {
+                    session.last_modified = Self::modified_time(&session.xml_path);
+                    Self::open_file_in_editor(&session.xml_path, editor.as_deref());
+                    XmlEditStage::Editing
+                }
+                (Ok(()), _) => {
+                    session.saved = false;
+                    XmlEditStage::Recompiled
+                }
+            };
+        }
+
+        // Watch for saves while the XML is open, including further edits after a recompile
+        if matches!(session.stage, XmlEditStage::Editing | XmlEditStage::Recompiled) {
+            let modified = Self::modified_time(&session.xml_path);
+            if modified.is_some() && modified != session.last_modified {
+                session.last_modified = modified;
+                session.saved = true;
+                session.stage = XmlEditStage::Editing;
+            }
+        }
+    }
+
+    fn modified_time(path: &Path) -> Option<std::time::SystemTime> {
+        fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    /// Window guiding the user through editing a file as XML
+    fn render_xml_edit_window(&mut self, ctx: &EguiContext) {
+        let Some(session) = &self.xml_edit else {
+            return;
+        };
+        // Keep checking the XML for saves without user input
+        ctx.request_repaint_after(std::time::Duration::from_millis(500));
+
+        let source_name = session.source.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let stage = session.stage.clone();
+        let saved = session.saved;
+        let xml_path = session.xml_path.clone();
+
+        let mut reopen = false;
+        let mut recompile = false;
+        let mut close = false;
+        let mut browse_editor = false;
+        let mut reset_editor = false;
+        egui::Window::new(format!("Edit as XML: {}", source_name))
+            .collapsible(false)
+            .resizable(false)
             .show(ctx, |ui| {
-                // Get the available screen space
-                let screen_rect = ctx.screen_rect();
-                
-                // Draw semi-transparent background
-                ui.allocate_ui_at_rect(screen_rect, |ui| {
-                    // Background with semi-transparent blue
-                    ui.painter().rect_filled(
-                        screen_rect,
-                        egui::Rounding::ZERO,
-                        Color32::from_rgba_unmultiplied(0, 100, 200, 100), // Semi-transparent blue
-                    );
-                    
-                    // Add animated dashed border for better visual feedback
-                    let border_color = Color32::from_rgb(0, 150, 255);
-                    let border_width = 4.0;
-                    
-                    // Create a dashed border effect by drawing multiple smaller rectangles
-                    let margin = border_width / 2.0;
-                    let inner_rect = screen_rect.shrink(margin);
-                    
-                    // Draw the main border
-                    ui.painter().rect_stroke(
-                        inner_rect,
-                        egui::Rounding::same(5.0),
-                        egui::Stroke::new(border_width, border_color),
-                    );
-                    
-                    // Add an inner glow effect with a slightly smaller rectangle
-                    let glow_rect = inner_rect.shrink(border_width);
-                    ui.painter().rect_stroke(
-                        glow_rect,
-                        egui::Rounding::same(5.0),
-                        egui::Stroke::new(1.0, Color32::from_rgba_unmultiplied(0, 150, 255, 150)),
-                    );
-                    
-                    // Center the content
-                    ui.allocate_ui_at_rect(screen_rect, |ui| {
-                        ui.centered_and_justified(|ui| {
-                            ui.vertical_centered(|ui| {
-                                // Create a centered box for the content
-                                ui.allocate_ui_with_layout(
-                                    egui::Vec2::new(400.0, 300.0),
-                                    egui::Layout::top_down(egui::Align::Center),
-                                    |ui| {
-                                        ui.add_space(20.0);
-                                        
-                                        // Large drop icon with background
-                                        ui.label(RichText::new("⬇").size(80.0).color(Color32::WHITE));
-                                        
-                                        ui.add_space(15.0);
-                                        
-                                        // Main drop message
-                                        ui.label(
-                                            RichText::new("Drop Files Here")
-                                                .size(28.0)
-                                                .color(Color32::WHITE)
-                                                .strong()
-                                        );
-                                        
-                                        ui.add_space(15.0);
-                                        
-                                        // File count and supported formats
-                                        let file_text = if hovered_files_count == 1 {
-                                            "1 file ready to drop".to_string()
-                                        } else {
-                                            format!("{} files ready to drop", hovered_files_count)
-                                        };
-                                        
-                                        ui.label(
-                                            RichText::new(file_text)
-                                                .size(18.0)
-                                                .color(Color32::from_rgb(200, 230, 255))
-                                        );
-                                        
-                                        ui.add_space(10.0);
-                                        
-                                                                // Supported formats
-                        let supported_formats = self.converter_tool.supported_formats_description();
-                                        
-                                        ui.label(
-                                            RichText::new(supported_formats)
-                                                .size(14.0)
-                                                .color(Color32::from_rgb(180, 210, 255))
-                                                .italics()
-                                        );
-                                        
-                                        ui.add_space(10.0);
-                                        
-                                        // Add a subtle hint about folder support
-                                        ui.label(
-                                            RichText::new("Files and folders are supported")
-                                                .size(12.0)
-                                                .color(Color32::from_rgb(150, 180, 220))
-                                                .italics()
-                                        );
-                                    }
-                                );
-                            });
-                        });
-                    });
+                match &stage {
+                    XmlEditStage::Decompiling => {
+                        ui.label("Converting to XML...");
+                    }
+                    XmlEditStage::Editing if saved => {
+                        ui.label(
+                            RichText::new("✔ Changes saved. Recompile to write them back to the source file.")
+                                .color(Color32::from_rgb(100, 200, 100)),
+                        );
+                    }
+                    XmlEditStage::Editing => {
+                        ui.label("Edit the XML and save it. Waiting for changes...");
+                    }
+                    XmlEditStage::Recompiling => {
+                        ui.label("Recompiling...");
+                    }
+                    XmlEditStage::Recompiled => {
+                        ui.label(
+                            RichText::new(format!("✔ {} was updated. Save the XML again to make more changes.", source_name))
+                                .color(Color32::from_rgb(100, 200, 100)),
+                        );
+                    }
+                    XmlEditStage::Failed(message) => {
+                        ui.label(RichText::new(message).color(Color32::from_rgb(255, 120, 120)));
+                    }
+                }
+                ui.label(RichText::new(xml_path.to_string_lossy()).size(11.0).color(Color32::from_rgb(150, 150, 150)));
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("Editor:");
+                    let editor = self
+                        .settings
+                        .xml_editor
+                        .as_ref()
+                        .map(|editor| editor.file_name().unwrap_or_default().to_string_lossy().to_string())
+                        .unwrap_or_else(|| "System default".to_string());
+                    ui.label(editor);
+                    browse_editor = ui.button("Browse").clicked();
+                    if self.settings.xml_editor.is_some() {
+                        reset_editor = ui.button("Reset").clicked();
+                    }
+                });
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    let editable = matches!(stage, XmlEditStage::Editing | XmlEditStage::Recompiled);
+                    reopen = ui.add_enabled(editable, egui::Button::new("Open XML")).clicked();
+                    recompile = ui
+                        .add_enabled(editable && saved, egui::Button::new("Recompile & Overwrite Source"))
+                        .on_hover_text(format!("Convert the XML back and replace {}", source_name))
+                        .clicked();
+                    close = ui
+                        .add_enabled(!matches!(stage, XmlEditStage::Decompiling | XmlEditStage::Recompiling), egui::Button::new("Close"))
+                        .clicked();
                 });
             });
+
+        if browse_editor {
+            if let Some(editor) = FileDialog::new().pick_file() {
+                self.settings.xml_editor = Some(editor);
+                if let Err(e) = self.settings.save() {
+                    eprintln!("Failed to save settings: {}", e);
+                }
+            }
+        }
+        if reset_editor {
+            self.settings.xml_editor = None;
+            if let Err(e) = self.settings.save() {
+                eprintln!("Failed to save settings: {}", e);
+            }
+        }
+        if reopen {
+            Self::open_file_in_editor(&xml_path, self.settings.xml_editor.as_deref());
+        }
+        if recompile {
+            self.recompile_xml_edit();
+        }
+        if close {
+            // Dropping the session deletes the working folder
+            self.xml_edit = None;
+        }
     }
 
-    fn get_output_path(&self, input_path: &Path) -> Option<PathBuf> {
-        let output_base = self.output_folder.as_ref()?;
-        let file_name = input_path.file_stem()?.to_str()?;
-        
-        // Determine output extension based on output format and custom extension
-        let extension = if let Some(custom_ext) = &self.custom_extension {
-            custom_ext.as_str()
-        } else {
-            self.output_format.extension()
-        };
+    /// Contents of the hover tooltip for a converter tool; egui handles placement
+    fn show_tool_tooltip(ui: &mut Ui, tool: ConverterTool) {
+        ui.set_max_width(300.0);
+        ui.label(
+            RichText::new(tool.label())
+                .size(14.0)
+                .strong()
+                .color(ui.visuals().strong_text_color())
+        );
+        ui.add_space(4.0);
+        ui.label(
+            RichText::new(tool.help_text())
+                .size(12.0)
+                .color(ui.visuals().text_color())
+        );
+    }
 
-        // Calculate relative path from base folder to maintain folder structure
-        let relative_path = if let Some(base_folder) = &self.base_folder {
-            // If we have a base folder, calculate relative path from it
-            if let Ok(relative) = input_path.parent().unwrap_or(Path::new("")).strip_prefix(base_folder) {
-                relative.to_path_buf()
-            } else {
-                // Fallback: use the parent directory relative to the input path
-                input_path.parent().unwrap_or(Path::new("")).to_path_buf()
+    /// Switch tools, keeping each tool's last output format and dropping settings the new tool doesn't support
+    fn select_converter_tool(&mut self, tool: ConverterTool) {
+        self.auto_tool = false;
+        if self.converter_tool == tool {
+            return;
+        }
+        // Remember the format used with the previous tool and restore this tool's
+        self.tool_output_formats.insert(self.converter_tool, self.output_format);
+        self.converter_tool = tool;
+        if let Some(&format) = self.tool_output_formats.get(&tool) {
+            self.output_format = format;
+        }
+        // Reset input file extension if tool doesn't support current filter
+        if !tool.available_input_extensions().contains(&self.input_file_extension) {
+            self.input_file_extension = InputFileExtension::Hkx;
+        }
+        // Reset output format if tool doesn't support current format
+        let available_formats = self.available_output_formats();
+        if !available_formats.contains(&self.output_format) {
+            if !available_formats.is_empty() {
+                self.output_format = available_formats[0];
             }
+        }
+    }
+
+    /// Switch to picking each file's tool with `auto_converter_tool`
+    fn select_auto_tool(&mut self) {
+        self.select_converter_tool(ConverterTool::HkxCmd);
+        self.auto_tool = true;
+    }
+
+    /// Name of the selected tool, or Auto
+    fn tool_label(&self) -> &'static str {
+        if self.auto_tool {
+            AUTO_TOOL_LABEL
         } else {
-            // Fallback to old behavior for single files or when no base folder is set
-            let base_dir = if self.input_paths.len() == 1 {
-                input_path.parent().unwrap_or(Path::new(""))
-            } else {
-                self.find_common_parent_dir()
-                    .unwrap_or_else(|| Path::new(""))
+            self.converter_tool.label()
+        }
+    }
+
+    /// Read the size and format of inputs added since the last frame, so each file is read once
+    fn read_new_input_file_info(&mut self) {
+        for path in &self.input_paths {
+            if !self.input_file_info.contains_key(path) {
+                self.input_file_info.insert(path.clone(), InputFileInfo::read(path));
+            }
+        }
+    }
+
+    /// Tool the current settings convert `input_path` to `output_format` with
+    fn tool_for_file(&self, input_path: &Path, output_format: OutputFormat) -> ConverterTool {
+        if self.auto_tool {
+            // Headers are read once per file; this runs every frame for every input
+            let input_format = match self.input_file_info.get(input_path) {
+                Some(info) => info.detected,
+                None => OutputFormat::detect_from_file(input_path),
             };
+            auto_converter_tool_for_format(input_path, input_format, output_format)
+        } else {
+            self.converter_tool
+        }
+    }
 
-            input_path
-                .parent()
-                .unwrap_or(Path::new(""))
-                .strip_prefix(base_dir)
-                .unwrap_or(Path::new(""))
-                .to_path_buf()
-        };
+    /// Get available output formats for the current tool
+    fn available_output_formats(&self) -> Vec<OutputFormat> {
+        self.converter_tool.available_output_formats()
+    }
+
+    /// Get the output formats the next conversion will produce
+    fn selected_output_formats(&self) -> Vec<OutputFormat> {
+        // Multi-target only makes sense when the tool offers more than one format
+        if self.multi_target && self.available_output_formats().len() > 1 {
+            // Keep the tool's ordering and drop formats the tool can't produce
+            self.available_output_formats()
+                .into_iter()
+                .filter(|format| self.multi_target_formats.contains(format))
+                .collect()
+        } else {
+            vec![self.output_format]
+        }
+    }
+
+    /// Get the path to the bookmarks file (relative to the executable)
+    fn get_bookmarks_file_path() -> PathBuf {
+        // Get the directory where the executable is located
+        if let Ok(exe_path) = std::env::current_exe() {
+            if let Some(exe_dir) = exe_path.parent() {
+                return exe_dir.join("bookmarked_output_folders.txt");
+            }
+        }
+        // Fallback to current directory
+        PathBuf::from("bookmarked_output_folders.txt")
+    }
+
+    /// Load bookmarks from file
+    fn load_bookmarks() -> Result<Vec<PathBuf>> {
+        let bookmark_file = Self::get_bookmarks_file_path();
+        
+        if !bookmark_file.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let content = fs::read_to_string(&bookmark_file)
+            .context("Failed to read bookmarks file")?;
+        
+        let bookmarks: Vec<PathBuf> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| PathBuf::from(line.trim()))
+            .filter(|path| path.exists()) // Only keep existing paths
+            .collect();
+        
+        Ok(bookmarks)
+    }
+
+    /// Save bookmarks to file
+    fn save_bookmarks(&self) -> Result<()> {
+        let bookmark_file = Self::get_bookmarks_file_path();
+        
+        let content: String = self.bookmarked_folders
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        
+        fs::write(&bookmark_file, content)
+            .context("Failed to save bookmarks file")?;
+        
+        Ok(())
+    }
+
+    /// Add current output folder to bookmarks
+    fn bookmark_current_folder(&mut self) {
+        if let Some(ref folder) = self.output_folder {
+            if !self.bookmarked_folders.contains(folder) {
+                self.bookmarked_folders.push(folder.clone());
+                if let Err(e) = self.save_bookmarks() {
+                    eprintln!("Failed to save bookmarks: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Remove current output folder from bookmarks
+    fn unbookmark_current_folder(&mut self) {
+        if let Some(ref folder) = self.output_folder {
+            self.bookmarked_folders.retain(|f| f != folder);
+            if let Err(e) = self.save_bookmarks() {
+                eprintln!("Failed to save bookmarks: {}", e);
+            }
+        }
+    }
+
+    /// Check if current output folder is bookmarked
+    fn is_current_folder_bookmarked(&self) -> bool {
+        if let Some(ref folder) = self.output_folder {
+            self.bookmarked_folders.contains(folder)
+        } else {
+            false
+        }
+    }
+
+    /// Whether outputs go straight into the output folder; never for the FNIS/Nemesis layout, which relies on its folders
+    fn flattens_output(&self) -> bool {
+        self.flatten_output && !self.framework_layout && self.active_output_template().is_none()
+    }
+
+    /// The output template, if one is set
+    fn active_output_template(&self) -> Option<String> {
+        let template = self.settings.output_template.trim();
+        (!template.is_empty()).then(|| template.to_string())
+    }
+
+    fn add_files_from_folder(&mut self, folder: &Path, recursive: bool) -> Result<()> {
+        if recursive {
+            self.add_files_recursive(folder);
+            return Ok(());
+        }
+
+        // Set the base folder for relative path calculations
+        self.base_folder = Some(folder.to_path_buf());
+        self.add_files_non_recursive(folder)
+    }
+
+    fn add_files_non_recursive(&mut self, folder: &Path) -> Result<()> {
+        let entries = fs::read_dir(folder).context("Failed to read directory")?;
+
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            if self.file_matches_filter(&path)
+                && (self.settings.include_hidden_files || !is_hidden_file(&path))
+                && (!self.framework_layout || is_framework_animation(&path, folder))
+                && !self.input_paths.contains(&path)
+            {
+                self.input_paths.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Start adding the matching files in `folder` and its subfolders in the background.
+    ///
+    /// [`Self::poll_folder_scan`] appends them to the list as they are found, holding them back
+    /// for confirmation once there are more than the Max Files limit.
+    fn add_files_recursive(&mut self, folder: &Path) {
+        // A new scan replaces one still running, keeping what it already added
+        self.stop_folder_scan(true);
+
+        let (files_tx, files_rx) = mpsc::unbounded_channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let scan_cancelled = cancelled.clone();
+        let (converter_tool, input_file_extension) = (self.converter_tool, self.input_file_extension);
+        let framework_layout = self.framework_layout;
+        let follow_links = self.settings.follow_links;
+        let include_hidden_files = self.settings.include_hidden_files;
+        let root = folder.to_path_buf();
+        self.tokio_handle.spawn_blocking(move || {
+            let mut batch = Vec::new();
+            // Folders already scanned, by canonical path, so a folder linked in twice is only scanned once
+            let mut visited_dirs = HashSet::new();
+            let mut entries = walkdir::WalkDir::new(&root).follow_links(follow_links).into_iter();
+            while let Some(entry) = entries.next() {
+                if scan_cancelled.load(Ordering::Relaxed) {
+                    return;
+                }
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        // Includes link cycles, which walkdir reports instead of descending into
+                        eprintln!("Error scanning folder: {}", e);
+                        continue;
+                    }
+                };
+                // The selected folder itself is scanned even when hidden
+                if !include_hidden_files && entry.depth() > 0 && is_hidden_file(entry.path()) {
+                    if entry.file_type().is_dir() {
+                        entries.skip_current_dir();
+                    }
+                    continue;
+                }
+                if follow_links && entry.file_type().is_dir() {
+                    let canonical = fs::canonicalize(entry.path()).unwrap_or_else(|_| entry.path().to_path_buf());
+                    if !visited_dirs.insert(canonical) {
+                        entries.skip_current_dir();
+                        continue;
+                    }
+                }
+                // Linked files are still added when not following links, only linked folders are skipped
+                let is_file = entry.file_type().is_file() || (entry.path_is_symlink() && entry.path().is_file());
+                if is_file
+                    && Self::input_filter_matches(converter_tool, input_file_extension, entry.path())
+                    && (!framework_layout || is_framework_animation(entry.path(), &root))
+                {
+                    batch.push(entry.into_path());
+                    if batch.len() >= FOLDER_SCAN_BATCH && files_tx.send(std::mem::take(&mut batch)).is_err() {
+                        return;
+                    }
+                }
+            }
+            let _ = files_tx.send(batch);
+        });
+
+        // Set the base folder for relative path calculations
+        let previous_base_folder = self.base_folder.replace(folder.to_path_buf());
+        self.folder_scan = Some(FolderScan {
+            folder: folder.to_path_buf(),
+            files_rx,
+            cancelled,
+            finished: false,
+            seen: self.input_paths.iter().cloned().collect(),
+            added: Vec::new(),
+            held: Vec::new(),
+            confirmed: false,
+            previous_base_folder,
+        });
+    }
+
+    /// Append the files the folder scan found since the last frame
+    fn poll_folder_scan(&mut self, ctx: &EguiContext) {
+        let max_files = self.settings.max_files();
+        let Some(scan) = &mut self.folder_scan else {
+            return;
+        };
+
+        let mut files_added = false;
+        while !scan.finished {
+            match scan.files_rx.try_recv() {
+                Ok(files) => {
+                    for path in files {
+                        if !scan.seen.insert(path.clone()) {
+                            continue;
+                        }
+                        if !scan.confirmed && max_files.is_some_and(|max_files| scan.added.len() >= max_files) {
+                            scan.held.push(path);
+                        } else {
+                            self.input_paths.push(path.clone());
+                            scan.added.push(path);
+                            files_added = true;
+                        }
+                    }
+                }
+                Err(mpsc::error::TryRecvError::Empty) => break,
+                Err(mpsc::error::TryRecvError::Disconnected) => scan.finished = true,
+            }
+        }
+
+        // Keep the scan until the user answers for held files
+        let done = scan.finished && scan.held.is_empty();
+        if done {
+            log_info!("Select Folder: Added {} files from {:?}", scan.added.len(), scan.folder);
+            self.folder_scan = None;
+        } else {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+        if files_added {
+            self.update_output_folder();
+        }
+    }
+
+    /// Add the files the folder scan held back past the Max Files limit, and the rest it finds
+    fn accept_held_scan_files(&mut self) {
+        if let Some(scan) = &mut self.folder_scan {
+            scan.confirmed = true;
+            let held = std::mem::take(&mut scan.held);
+            self.input_paths.extend(held.iter().cloned());
+            scan.added.extend(held);
+        }
+    }
+
+    /// Stop the folder scan, keeping the files it added when `keep_added` or removing them again
+    fn stop_folder_scan(&mut self, keep_added: bool) {
+        let Some(scan) = self.folder_scan.take() else {
+            return;
+        };
+        scan.cancelled.store(true, Ordering::Relaxed);
+        if !keep_added {
+            let added: HashSet<PathBuf> = scan.added.into_iter().collect();
+            self.input_paths.retain(|path| !added.contains(path));
+            self.prune_format_overrides();
+            self.base_folder = scan.previous_base_folder;
+        }
+    }
+
+    /// Pick files to add to the list
+    fn browse_input_files(&mut self) {
+        if let Some(paths) = FileDialog::new()
+            .add_filter(self.converter_tool.supported_formats_description(), self.converter_tool.supported_extensions())
+            .add_filter("BSA/BA2 archives", ARCHIVE_EXTENSIONS)
+            .add_filter("All files", &["*"])
+            .pick_files()
+        {
+            // Append like the folder buttons and drag & drop, skipping duplicates
+            let mut files_added = 0;
+            for path in paths {
+                if is_archive(&path) {
+                    self.add_archive(&path);
+                } else if self.add_file(path) == AddFileOutcome::Added {
+                    files_added += 1;
+                }
+            }
+            log_info!("Browse Files: Added {} files", files_added);
+            self.update_output_folder();
+        }
+    }
+
+    /// Pick a .txt list of input paths to add
+    fn import_file_list(&mut self) {
+        if let Some(list_path) = FileDialog::new()
+            .add_filter("Text files", &["txt"])
+            .pick_file()
+        {
+            match self.add_files_from_list(&list_path) {
+                Ok(counts) => {
+                    log_info!(
+                        "Import List: Added {} files, skipped {} already listed and {} missing or unsupported",
+                        counts.added,
+                        counts.duplicates,
+                        counts.filtered
+                    );
+                    self.update_output_folder();
+                }
+                Err(e) => eprintln!("Error importing file list: {:#}", e),
+            }
+        }
+    }
+
+    /// Pick a folder and add its matching files, including subfolders when `recursive`, in place of
+    /// the listed files when `replace`
+    fn select_input_folder(&mut self, recursive: bool, replace: bool) {
+        if let Some(folder) = FileDialog::new().pick_folder() {
+            if replace {
+                self.clear_input_files();
+            }
+            if let Err(e) = self.add_files_from_folder(&folder, recursive) {
+                eprintln!("Error adding files from folder: {}", e);
+            }
+            self.update_output_folder();
+        }
+    }
+
+    /// Whether `path` is shown by the file list filter, given as `needle` in lowercase
+    fn matches_input_filter(&self, path: &Path, needle: &str) -> bool {
+        needle.is_empty() || self.get_relative_path_display(path).to_lowercase().contains(needle)
+    }
+
+    fn clear_input_files(&mut self) {
+        self.stop_folder_scan(true);
+        if !self.input_paths.is_empty() {
+            let archive_extractions = std::mem::take(&mut self.archive_extractions);
+            self.push_input_undo(archive_extractions);
+        }
+        self.input_paths.clear();
+        self.archive_extractions.clear();
+        for pending in self.pending_archives.drain(..) {
+            pending.cancelled.store(true, Ordering::Relaxed);
+        }
+        self.selected_input_paths.clear();
+        self.input_file_info.clear();
+        self.format_overrides.clear();
+        self.base_folder = None;
+        // Reset the manually set flag when clearing all files
+        self.output_folder_manually_set = false;
+    }
+
+    /// Forget the output formats picked for files no longer listed, so re-adding one starts afresh
+    fn prune_format_overrides(&mut self) {
+        let listed: HashSet<&PathBuf> = self.input_paths.iter().collect();
+        self.format_overrides.retain(|path, _| listed.contains(path));
+    }
+
+    /// Remember the input list before it's changed, so Ctrl+Z can bring it back
+    fn push_input_undo(&mut self, archive_extractions: Vec<ArchiveExtraction>) {
+        if self.input_undo.len() >= INPUT_UNDO_LIMIT {
+            self.input_undo.remove(0);
+        }
+        self.input_undo.push(InputListSnapshot {
+            input_paths: self.input_paths.clone(),
+            base_folder: self.base_folder.clone(),
+            output_folder_manually_set: self.output_folder_manually_set,
+            format_overrides: self.format_overrides.clone(),
+            archive_extractions,
+        });
+    }
+
+    /// Restore the input list from before the last Clear All or removal
+    fn undo_input_change(&mut self) {
+        let Some(snapshot) = self.input_undo.pop() else {
+            return;
+        };
+        self.stop_folder_scan(true);
+        self.input_paths = snapshot.input_paths;
+        self.base_folder = snapshot.base_folder;
+        self.output_folder_manually_set = snapshot.output_folder_manually_set;
+        self.format_overrides = snapshot.format_overrides;
+        self.archive_extractions.extend(snapshot.archive_extractions);
+        self.selected_input_paths.clear();
+        self.input_sort = None;
+        self.update_output_folder();
+    }
+
+    fn update_output_folder(&mut self) {
+        // Only update output folder if it hasn't been manually set by the user
+        if !self.output_folder_manually_set {
+            if let Some(input_path) = self.input_paths.first() {
+                // Files from an archive are written next to the archive, not into its temporary folder
+                let source = self.source_archive(input_path).map(|extraction| extraction.archive.as_path()).unwrap_or(input_path);
+                self.output_folder = Some(source.parent().unwrap_or(Path::new("")).to_path_buf());
+            }
+        }
+    }
+
+    /// Start extracting the files of a BSA/BA2 archive that match the current filter in the
+    /// background. [`Self::poll_archive_extractions`] adds them to the list once they're all out.
+    fn add_archive(&mut self, archive_path: &Path) {
+        let (result_tx, result_rx) = oneshot::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let task_cancelled = cancelled.clone();
+        let (converter_tool, input_file_extension) = (self.converter_tool, self.input_file_extension);
+        let unsupported = format!(
+            "no files supported by {} ({})",
+            self.tool_label(),
+            self.input_file_extension.label_for_tool(self.converter_tool)
+        );
+        let temp_dir = self.settings.temp_dir.clone();
+        let archive = archive_path.to_path_buf();
+        self.tokio_handle.spawn_blocking(move || {
+            let is_input = |path: &Path| Self::input_filter_matches(converter_tool, input_file_extension, path);
+            let _ = result_tx.send(Self::extract_archive(&archive, is_input, &unsupported, temp_dir.as_deref(), &task_cancelled));
+        });
+        self.pending_archives.push(PendingArchive {
+            archive: archive_path.to_path_buf(),
+            cancelled,
+            result_rx,
+        });
+    }
+
+    /// Extract the files of `archive` that `is_input` takes into a new temp folder, stopping early once
+    /// `cancelled` is set
+    fn extract_archive(
+        archive: &Path,
+        is_input: impl Fn(&Path) -> bool,
+        unsupported: &str,
+        temp_dir: Option<&Path>,
+        cancelled: &AtomicBool,
+    ) -> ArchiveExtractResult {
+        let archive_name = archive.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let opened = Archive::open(archive).map_err(|e| format!("{:#}", e))?;
+        let entries: Vec<_> = opened.entries().iter().filter(|entry| is_input(Path::new(&entry.path))).collect();
+        if entries.is_empty() {
+            return Err(format!("{} has {}", archive_name, unsupported));
+        }
+        let dir = create_temp_dir("hkx_archive_", temp_dir)
+            .map_err(|e| format!("Failed to create a temporary folder for {}: {}", archive_name, e))?;
+        let mut paths = Vec::new();
+        for entry in entries {
+            if cancelled.load(Ordering::Relaxed) {
+                break;
+            }
+            match opened.extract(entry, dir.path()) {
+                Ok(path) => paths.push(path),
+                Err(e) => eprintln!("ERROR: {:#}", e),
+            }
+        }
+        Ok((dir, paths))
+    }
+
+    /// Add the files of the archives that finished extracting since the last frame
+    fn poll_archive_extractions(&mut self, ctx: &EguiContext) {
+        if self.pending_archives.is_empty() {
+            return;
+        }
+        let mut files_added = false;
+        for mut pending in std::mem::take(&mut self.pending_archives) {
+            let archive_name = pending.archive.file_name().unwrap_or_default().to_string_lossy().to_string();
+            match pending.result_rx.try_recv() {
+                Ok(Ok((dir, paths))) => {
+                    let mut added = 0;
+                    for path in paths {
+                        if !self.input_paths.contains(&path) {
+                            self.input_paths.push(path);
+                            added += 1;
+                        }
+                    }
+                    log_info!("Archive: Extracted {} files from {}", added, archive_name);
+                    self.archive_extractions.push(ArchiveExtraction { archive: pending.archive, dir });
+                    files_added |= added > 0;
+                }
+                Ok(Err(message)) => {
+                    eprintln!("Error extracting archive: {}", message);
+                    self.drop_notice = Some((message, std::time::Instant::now()));
+                }
+                Err(oneshot::error::TryRecvError::Empty) => self.pending_archives.push(pending),
+                Err(oneshot::error::TryRecvError::Closed) => eprintln!("Extracting {} stopped unexpectedly", archive_name),
+            }
+        }
+        if !self.pending_archives.is_empty() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+        if files_added {
+            self.update_output_folder();
+        }
+    }
+
+    /// The archive `path` was extracted from, if it came from one
+    fn source_archive(&self, path: &Path) -> Option<&ArchiveExtraction> {
+        self.archive_extractions.iter().find(|extraction| path.starts_with(extraction.dir.path()))
+    }
+
+    /// Add a single file to the input files list, checking if it matches the current extension filter
+    fn add_file(&mut self, file_path: PathBuf) -> AddFileOutcome {
+        if !self.file_matches_filter(&file_path) {
+            AddFileOutcome::FilterSkipped
+        } else if self.input_paths.contains(&file_path) {
+            AddFileOutcome::DuplicateSkipped
+        } else {
+            self.input_paths.push(file_path);
+            AddFileOutcome::Added
+        }
+    }
+
+    /// Add the files listed one per line in a text file, returning how many were added and skipped.
+    ///
+    /// Blank lines and lines starting with `#` are ignored, surrounding quotes are removed, and
+    /// relative paths are relative to the list file.
+    fn add_files_from_list(&mut self, list_path: &Path) -> Result<AddCounts> {
+        let bytes = fs::read(list_path).context("Failed to read file list")?;
+        let content = decode_text(&bytes);
+        let list_dir = list_path.parent().unwrap_or(Path::new(""));
+
+        let mut counts = AddCounts::default();
+        for line in content.lines() {
+            let line = line.trim().trim_matches('"');
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let path = normalize_listed_path(line, list_dir);
+            counts.count(self.add_file(path));
+        }
+        Ok(counts)
+    }
+
+    /// Process dropped files and add valid ones to the input files list
+    fn handle_dropped_files(&mut self, dropped_files: Vec<egui::DroppedFile>) {
+        let mut counts = AddCounts::default();
+        // Files the current tool and filter can't take, as opposed to ones already listed
+        let mut unsupported_names = Vec::new();
+        let mut add_dropped_file = |app: &mut Self, counts: &mut AddCounts, path: PathBuf| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let outcome = app.add_file(path);
+            if outcome == AddFileOutcome::FilterSkipped {
+                unsupported_names.push(name);
+            }
+            counts.count(outcome);
+        };
+
+        for dropped_file in dropped_files {
+            // Some apps drop paths or file:// URLs as text instead of file references
+            let paths = match dropped_file.path {
+                Some(path) => vec![path],
+                None => dropped_text_paths(&dropped_file),
+            };
+            for path in paths {
+                if is_file_list(&path) {
+                    // A dropped .txt is a list of input paths
+                    match self.add_files_from_list(&path) {
+                        Ok(list_counts) => counts.add(list_counts),
+                        Err(e) => eprintln!("Error importing file list {:?}: {:#}", path, e),
+                    }
+                } else if is_archive(&path) {
+                    self.add_archive(&path);
+                } else if path.is_file() {
+                    add_dropped_file(self, &mut counts, path);
+                } else if path.is_dir() {
+                    // If a directory is dropped, add all files from it (non-recursive)
+                    // Set the base folder for relative path calculations
+                    self.base_folder = Some(path.clone());
+                    if let Ok(entries) = std::fs::read_dir(&path) {
+                        for entry in entries.flatten() {
+                            let entry_path = entry.path();
+                            if entry_path.is_file() {
+                                add_dropped_file(self, &mut counts, entry_path);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Update output folder if files were added
+        if counts.added > 0 {
+            self.update_output_folder();
+        }
+
+        // Print feedback for debugging
+        if counts.added > 0 || counts.duplicates > 0 || counts.filtered > 0 {
+            log_info!(
+                "Drag & Drop: Added {} files, skipped {} already listed and {} missing or unsupported",
+                counts.added,
+                counts.duplicates,
+                counts.filtered
+            );
+        }
+
+        // Say why nothing (or less than expected) appeared in the list, one line per reason
+        let mut lines = Vec::new();
+        if !unsupported_names.is_empty() {
+            let mut line = format!(
+                "Skipped {} file{} not supported by {} ({})",
+                unsupported_names.len(),
+                if unsupported_names.len() == 1 { "" } else { "s" },
+                self.tool_label(),
+                self.input_file_extension.label_for_tool(self.converter_tool)
+            );
+            let examples: Vec<&str> = unsupported_names.iter().take(DROP_NOTICE_EXAMPLES).map(String::as_str).collect();
+            line.push_str(&format!(": {}", examples.join(", ")));
+            if unsupported_names.len() > DROP_NOTICE_EXAMPLES {
+                line.push_str(&format!(" and {} more", unsupported_names.len() - DROP_NOTICE_EXAMPLES));
+            }
+            lines.push(line);
+        }
+        if counts.duplicates > 0 {
+            lines.push(format!(
+                "Skipped {} file{} already in the list",
+                counts.duplicates,
+                if counts.duplicates == 1 { "" } else { "s" }
+            ));
+        }
+        if !lines.is_empty() {
+            self.drop_notice = Some((lines.join("\n"), std::time::Instant::now()));
+        }
+    }
+
+    /// Add the files pasted with Ctrl+V, one path per line, and say how many were added
+    fn add_pasted_paths(&mut self, text: &str) {
+        let paths = text_paths(text);
+        if paths.is_empty() {
+            self.drop_notice = Some(("The clipboard has no paths of existing files".to_string(), std::time::Instant::now()));
+            return;
+        }
+        let mut counts = AddCounts::default();
+        for path in paths {
+            counts.count(self.add_file(path));
+        }
+        if counts.added > 0 {
+            self.update_output_folder();
+        }
+        log_info!(
+            "Paste: Added {} files, skipped {} already listed and {} missing or unsupported",
+            counts.added,
+            counts.duplicates,
+            counts.filtered
+        );
+
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        let mut lines = vec![format!("Added {} pasted file{}", counts.added, plural(counts.added))];
+        if counts.filtered > 0 {
+            lines.push(format!(
+                "Skipped {} folder{} or file{} {} can't take ({})",
+                counts.filtered,
+                plural(counts.filtered),
+                plural(counts.filtered),
+                self.tool_label(),
+                self.input_file_extension.label_for_tool(self.converter_tool)
+            ));
+        }
+        if counts.duplicates > 0 {
+            lines.push(format!("Skipped {} file{} already in the list", counts.duplicates, plural(counts.duplicates)));
+        }
+        self.drop_notice = Some((lines.join("\n"), std::time::Instant::now()));
+    }
+
+    /// Pick a folder to watch, replacing the one being watched
+    fn start_watching(&mut self) {
+        let Some(folder) = FileDialog::new().set_title("Folder to watch").pick_folder() else {
+            return;
+        };
+        match WatchFolder::start(folder.clone()) {
+            Ok(watch_folder) => {
+                self.tool_log.push(format!("[watch] Watching {}", folder.to_string_lossy()));
+                self.watch_folder = Some(watch_folder);
+            }
+            Err(e) => {
+                self.conversion_status = ConversionStatus::Error {
+                    message: format!("Couldn't watch {:?}: {}", folder, e),
+                };
+            }
+        }
+    }
+
+    /// Convert watched files once they've settled and no other batch is running
+    fn poll_watch_folder(&mut self, ctx: &EguiContext) {
+        let converting = self.is_converting();
+        let Some(watch_folder) = &mut self.watch_folder else {
+            return;
+        };
+        watch_folder.poll();
+        // Outputs a finished batch never wrote, such as those of failed conversions, won't show up
+        if !converting {
+            watch_folder.produced.retain(|path| path.exists());
+        }
+        ctx.request_repaint_after(std::time::Duration::from_millis(500));
+
+        if watch_folder.ready.is_empty() {
+            return;
+        }
+        let busy = self.is_converting()
+            || self.pending_unreadable_inputs.is_some()
+            || self.pending_overwrite_confirmation.is_some()
+            || self.pending_resume_confirmation.is_some();
+        if busy {
+            return;
+        }
+        let ready = self.watch_folder.as_mut().map(|watch_folder| std::mem::take(&mut watch_folder.ready)).unwrap_or_default();
+        let input_paths: Vec<PathBuf> = ready.into_iter().filter(|path| self.file_matches_filter(path)).collect();
+        if input_paths.is_empty() {
+            return;
+        }
+
+        match self.current_job(input_paths) {
+            Ok(job) => {
+                let names: Vec<String> = job
+                    .input_paths
+                    .iter()
+                    .map(|path| path.file_name().unwrap_or_default().to_string_lossy().to_string())
+                    .collect();
+                if let Some(watch_folder) = &mut self.watch_folder {
+                    for (input_path, format) in job.outputs() {
+                        if let Some(output_path) = job.output_path(input_path, format) {
+                            watch_folder.produced.insert(output_path);
+                        }
+                    }
+                }
+                self.tool_log.push(format!("[watch] Converting {}", names.join(", ")));
+                self.reset_conversion_state();
+                self.start_jobs(vec![job], false, true);
+            }
+            Err(message) => self.tool_log.push(format!("[watch] Not converting new files: {}", message)),
+        }
+    }
+
+    fn render_watch_folder(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            match &self.watch_folder {
+                Some(watch_folder) => {
+                    ui.label(RichText::new("👁").color(Color32::from_rgb(100, 200, 100)));
+                    ui.label(watch_folder.folder.to_string_lossy());
+                    let waiting = watch_folder.pending.len() + watch_folder.ready.len();
+                    if waiting > 0 {
+                        ui.label(
+                            RichText::new(format!("({} waiting)", waiting))
+                                .color(Color32::from_rgb(150, 150, 150))
+                                .size(12.0),
+                        );
+                    }
+                    if ui.button("Stop Watching").clicked() {
+                        self.tool_log.push(format!("[watch] Stopped watching {}", watch_folder.folder.to_string_lossy()));
+                        self.watch_folder = None;
+                    }
+                }
+                None => {
+                    if ui
+                        .button("Start Watching")
+                        .on_hover_text("Convert new or changed files in a folder automatically with the current settings")
+                        .clicked()
+                    {
+                        self.start_watching();
+                    }
+                }
+            }
+        });
+    }
+
+    /// Show the skipped-files notice in a corner until it expires or is dismissed
+    fn render_drop_notice(&mut self, ctx: &EguiContext) {
+        let Some((message, shown_at)) = &self.drop_notice else {
+            return;
+        };
+        let elapsed = shown_at.elapsed();
+        if elapsed >= DROP_NOTICE_DURATION {
+            self.drop_notice = None;
+            return;
+        }
+        ctx.request_repaint_after(DROP_NOTICE_DURATION - elapsed);
+
+        let mut dismissed = false;
+        egui::Window::new("drop_notice")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 40.0))
+            .show(ctx, |ui| {
+                ui.set_max_width(360.0);
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("⚠ {}", message)).color(Color32::from_rgb(255, 180, 80)));
+                    dismissed = ui.small_button("✖").clicked();
+                });
+            });
+        if dismissed {
+            self.drop_notice = None;
+        }
+    }
+
+    /// Render a visual overlay when files are being dragged over the window
+    fn render_drag_drop_overlay(&self, ctx: &EguiContext, hovered_files_count: usize) {
+        // Create a semi-transparent overlay covering the entire window
+        egui::Area::new("drag_drop_overlay".into())
+            .fixed_pos(egui::Pos2::ZERO)
+            .show(ctx, |ui| {
+                // Get the available screen space
+                let screen_rect = ctx.screen_rect();
+                
+                // Draw semi-transparent background
+                ui.allocate_ui_at_rect(screen_rect, |ui| {
+                    // Background with semi-transparent blue
+                    ui.painter().rect_filled(
+                        screen_rect,
+                        egui::Rounding::ZERO,
+                        Color32::from_rgba_unmultiplied(0, 100, 200, 100), // Semi-transparent blue
+                    );
+                    
+                    // Add animated dashed border for better visual feedback
+                    let border_color = Color32::from_rgb(0, 150, 255);
+                    let border_width = 4.0;
+                    
+                    // Create a dashed border effect by drawing multiple smaller rectangles
+                    let margin = border_width / 2.0;
+                    let inner_rect = screen_rect.shrink(margin);
+                    
+                    // Draw the main border
+                    ui.painter().rect_stroke(
+                        inner_rect,
+                        egui::Rounding::same(5.0),
+                        egui::Stroke::new(border_width, border_color),
+                    );
+                    
+                    // Add an inner glow effect with a slightly smaller rectangle
+                    let glow_rect = inner_rect.shrink(border_width);
+                    ui.painter().rect_stroke(
+                        glow_rect,
+                        egui::Rounding::same(5.0),
+                        egui::Stroke::new(1.0, Color32::from_rgba_unmultiplied(0, 150, 255, 150)),
+                    );
+                    
+                    // Center the content
+                    ui.allocate_ui_at_rect(screen_rect, |ui| {
+                        ui.centered_and_justified(|ui| {
+                            ui.vertical_centered(|ui| {
+                                // Create a centered box for the content
+                                ui.allocate_ui_with_layout(
+                                    egui::Vec2::new(400.0, 300.0),
+                                    egui::Layout::top_down(egui::Align::Center),
+                                    |ui| {
+                                        ui.add_space(20.0);
+                                        
+                                        // Large drop icon with background
+                                        ui.label(RichText::new("⬇").size(80.0).color(Color32::WHITE));
+                                        
+                                        ui.add_space(15.0);
+                                        
+                                        // Main drop message
+                                        ui.label(
+                                            RichText::new("Drop Files Here")
+                                                .size(28.0)
+                                                .color(Color32::WHITE)
+                                                .strong()
+                                        );
+                                        
+                                        ui.add_space(15.0);
+                                        
+                                        // File count and supported formats
+                                        let file_text = if hovered_files_count == 1 {
+                                            "1 file ready to drop".to_string()
+                                        } else {
+                                            format!("{} files ready to drop", hovered_files_count)
+                                        };
+                                        
+                                        ui.label(
+                                            RichText::new(file_text)
+                                                .size(18.0)
+                                                .color(Color32::from_rgb(200, 230, 255))
+                                        );
+                                        
+                                        ui.add_space(10.0);
+                                        
+                                                                // Supported formats
+                        let supported_formats = self.converter_tool.supported_formats_description();
+                                        
+                                        ui.label(
+                                            RichText::new(supported_formats)
+                                                .size(14.0)
+                                                .color(Color32::from_rgb(180, 210, 255))
+                                                .italics()
+                                        );
+                                        
+                                        ui.add_space(10.0);
+                                        
+                                        // Add a subtle hint about folder support
+                                        ui.label(
+                                            RichText::new("Files, folders and .txt lists of paths are supported")
+                                                .size(12.0)
+                                                .color(Color32::from_rgb(150, 180, 220))
+                                                .italics()
+                                        );
+                                    }
+                                );
+                            });
+                        });
+                    });
+                });
+            });
+    }
+
+    /// Apply the command-line preset, inputs and output folder, and run them on startup if asked to
+    fn with_cli_args(mut self, args: CliArgs, exit_code: Arc<AtomicI32>) -> Self {
+        if let Some(name) = &args.preset {
+            match self.settings.presets.iter().find(|preset| &preset.name == name).cloned() {
+                Some(preset) => self.apply_preset(&preset),
+                None => eprintln!("ERROR: No preset named {:?}", name),
+            }
+        }
+
+        for input in &args.inputs {
+            if is_file_list(input) {
+                if let Err(e) = self.add_files_from_list(input) {
+                    eprintln!("Error importing file list {:?}: {:#}", input, e);
+                }
+            } else if is_archive(input) {
+                self.add_archive(input);
+            } else if input.is_dir() {
+                if let Err(e) = self.add_files_from_folder(input, args.recursive) {
+                    eprintln!("Error adding files from folder: {}", e);
+                }
+            } else {
+                match self.add_file(input.clone()) {
+                    AddFileOutcome::Added => {}
+                    AddFileOutcome::DuplicateSkipped => eprintln!("Skipped {:?}: listed more than once", input),
+                    AddFileOutcome::FilterSkipped => {
+                        eprintln!("Skipped {:?}: missing or not supported by {}", input, self.tool_label())
+                    }
+                }
+            }
+        }
+        self.update_output_folder();
+        if let Some(output) = args.output {
+            self.output_folder = Some(output);
+            self.output_folder_manually_set = true;
+        }
+
+        if args.run || args.exit_after {
+            self.auto_run = Some(AutoRun {
+                overwrite: args.overwrite,
+                exit_after: args.exit_after,
+                json: args.json,
+                started: false,
+                started_at: None,
+                exit_code,
+            });
+        }
+        self
+    }
+
+    /// Start the command-line conversion once the inputs are listed, then close when it ends with --exit-after
+    fn poll_auto_run(&mut self, ctx: &EguiContext) {
+        // Nobody may be there to confirm going over Max Files, and the command line named these inputs
+        let waiting_to_start = self.auto_run.as_ref().is_some_and(|auto_run| !auto_run.started);
+        if waiting_to_start && self.folder_scan.as_ref().is_some_and(|scan| !scan.held.is_empty()) {
+            self.accept_held_scan_files();
+        }
+        let Some(auto_run) = &mut self.auto_run else {
+            return;
+        };
+
+        if !auto_run.started {
+            // Wait for folders with subfolders to finish listing, and archives to be extracted
+            if self.folder_scan.is_some() || !self.pending_archives.is_empty() {
+                return;
+            }
+            auto_run.started = true;
+            auto_run.started_at = Some(std::time::Instant::now());
+            let (overwrite, exit_after) = (auto_run.overwrite, auto_run.exit_after);
+            self.reset_conversion_state();
+            match self.current_job(self.input_paths.clone()) {
+                // Nobody is there to answer the resume question when exiting afterwards
+                Ok(job) => self.start_jobs(vec![job], overwrite, overwrite || exit_after),
+                Err(message) => self.conversion_status = ConversionStatus::Error { message },
+            }
+            if !exit_after {
+                self.auto_run = None;
+            }
+            ctx.request_repaint();
+            return;
+        }
+        let (json, started_at, exit_code_slot) = (auto_run.json, auto_run.started_at, auto_run.exit_code.clone());
+
+        let message = if let Some((_, unreadable)) = &self.pending_unreadable_inputs {
+            self.error_details = unreadable.iter().map(|(_, problem)| problem.clone()).collect();
+            format!("Not converting: {} input(s) are empty or can't be opened.", unreadable.len())
+        } else if let Some((_, overwritten)) = &self.pending_overwrite_confirmation {
+            format!("Not converting: {} output(s) would replace their source file. Pass --overwrite to allow it.", overwritten)
+        } else {
+            // Per-file errors are shown while the batch runs, so wait for the batch itself to end
+            let batch_running = self.progress_rx.as_ref().is_some_and(|rx| !rx.is_closed() || !rx.is_empty());
+            if batch_running {
+                ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                return;
+            }
+            match &self.conversion_status {
+                ConversionStatus::Completed { message, .. } => {
+                    if !json {
+                        println!("{}", message);
+                    }
+                    String::new()
+                }
+                ConversionStatus::Error { message } => message.clone(),
+                _ => "Conversion didn't start".to_string(),
+            }
+        };
+        let failed_inputs: Vec<&PathBuf> = self.failed_jobs.iter().flat_map(|job| &job.input_paths).collect();
+        let exit_code = if message.is_empty() {
+            0
+        } else {
+            if !json {
+                eprintln!("ERROR: {}", message);
+                for details in &self.error_details {
+                    eprintln!("{}", details);
+                }
+            }
+            failed_inputs.len().clamp(1, MAX_FAILURE_EXIT_CODE) as i32
+        };
+        if json {
+            let duration = started_at.map(|started_at| started_at.elapsed()).unwrap_or_default();
+            println!("{}", self.cli_summary_json(&message, &failed_inputs, duration));
+        }
+        exit_code_slot.store(exit_code, Ordering::SeqCst);
+        self.auto_run = None;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    }
+
+    /// JSON summary of the finished command-line batch for --json, with `message` empty when it succeeded
+    fn cli_summary_json(&self, message: &str, failed_inputs: &[&PathBuf], duration: std::time::Duration) -> String {
+        let outputs: Vec<serde_json::Value> = self
+            .completed_outputs
+            .iter()
+            .map(|output| serde_json::json!({ "path": output.path.to_string_lossy(), "sha256": output.sha256 }))
+            .collect();
+        let failed: Vec<_> = failed_inputs.iter().map(|input| input.to_string_lossy()).collect();
+        let (status, message) = match &self.conversion_status {
+            ConversionStatus::Completed { message: completed, .. } if message.is_empty() => ("completed", completed.as_str()),
+            _ => ("failed", message),
+        };
+        serde_json::json!({
+            "status": status,
+            "message": message,
+            "converted": self.completed_outputs.len(),
+            "failed": failed_inputs.len(),
+            // Milliseconds are plenty for a batch duration
+            "duration_secs": (duration.as_secs_f64() * 1000.0).round() / 1000.0,
+            "outputs": outputs,
+            "failed_inputs": failed,
+            "errors": self.error_details,
+        })
+        .to_string()
+    }
+
+    /// Show the batch progress in the window title and ask for attention when a batch ends in the background
+    fn update_window_progress(&mut self, ctx: &EguiContext) {
+        let batch_running = self.progress_rx.as_ref().is_some_and(|rx| !rx.is_closed() || !rx.is_empty());
+        let percent = batch_running.then(|| match &self.conversion_status {
+            ConversionStatus::Running { progress, total, .. }
+            | ConversionStatus::Paused { progress, total }
+            | ConversionStatus::Cancelling { progress, total }
+                if *total > 0 =>
+            {
+                progress * 100 / total
+            }
+            // Files that fail mid-batch show an error without progress
+            _ => self.title_progress.unwrap_or(0),
+        });
+        if percent.is_some() {
+            // Notice the end of the batch even when nothing else repaints the unfocused window
+            ctx.request_repaint_after(std::time::Duration::from_millis(250));
+        }
+        if percent == self.title_progress {
+            return;
+        }
+
+        let title = match percent {
+            Some(percent) if matches!(self.conversion_status, ConversionStatus::Paused { .. }) => {
+                format!("{}% (paused) - {}", percent, WINDOW_TITLE)
+            }
+            Some(percent) => format!("{}% - {}", percent, WINDOW_TITLE),
+            None => WINDOW_TITLE.to_string(),
+        };
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+
+        // Flash the taskbar button when a batch ends while the window isn't focused; watch batches run unattended
+        let batch_ended = self.title_progress.is_some() && percent.is_none();
+        if batch_ended && self.watch_folder.is_none() {
+            let failed = matches!(self.conversion_status, ConversionStatus::Error { .. });
+            if !ctx.input(|i| i.viewport().focused.unwrap_or(true)) {
+                let attention = if failed { egui::UserAttentionType::Critical } else { egui::UserAttentionType::Informational };
+                ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(attention));
+            }
+            if self.settings.chime_on_batch_end {
+                play_chime(failed);
+            }
+            if self.settings.flash_on_batch_end {
+                self.batch_end_flash = Some((std::time::Instant::now(), failed));
+            }
+        }
+        self.title_progress = percent;
+    }
+
+    /// Fading border around the window after a batch ends, green when it succeeded and red when it failed
+    fn render_batch_end_flash(&mut self, ctx: &EguiContext) {
+        let Some((ended_at, failed)) = self.batch_end_flash else {
+            return;
+        };
+        let elapsed = ended_at.elapsed();
+        if elapsed >= BATCH_END_FLASH_DURATION {
+            self.batch_end_flash = None;
+            return;
+        }
+        ctx.request_repaint();
+
+        let fade = 1.0 - elapsed.as_secs_f32() / BATCH_END_FLASH_DURATION.as_secs_f32();
+        let (r, g, b) = if failed { (255, 120, 120) } else { (100, 200, 100) };
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("batch_end_flash")));
+        let screen_rect = ctx.screen_rect();
+        painter.rect_filled(screen_rect, egui::Rounding::ZERO, Color32::from_rgba_unmultiplied(r, g, b, (40.0 * fade) as u8));
+        painter.rect_stroke(
+            screen_rect.shrink(4.0),
+            egui::Rounding::ZERO,
+            egui::Stroke::new(8.0, Color32::from_rgba_unmultiplied(r, g, b, (255.0 * fade) as u8)),
+        );
+    }
+
+    /// Clear the last batch's status before starting a new one
+    fn reset_conversion_state(&mut self) {
+        self.conversion_status = ConversionStatus::Idle;
+        self.progress_rx = None;
+        self.cancel_tx = None;
+        self.cancel_outcome = None;
+    }
+
+    /// Start converting the listed files with the current settings
+    fn run_conversion(&mut self) {
+        self.reset_conversion_state();
+        self.start_conversion();
+    }
+
+    fn start_conversion(&mut self) {
+        match self.current_job(self.input_paths.clone()) {
+            Ok(job) => self.start_jobs(vec![job], false, false),
+            Err(message) => self.conversion_status = ConversionStatus::Error { message },
+        }
+    }
+
+    /// Whether a batch is running, paused or still stopping after a cancel
+    fn is_converting(&self) -> bool {
+        matches!(
+            self.conversion_status,
+            ConversionStatus::Running { .. } | ConversionStatus::Paused { .. } | ConversionStatus::Cancelling { .. }
+        )
+    }
+
+    /// Convert one listed file with the current settings, leaving the rest of the list alone
+    fn convert_single_file(&mut self, path: PathBuf) {
+        self.reset_conversion_state();
+        match self.current_job(vec![path]) {
+            Ok(job) => self.start_jobs(vec![job], false, false),
+            Err(message) => self.conversion_status = ConversionStatus::Error { message },
+        }
+    }
+
+    /// Re-run the conversion for only the files that failed in the last batch
+    fn retry_failed_conversions(&mut self) {
+        self.start_jobs(self.failed_jobs.clone(), false, false);
+    }
+
+    /// Add the current tool, formats and files to the job queue
+    fn add_current_job_to_queue(&mut self) {
+        match self.current_job(self.input_paths.clone()) {
+            Ok(job) => self.job_queue.push(job),
+            Err(message) => self.conversion_status = ConversionStatus::Error { message },
+        }
+    }
+
+    /// Save the tool commands the current batch would run as a batch file (a shell script outside
+    /// Windows), for running them by hand
+    fn export_commands(&mut self) {
+        let job = match self.current_job(self.input_paths.clone()) {
+            Ok(job) => job,
+            Err(message) => {
+                self.conversion_status = ConversionStatus::Error { message };
+                return;
+            }
+        };
+        let windows = cfg!(windows);
+        let mut command_lines = Vec::new();
+        for (input_path, output_format) in job.outputs() {
+            let Some(output_path) = job.output_path(input_path, output_format) else {
+                continue;
+            };
+            let converter = Converter::new(job.tool_for(input_path, output_format), output_format, self.tool_paths.clone())
+                .with_skeleton_file(
+                    skeleton_for_input(input_path, &job.skeleton_rules, job.skeleton_file.as_deref()).map(Path::to_path_buf),
+                )
+                .with_version_override(job.version_override.clone())
+                .with_wine(job.wine.clone());
+            match converter.command_lines(input_path, &output_path, windows) {
+                Ok(lines) => command_lines.extend(lines),
+                Err(e) => {
+                    self.conversion_status = ConversionStatus::Error {
+                        message: format!("Can't export the command for {}: {:#}", input_path.display(), e),
+                    };
+                    return;
+                }
+            }
+        }
+
+        let extension = if windows { "bat" } else { "sh" };
+        let Some(script_path) = FileDialog::new()
+            .set_title("Export Commands")
+            .add_filter("Script", &[extension])
+            .set_file_name(&format!("convert.{}", extension))
+            .save_file()
+        else {
+            return;
+        };
+        match fs::write(&script_path, command_script(&command_lines, windows)) {
+            Ok(()) => {
+                let message = format!("Saved {} command(s) to {}", command_lines.len(), script_path.display());
+                self.drop_notice = Some((message, std::time::Instant::now()));
+            }
+            Err(e) => {
+                self.conversion_status = ConversionStatus::Error {
+                    message: format!("Failed to save {}: {}", script_path.display(), e),
+                };
+            }
+        }
+    }
+
+    /// Run every queued job, one after another
+    fn run_job_queue(&mut self) {
+        self.start_jobs(self.job_queue.clone(), false, false);
+    }
+
+    /// Output formats `path` is converted to: its override from the file list if the tool can write
+    /// it, otherwise the selected formats
+    fn output_formats_for(&self, path: &Path) -> Vec<OutputFormat> {
+        match self.format_overrides.get(path) {
+            Some(format) if self.available_output_formats().contains(format) => vec![*format],
+            _ => self.selected_output_formats(),
+        }
+    }
+
+    /// Whether any listed file, or the selected formats, will need the skeleton file
+    fn needs_skeleton(&self) -> bool {
+        self.selected_output_formats().iter().any(|format| format.requires_skeleton())
+            || self.input_paths.iter().any(|path| self.file_needs_skeleton(path))
+    }
+
+    /// Whether converting `path` with its output formats will need the skeleton file
+    fn file_needs_skeleton(&self, path: &Path) -> bool {
+        self.converter_tool.supports_file(path)
+            && self.output_formats_for(path).iter().any(|format| format.requires_skeleton())
+    }
+
+    /// Why the skeleton file and folder rules can't cover `input_paths` for their formats, if they can't
+    fn skeleton_problem(&self, input_paths: &[PathBuf]) -> Option<String> {
+        if !self.needs_skeleton() {
+            return None;
+        }
+        let rule_problem = self
+            .skeleton_rules
+            .iter()
+            .find_map(|rule| validate_skeleton_file(&rule.skeleton_file).err().map(|e| e.to_string()));
+        if rule_problem.is_some() {
+            return rule_problem;
+        }
+        match &self.skeleton_file {
+            None => {
+                let uncovered = input_paths
+                    .iter()
+                    .filter(|path| self.file_needs_skeleton(path) && skeleton_for_input(path, &self.skeleton_rules, None).is_none())
+                    .count();
+                if self.skeleton_rules.is_empty() {
+                    Some("KF conversion needs a skeleton file. Choose the skeleton.hkx the animations were made for.".to_string())
+                } else if uncovered > 0 {
+                    Some(format!(
+                        "{} file(s) aren't in a folder with its own skeleton. Choose a skeleton file for the rest.",
+                        uncovered
+                    ))
+                } else {
+                    None
+                }
+            }
+            Some(skeleton_file) => validate_skeleton_file(skeleton_file).err().map(|e| e.to_string()),
+        }
+    }
+
+    /// Pick a folder, then the skeleton for the inputs inside it
+    fn add_skeleton_rule(&mut self) {
+        let Some(folder) = FileDialog::new().set_title("Folder of animations").pick_folder() else {
+            return;
+        };
+        let Some(skeleton_file) = FileDialog::new()
+            .set_title("Skeleton for that folder")
+            .add_filter("HKX files", &["hkx"])
+            .pick_file()
+        else {
+            return;
+        };
+        // One skeleton per folder, so a second pick replaces the first
+        self.skeleton_rules.retain(|rule| rule.folder != folder);
+        self.skeleton_rules.push(SkeletonRule { folder, skeleton_file });
+    }
+
+    /// Why the selected tool, formats and `input_paths` can't be converted together, if they can't
+    fn configuration_problem(&self, input_paths: &[PathBuf]) -> Option<String> {
+        let output_formats = self.selected_output_formats();
+        let mut unconvertible = input_paths.iter().flat_map(|input_path| {
+            self.output_formats_for(input_path)
+                .into_iter()
+                .filter_map(move |format| self.tool_for_file(input_path, format).check_conversion(input_path, format).err())
+                .map(move |e| (input_path, e))
+        });
+        if let Some((input_path, first_problem)) = unconvertible.next() {
+            let file_name = input_path.file_name().unwrap_or_default().to_string_lossy();
+            let others = unconvertible.map(|(path, _)| path).filter(|path| *path != input_path).collect::<HashSet<_>>().len();
+            return Some(if others == 0 {
+                format!("{}: {}", file_name, first_problem)
+            } else {
+                format!("{}: {} (and {} more files can't be converted)", file_name, first_problem, others)
+            });
+        }
+        for format in &output_formats {
+            if let Some(Err(e)) = self.custom_extensions.get(format).map(|extension| validate_custom_extension(extension)) {
+                return Some(format!("Custom extension for {}: {}", format.label(), e));
+            }
+        }
+        if let Some(Err(e)) = self.active_output_template().map(|template| validate_output_template(&template)) {
+            return Some(format!("{:#}", e));
+        }
+        if self.output_next_to_inputs && input_paths.iter().any(|input_path| self.source_archive(input_path).is_some()) {
+            return Some("Files from an archive have no folder of their own. Turn off Next to inputs and pick an output folder.".to_string());
+        }
+        if self.tools_need_wine() {
+            return Some("The conversion tools are Windows programs and require Windows or Wine. Set a Wine command under Wine.".to_string());
+        }
+        if self.active_version_override().is_some_and(|version| version.contains(char::is_whitespace)) {
+            return Some("Target version can't contain spaces".to_string());
+        }
+        self.skeleton_problem(input_paths)
+    }
+
+    /// Whether the tools can't run because this isn't Windows and no Wine command is set
+    fn tools_need_wine(&self) -> bool {
+        !cfg!(windows) && self.settings.wine.is_none()
+    }
+
+    /// The version override to use with the current tool, if one is set and the tool takes it
+    fn active_version_override(&self) -> Option<String> {
+        let version = self.version_override.trim();
+        // Auto mixes tools whose version flags differ
+        (!self.auto_tool && self.converter_tool.supports_version_override() && !version.is_empty()).then(|| version.to_string())
+    }
+
+    /// Snapshot the current settings as a job for `input_paths`, or explain why they can't be converted
+    fn current_job(&self, input_paths: Vec<PathBuf>) -> Result<ConversionJob, String> {
+        if input_paths.is_empty() {
+            return Err("No input files selected".to_string());
+        }
+        let output_folder = if self.output_next_to_inputs {
+            // Only used to tell the batch's own folder apart in the queue and when checking it's still there
+            let first_folder = input_paths[0].parent().map(Path::to_path_buf);
+            self.base_folder.clone().or(first_folder).unwrap_or_default()
+        } else {
+            let Some(output_folder) = self.output_folder.clone() else {
+                return Err("No output folder selected".to_string());
+            };
+            output_folder
+        };
+        let output_formats = self.selected_output_formats();
+        if output_formats.is_empty() {
+            return Err("No output formats selected".to_string());
+        }
+        if let Some(problem) = self.configuration_problem(&input_paths) {
+            return Err(problem);
+        }
+
+        let available_formats = self.available_output_formats();
+        let format_overrides = input_paths
+            .iter()
+            .filter_map(|input_path| {
+                let format = self.format_overrides.get(input_path).filter(|format| available_formats.contains(format))?;
+                Some((input_path.clone(), *format))
+            })
+            .collect();
+        Ok(ConversionJob {
+            converter_tool: self.converter_tool,
+            auto_tool: self.auto_tool,
+            output_formats,
+            format_overrides,
+            input_paths,
+            output_folder,
+            skeleton_file: self.skeleton_file.clone(),
+            skeleton_rules: self.skeleton_rules.clone(),
+            output_suffix: self.output_suffix.clone(),
+            suffix_separator: self.settings.suffix_separator().to_string(),
+            custom_extensions: self.custom_extensions.clone(),
+            base_folder: self.base_folder.clone(),
+            archive_dirs: self.archive_extractions.iter().map(|extraction| extraction.dir.path().to_path_buf()).collect(),
+            flatten_output: self.flattens_output(),
+            next_to_inputs: self.output_next_to_inputs,
+            verify_round_trip: self.verify_round_trip,
+            abort_on_error: self.abort_on_error,
+            delete_sources: self.delete_sources,
+            version_override: self.active_version_override(),
+            filename_case: self.settings.filename_case,
+            output_template: self.active_output_template(),
+            reformat_xml: self.settings.reformat_xml,
+            wine: self.settings.wine.clone(),
+            atomic_output: self.settings.atomic_output,
+            skip_outputs: HashSet::new(),
+            timeout: self.settings.conversion_timeout(),
+        })
+    }
+
+    /// Count the input files that would be overwritten by their own (or another job's) output
+    fn count_overwritten_sources(jobs: &[ConversionJob]) -> usize {
+        let sources: HashSet<PathBuf> = jobs
+            .iter()
+            .flat_map(|job| &job.input_paths)
+            .map(|path| ensure_absolute_path(path))
+            .collect();
+
+        let mut overwritten = HashSet::new();
+        for job in jobs {
+            for (input_path, format) in job.outputs() {
+                if let Some(output_path) = job.output_path(input_path, format) {
+                    let output_path = ensure_absolute_path(&output_path);
+                    if sources.contains(&output_path) {
+                        overwritten.insert(output_path);
+                    }
+                }
+            }
+        }
+        overwritten.len()
+    }
+
+    /// Outputs that more than one input of the same job would write, with those inputs, in the order they
+    /// first appear. Jobs run one after another, so a later job rewriting an earlier one's output doesn't race.
+    fn output_collisions(jobs: &[ConversionJob]) -> Vec<(PathBuf, Vec<PathBuf>)> {
+        let mut collisions = Vec::new();
+        for job in jobs {
+            let mut writers: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+            let mut order = Vec::new();
+            for (input_path, format) in job.outputs() {
+                let Some(output_path) = job.output_path(input_path, format) else {
+                    continue;
+                };
+                let output_path = ensure_absolute_path(&output_path);
+                writers
+                    .entry(output_path.clone())
+                    .or_insert_with(|| {
+                        order.push(output_path);
+                        Vec::new()
+                    })
+                    .push(input_path.clone());
+            }
+            collisions.extend(order.into_iter().filter_map(|output_path| {
+                let inputs = writers.remove(&output_path)?;
+                (inputs.len() > 1).then_some((output_path, inputs))
+            }));
+        }
+        collisions
+    }
+
+    fn start_jobs(&mut self, jobs: Vec<ConversionJob>, overwrite_confirmed: bool, resume_answered: bool) {
+        if jobs.is_empty() {
+            self.conversion_status = ConversionStatus::Error {
+                message: "No jobs to run".to_string(),
+            };
+            return;
+        }
+
+        // Catch unplugged drives and read-only folders before any work starts
+        let output_folders: HashSet<PathBuf> = jobs
+            .iter()
+            .flat_map(|job| {
+                if job.next_to_inputs {
+                    job.input_paths.iter().filter_map(|input| ensure_absolute_path(input).parent().map(Path::to_path_buf)).collect()
+                } else {
+                    vec![job.output_folder.clone()]
+                }
+            })
+            .collect();
+        for output_folder in &output_folders {
+            if let Err(e) = check_writable_folder(output_folder) {
+                self.conversion_status = ConversionStatus::Error {
+                    message: format!("{:#}", e),
+                };
+                return;
+            }
+        }
+
+        // Skeletons may have been moved or replaced since a job was queued, and hkxcmd fails obscurely on a bad one
+        for job in jobs.iter().filter(|job| job.outputs().any(|(_, format)| format.requires_skeleton())) {
+            let skeletons = job.skeleton_file.iter().chain(job.skeleton_rules.iter().map(|rule| &rule.skeleton_file));
+            for skeleton in skeletons {
+                if let Err(e) = validate_skeleton_file(skeleton) {
+                    self.conversion_status = ConversionStatus::Error {
+                        message: format!("{:#}", e),
+                    };
+                    return;
+                }
+            }
+        }
+
+        // Empty inputs and files held open by a mod manager would otherwise fail deep inside the tool
+        let mut unreadable: Vec<UnreadableInput> = jobs
+            .iter()
+            .flat_map(|job| &job.input_paths)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|input| check_input_readable(input).err().map(|e| (input.clone(), format!("{:#}", e))))
+            .collect();
+        if !unreadable.is_empty() {
+            unreadable.sort();
+            self.pending_unreadable_inputs = Some((jobs, unreadable));
+            return;
+        }
+
+        // Inputs sharing an output would overwrite each other mid-batch, so have the names fixed first
+        let collisions = Self::output_collisions(&jobs);
+        if let Some((output_path, inputs)) = collisions.first() {
+            let input_names: Vec<String> = inputs.iter().map(|input| self.get_relative_path_display(input)).collect();
+            self.conversion_status = ConversionStatus::Error {
+                message: format!(
+                    "{} output file{} would be written by more than one input, e.g. {} from {}. Change the suffix or turn off Flatten output, then run again (Details lists them all).",
+                    collisions.len(),
+                    if collisions.len() == 1 { "" } else { "s" },
+                    output_path.file_name().unwrap_or_default().to_string_lossy(),
+                    input_names.join(" and ")
+                ),
+            };
+            self.error_details = collisions
+                .iter()
+                .map(|(output_path, inputs)| {
+                    let inputs: Vec<String> = inputs.iter().map(|input| format!("  ← {}", input.display())).collect();
+                    format!("{}\n{}", output_path.display(), inputs.join("\n"))
+                })
+                .collect();
+            return;
+        }
+
+        // Ask before writing over source files (e.g. same folder, same extension, no suffix)
+        if !overwrite_confirmed {
+            let overwritten = Self::count_overwritten_sources(&jobs);
+            if overwritten > 0 {
+                self.pending_overwrite_confirmation = Some((jobs, overwritten));
+                return;
+            }
+        }
+
+        // Offer to pick up where an interrupted run of the same batch stopped
+        if !resume_answered {
+            let completed = BatchJournal::completed_outputs(&jobs);
+            if !completed.is_empty() {
+                self.pending_resume_confirmation = Some((jobs, completed));
+                return;
+            }
+        }
+
+        // Setup channels for progress communication
+        let (progress_tx, progress_rx) = mpsc::unbounded_channel();
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        
+        let pause_gate = Arc::new(PauseGate::default());
+        let worker_counts = Arc::new(WorkerCounts::default());
+
+        self.progress_rx = Some(progress_rx);
+        self.worker_counts = Some(worker_counts.clone());
+        self.cancel_tx = Some(cancel_tx);
+        self.pause_gate = Some(pause_gate.clone());
+        self.conversion_status = ConversionStatus::Running {
+            current_file: "Starting...".to_string(),
+            progress: 0,
+            total: jobs.iter().map(ConversionJob::output_count).sum(),
+        };
+        self.failed_jobs.clear();
+        self.error_details.clear();
+        // Watch batches keep adding to the same log
+        if self.watch_folder.is_none() {
+            self.tool_log.clear();
+        }
+        self.completed_outputs.clear();
+
+        // Clone data needed for the async task
+        let tool_paths = self.tool_paths.clone();
+        let temp_base_dir = self.settings.temp_dir.clone();
+
+        // Spawn the async conversion task
+        self.tokio_handle.spawn(async move {
+            let result = Self::run_conversion_async(
+                jobs,
+                tool_paths,
+                temp_base_dir,
+                pause_gate,
+                worker_counts,
+                progress_tx,
+                cancel_rx,
+            ).await;
+
+            // The task will complete on its own
+            drop(result);
+        });
+    }
+
+    /// Run the jobs one after another, converting the files within each job concurrently
+    async fn run_conversion_async(
+        jobs: Vec<ConversionJob>,
+        tool_paths: ToolPaths,
+        temp_base_dir: Option<PathBuf>,
+        pause_gate: Arc<PauseGate>,
+        worker_counts: Arc<WorkerCounts>,
+        progress_tx: mpsc::UnboundedSender<ConversionProgress>,
+        mut cancel_rx: oneshot::Receiver<()>,
+    ) -> Result<()> {
+        let started_at = std::time::Instant::now();
+        let mut stats = ConversionStats::default();
+        let journal = match BatchJournal::start(&jobs) {
+            Ok(journal) => Some(Arc::new(journal)),
+            Err(e) => {
+                eprintln!("Resuming won't be possible for this batch: {:#}", e);
+                None
+            }
+        };
+
+        let total_files: usize = jobs.iter().map(ConversionJob::output_count).sum();
+        // Position of the next file across all jobs, for combined progress
+        let mut next_index = 0;
+        let mut successful_conversions = 0;
+        // Outputs left from an interrupted run of this batch, counted as converted
+        let mut resumed_conversions = 0;
+        let mut failed_conversions = 0;
+        // Failed conversions whose tool hung and was killed
+        let mut timed_out_conversions = 0;
+        let mut failed_jobs = Vec::new();
+        let mut error_details = Vec::new();
+        let mut completed_outputs = Vec::new();
+        // Inputs whose output didn't convert back to an identical source
+        let mut mismatched_files: Vec<PathBuf> = Vec::new();
+        // Set once cancel is pressed; files that already finished are still counted
+        let mut cancelled = false;
+        // One tool process per CPU core; more only makes them compete for the CPU
+        let workers = Arc::new(Semaphore::new(
+            std::thread::available_parallelism().map_or(4, |cores| cores.get()),
+        ));
+        // Output folder that disappeared mid-batch (e.g. an unplugged drive), which stops the batch
+        let mut lost_output_folder: Option<PathBuf> = None;
+
+        for (job_index, job) in jobs.iter().enumerate() {
+            let converter_tool = job.converter_tool;
+            let auto_tool = job.auto_tool;
+            let mut failed_files: Vec<PathBuf> = Vec::new();
+
+            // HCT can now process asynchronously with isolated temp directories
+            log_info!("Processing {} files with {}", job.output_count(), match converter_tool {
+                _ if auto_tool => "the tool picked for each file",
+                ConverterTool::Hct => "HCT (using isolated temp directories)",
+                ConverterTool::HavokBehaviorPostProcess => "HavokBehaviorPostProcess",
+                _ => "concurrent processing"
+            });
+            if jobs.len() > 1 {
+                let _ = progress_tx.send(
+                    ConversionProgress::item_running(&format!("job {} of {}", job_index + 1, jobs.len()), next_index, total_files)
+                        .with_log_line(format!("Job {} of {}: {}", job_index + 1, jobs.len(), job.label())),
+                );
+            }
+
+            // Output paths already claimed by a task of this job. Tools like
+            // HavokBehaviorPostProcess edit the output in place, so two tasks sharing
+            // a destination would race and silently corrupt each other's result.
+            // Jobs run one after another, so a later job may write an earlier one's output.
+            let mut claimed_outputs: HashSet<PathBuf> = HashSet::new();
+            let mut conversion_tasks = Vec::new();
+            // Input and output path for each spawned task, in spawn order
+            let mut task_inputs = Vec::new();
+
+            // One task per input per output format
+            for (input_path, output_format) in job.outputs() {
+                let index = next_index;
+                next_index += 1;
+
+                // Stop starting new files once cancelled, but collect the ones already started
+                if cancel_rx.try_recv().is_ok() || pause_gate.is_cancelled() {
+                    cancelled = true;
+                    break;
+                }
+
+                let output_path = job
+                    .output_path(input_path, output_format)
+                    .context("Failed to determine output path")?;
+
+                if !claimed_outputs.insert(ensure_absolute_path(&output_path)) {
+                    let file_name = input_path.file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string();
+                    eprintln!("ERROR: Output collision for {:?}: {:?} is already written by another file in this job", input_path, output_path);
+                    error_details.push((
+                        index,
+                        format!("#{} {} → {}\nOutput is already written by another file in this job", index + 1, input_path.display(), output_path.display()),
+                    ));
+                    let message = if job.flatten_output {
+                        format!("Output collision: {} has the same name as another file in the flattened output", file_name)
+                    } else {
+                        format!("Output collision: {} maps to an output already used in this job", file_name)
+                    };
+                    let _ = progress_tx.send(ConversionProgress::item_failed(&file_name, index, total_files, message));
+                    failed_conversions += 1;
+                    if !failed_files.contains(input_path) {
+                        failed_files.push(input_path.clone());
+                    }
+                    if job.abort_on_error {
+                        pause_gate.abort(format!("{}: output collides with another file in this job ({:?})", file_name, output_path));
+                        cancelled = true;
+                        break;
+                    }
+                    continue;
+                }
+
+                if job.skip_outputs.contains(&output_path) {
+                    successful_conversions += 1;
+                    resumed_conversions += 1;
+                    completed_outputs.push(CompletedOutput {
+                        index,
+                        path: output_path,
+                        sha256: None,
+                        resumed: true,
+                    });
+                    continue;
+                }
+
+                if let Some(parent) = output_path.parent() {
+                    if let Err(e) = fs::create_dir_all(parent) {
+                        if !job.output_folder.exists() {
+                            lost_output_folder = Some(job.output_folder.clone());
+                            break;
+                        }
+                        let file_name = input_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                        eprintln!("ERROR: Failed to create output directory {:?}: {}", parent, e);
+                        error_details.push((
+                            index,
+                            format!("#{} {} → {}\nFailed to create the output folder: {}", index + 1, input_path.display(), output_path.display(), e),
+                        ));
+                        let message = format!("Failed to create the output folder for {}", file_name);
+                        let _ = progress_tx.send(ConversionProgress::item_failed(&file_name, index, total_files, message));
+                        failed_conversions += 1;
+                        if !failed_files.contains(input_path) {
+                            failed_files.push(input_path.clone());
+                        }
+                        if job.abort_on_error {
+                            pause_gate.abort(format!("{}: failed to create output folder {:?}: {}", file_name, parent, e));
+                            cancelled = true;
+                            break;
+                        }
+                        continue;
+                    }
+                }
+
+                log_debug!("Preparing to convert {:?} to {:?}", input_path, output_path);
+
+                // Forward live tool output for this file to the log pane
+                let forwarder = ToolOutputForwarder {
+                    progress_tx: progress_tx.clone(),
+                    file_name: input_path.file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string(),
+                    file_index: index,
+                    total_files,
+                };
+                let converter = Converter::new(job.tool_for(input_path, output_format), output_format, tool_paths.clone())
+                    .with_skeleton_file(
+                        skeleton_for_input(input_path, &job.skeleton_rules, job.skeleton_file.as_deref()).map(Path::to_path_buf),
+                    )
+                    .with_temp_dir(temp_base_dir.clone())
+                    .with_timeout(job.timeout)
+                    .with_version_override(job.version_override.clone())
+                    .with_reformat_xml(job.reformat_xml)
+                    .with_wine(job.wine.clone())
+                    .with_atomic_output(job.atomic_output)
+                    .with_output_handler({
+                        let forwarder = forwarder.clone();
+                        move |line| forwarder.forward(line)
+                    });
+
+                // Clone needed data for the async task
+                let input_path_clone = input_path.clone();
+                let output_path_clone = output_path.clone();
+                let progress_tx_clone = progress_tx.clone();
+                let pause_gate = pause_gate.clone();
+                let verify_round_trip = job.verify_round_trip;
+                let abort_on_error = job.abort_on_error;
+                let journal = journal.clone();
+                let file_name = input_path.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+
+                // Create individual conversion task
+                let workers = workers.clone();
+                let worker_counts = worker_counts.clone();
+                worker_counts.queued.fetch_add(1, Ordering::SeqCst);
+                let conversion_task = tokio::spawn(async move {
+                    // Wait for a free worker, then hold off while paused; files already converting are left to finish
+                    let _worker = workers.acquire_owned().await;
+                    if !pause_gate.wait_while_paused().await {
+                        worker_counts.queued.fetch_sub(1, Ordering::SeqCst);
+                        log_debug!("Skipped {:?}: conversion was cancelled", input_path_clone);
+                        return Ok(None);
+                    }
+                    worker_counts.start();
+
+                    let result: Result<Option<ConvertedFile>> = async {
+                        // Send progress update when starting this file
+                        let _ = progress_tx_clone.send(ConversionProgress::item_running(&file_name, index, total_files));
+
+                        log_debug!("Starting conversion of {:?}", input_path_clone);
+
+                        // Run the actual conversion
+                        let result = converter.convert(&input_path_clone, &output_path_clone).await;
+
+                        match result {
+                            Ok(()) => {
+                                if !output_path_clone.exists() {
+                                    let error_msg = format!("Output file was not created: {:?}", output_path_clone);
+                                    eprintln!("ERROR: {}", error_msg);
+                                    if abort_on_error {
+                                        pause_gate.abort(format!("{}: {}", file_name, error_msg));
+                                    }
+                                    let message = format!("Failed to convert {}", file_name);
+                                    let _ = progress_tx_clone.send(ConversionProgress::item_failed(&file_name, index, total_files, message));
+                                    return Err(anyhow::anyhow!(error_msg));
+                                }
+
+                                log_debug!("Completed conversion of {:?}", input_path_clone);
+                                let metadata = fs::metadata(&output_path_clone)?;
+                                log_debug!("Output file size: {} bytes", metadata.len());
+                                let sha256 = match file_sha256(&output_path_clone) {
+                                    Ok(hash) => Some(hash),
+                                    Err(e) => {
+                                        eprintln!("ERROR hashing {:?}: {:#}", output_path_clone, e);
+                                        None
+                                    }
+                                };
+                                let mut converted = ConvertedFile {
+                                    input_bytes: fs::metadata(&input_path_clone).map(|m| m.len()).unwrap_or(0),
+                                    output_bytes: metadata.len(),
+                                    verify: VerifyOutcome::Skipped,
+                                    sha256,
+                                };
+
+                                if !verify_round_trip {
+                                    if let Some(journal) = &journal {
+                                        journal.record(&output_path_clone);
+                                    }
+                                    return Ok(Some(converted));
+                                }
+
+                                // Convert back and compare with the source
+                                let outcome = match converter.verify_round_trip(&input_path_clone, &output_path_clone).await {
+                                    Ok(outcome) => outcome,
+                                    Err(e) => {
+                                        eprintln!("ERROR verifying {}: {:#}", file_name, e);
+                                        VerifyOutcome::Mismatched
+                                    }
+                                };
+                                forwarder.forward(match outcome {
+                                    VerifyOutcome::Matched => "Round-trip verification passed",
+                                    VerifyOutcome::Mismatched => "Round-trip verification FAILED: output does not convert back to the source",
+                                    VerifyOutcome::Skipped => "Round-trip verification skipped: this tool can't convert back to the source format",
+                                });
+                                // A mismatched output is converted again on resume
+                                if outcome != VerifyOutcome::Mismatched {
+                                    if let Some(journal) = &journal {
+                                        journal.record(&output_path_clone);
+                                    }
+                                }
+                                converted.verify = outcome;
+                                Ok(Some(converted))
+                            }
+                            Err(e) => {
+                                eprintln!("ERROR converting {}: {}", file_name, e);
+                                if abort_on_error {
+                                    pause_gate.abort(format!("{}: {:#}", file_name, e));
+                                }
+                                let message = match timed_out(&e) {
+                                    Some(timeout) => format!("Timed out converting {} after {}s", file_name, timeout.as_secs()),
+                                    None => format!("Failed to convert {}", file_name),
+                                };
+                                let _ = progress_tx_clone.send(ConversionProgress::item_failed(&file_name, index, total_files, message));
+                                Err(e)
+                            }
+                        }
+                    }
+                    .await;
+                    worker_counts.finish();
+                    result
+                });
+
+                conversion_tasks.push(conversion_task);
+                task_inputs.push((index, input_path.clone(), output_path));
+            }
+
+            // Wait for this job's conversions to complete before starting the next job
+            let results = join_all(conversion_tasks).await;
+            
+            // Check results and count successes
+            for (result, (index, input_path, output_path)) in results.into_iter().zip(task_inputs) {
+                match result {
+                    // Cancelled before it started
+                    Ok(Ok(None)) => {}
+                    Ok(Ok(Some(converted))) => {
+                        successful_conversions += 1;
+                        completed_outputs.push(CompletedOutput {
+                            index,
+                            path: output_path,
+                            sha256: converted.sha256,
+                            resumed: false,
+                        });
+                        stats.input_bytes += converted.input_bytes;
+                        stats.output_bytes += converted.output_bytes;
+                        if converted.verify == VerifyOutcome::Mismatched {
+                            mismatched_files.push(input_path);
+                        }
+                    }
+                    Ok(Err(e)) => {
+                        eprintln!("ERROR: Conversion task failed: {}", e);
+                        error_details.push((index, format!("#{} {} → {}\n{:#}", index + 1, input_path.display(), output_path.display(), e)));
+                        failed_conversions += 1;
+                        if timed_out(&e).is_some() {
+                            timed_out_conversions += 1;
+                        }
+                        if !failed_files.contains(&input_path) {
+                            failed_files.push(input_path);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("ERROR: Task execution failed: {}", e);
+                        error_details.push((index, format!("#{} {} → {}\n{}", index + 1, input_path.display(), output_path.display(), e)));
+                        failed_conversions += 1;
+                        if !failed_files.contains(&input_path) {
+                            failed_files.push(input_path);
+                        }
+                    }
+                }
+            }
+
+            // Files that failed because the folder vanished, and the ones never started, can be retried
+            if lost_output_folder.is_some() || !job.output_folder.exists() {
+                lost_output_folder = Some(job.output_folder.clone());
+                let completed: HashSet<&PathBuf> = completed_outputs.iter().map(|output| &output.path).collect();
+                for input_path in &job.input_paths {
+                    let all_converted = job.formats_for(input_path).iter().all(|format| {
+                        job.output_path(input_path, *format).is_some_and(|output_path| completed.contains(&output_path))
+                    });
+                    if !all_converted && !failed_files.contains(input_path) {
+                        failed_files.push(input_path.clone());
+                    }
+                }
+            }
+
+            if !failed_files.is_empty() {
+                // Retry in the job's input order, not the order the failures came in
+                let failed: HashSet<&PathBuf> = failed_files.iter().collect();
+                let failed_files = job.input_paths.iter().filter(|path| failed.contains(path)).cloned().collect();
+                failed_jobs.push(job.with_inputs(failed_files));
+            }
+
+            if lost_output_folder.is_some() {
+                break;
+            }
+
+            if cancelled || cancel_rx.try_recv().is_ok() || pause_gate.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+        }
+
+        // Report results in input order rather than the order the conversions finished in
+        completed_outputs.sort_by_key(|output| output.index);
+        error_details.sort_by_key(|(index, _)| *index);
+        let error_details: Vec<String> = error_details.into_iter().map(|(_, details)| details).collect();
+
+        if let Some(output_folder) = lost_output_folder {
+            let _ = progress_tx.send(ConversionProgress::batch_done(
+                "Stopped",
+                successful_conversions,
+                total_files,
+                ConversionStatus::Error {
+                    message: format!(
+                        "Output folder {:?} is no longer available. Converted {} of {} files before it disappeared.",
+                        output_folder, successful_conversions, total_files
+                    ),
+                },
+                failed_jobs,
+                completed_outputs,
+                error_details,
+            ));
+            return Ok(());
+        }
+
+        // Show the error that stopped the batch rather than a plain cancel
+        if let Some(error) = pause_gate.abort_error() {
+            let _ = progress_tx.send(ConversionProgress::batch_done(
+                "Stopped",
+                successful_conversions,
+                total_files,
+                ConversionStatus::Error {
+                    message: format!(
+                        "Stopped at the first error. Converted {} of {} files before it.\n{}",
+                        successful_conversions, total_files, error
+                    ),
+                },
+                failed_jobs,
+                completed_outputs,
+                error_details,
+            ));
+            return Ok(());
+        }
+
+        // Report what finished before the cancel so a partial run isn't lost
+        if cancelled {
+            let failed_note = if failed_conversions > 0 {
+                format!(", {} failed", failed_conversions)
+            } else {
+                String::new()
+            };
+            let _ = progress_tx.send(ConversionProgress::batch_done(
+                "Cancelled",
+                successful_conversions,
+                total_files,
+                ConversionStatus::Error {
+                    message: format!(
+                        "Conversion cancelled: {} of {} files converted before cancelling{}",
+                        successful_conversions, total_files, failed_note
+                    ),
+                },
+                failed_jobs,
+                completed_outputs,
+                error_details,
+            ));
+            return Ok(());
+        }
+
+        // The batch ran to the end, so there's nothing left to resume
+        drop(journal);
+        BatchJournal::remove();
+
+        let sources_deleted = Self::delete_converted_sources(&jobs, &completed_outputs, &mismatched_files);
+
+        // Send completion message
+        stats.duration = started_at.elapsed();
+        let timeout_note = if timed_out_conversions > 0 {
+            format!(", {} timed out", timed_out_conversions)
+        } else {
+            String::new()
+        };
+        let delete_note = if sources_deleted > 0 {
+            format!(", {} source file{} deleted", sources_deleted, if sources_deleted == 1 { "" } else { "s" })
+        } else {
+            String::new()
+        };
+        let resumed_note = if resumed_conversions > 0 {
+            format!(" ({} already converted by an earlier run)", resumed_conversions)
+        } else {
+            String::new()
+        };
+        let verify_note = if mismatched_files.is_empty() {
+            String::new()
+        } else {
+            for path in &mismatched_files {
+                eprintln!("WARNING: Round-trip verification mismatch: {:?}", path);
+            }
+            format!(", {} failed round-trip verification", mismatched_files.len())
+        };
+        if failed_conversions > 0 || !mismatched_files.is_empty() {
+            let _ = progress_tx.send(ConversionProgress::batch_done(
+                "Completed",
+                successful_conversions,
+                total_files,
+                ConversionStatus::Error {
+                    message: format!(
+                        "Converted {} of {} files{} ({} failed{}{}{})",
+                        successful_conversions, total_files, resumed_note, failed_conversions, timeout_note, verify_note, delete_note
+                    ),
+                },
+                failed_jobs,
+                completed_outputs,
+                error_details,
+            ));
+        } else {
+            let _ = progress_tx.send(ConversionProgress::batch_done(
+                "Completed",
+                successful_conversions,
+                total_files,
+                ConversionStatus::Completed {
+                    message: format!(
+                        "Successfully converted {} of {} files{}{}",
+                        successful_conversions, total_files, resumed_note, delete_note
+                    ),
+                    stats,
+                },
+                Vec::new(),
+                completed_outputs,
+                Vec::new(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Delete the inputs of `delete_sources` jobs whose outputs, in every job of the batch that
+    /// used them, were written by this run and aren't empty. Returns how many were deleted.
+    fn delete_converted_sources(jobs: &[ConversionJob], completed_outputs: &[CompletedOutput], mismatched_files: &[PathBuf]) -> usize {
+        let completed: HashSet<&PathBuf> =
+            completed_outputs.iter().filter(|output| !output.resumed).map(|output| &output.path).collect();
+        let is_safe_to_delete = |input_path: &PathBuf| {
+            let input_absolute = ensure_absolute_path(input_path);
+            !mismatched_files.contains(input_path)
+                && jobs.iter().filter(|job| job.input_paths.contains(input_path)).all(|job| {
+                    job.formats_for(input_path).iter().all(|&format| {
+                        job.output_path(input_path, format).is_some_and(|output_path| {
+                            // Never delete a file that is its own output
+                            ensure_absolute_path(&output_path) != input_absolute
+                                && completed.contains(&output_path)
+                                && fs::metadata(&output_path).is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+                        })
+                    })
+                })
+        };
+
+        let to_delete: HashSet<&PathBuf> = jobs
+            .iter()
+            .filter(|job| job.delete_sources)
+            .flat_map(|job| &job.input_paths)
+            .filter(|input_path| is_safe_to_delete(input_path))
+            .collect();
+        let mut deleted = 0;
+        for input_path in to_delete {
+            match fs::remove_file(input_path) {
+                Ok(()) => {
+                    log_info!("Deleted source {:?}", input_path);
+                    deleted += 1;
+                }
+                Err(e) => eprintln!("ERROR: Failed to delete source {:?}: {}", input_path, e),
+            }
+        }
+        deleted
+    }
+
+    /// Get relative path for display purposes
+    fn get_relative_path_display(&self, path: &Path) -> String {
+        if let Some(extraction) = self.source_archive(path) {
+            let relative = path.strip_prefix(extraction.dir.path()).unwrap_or(path);
+            return format!(
+                "{}: {}",
+                extraction.archive.file_name().unwrap_or_default().to_string_lossy(),
+                relative.to_string_lossy()
+            );
+        }
+        if let Some(base_folder) = &self.base_folder {
+            if let Ok(relative) = path.strip_prefix(base_folder) {
+                relative.to_string_lossy().to_string()
+            } else {
+                // Keep the parent folder so files from different roots can be told apart
+                match path.parent().and_then(Path::file_name) {
+                    Some(parent) => format!(
+                        "...{sep}{}{sep}{}",
+                        parent.to_string_lossy(),
+                        path.file_name().unwrap_or_default().to_string_lossy(),
+                        sep = std::path::MAIN_SEPARATOR
+                    ),
+                    None => path.to_string_lossy().to_string(),
+                }
+            }
+        } else {
+            path.file_name().unwrap_or_default().to_string_lossy().to_string()
+        }
+    }
+
+    /// Whether `path` is outside the base folder, so its subfolders won't be recreated in the output
+    fn is_outside_base_folder(&self, path: &Path) -> bool {
+        self.base_folder_for(path).is_some_and(|base_folder| !path.starts_with(base_folder))
+    }
+
+    /// Folder whose layout `path` keeps in the output: its archive's extraction folder, or the base folder
+    fn base_folder_for(&self, path: &Path) -> Option<&Path> {
+        self.source_archive(path).map(|extraction| extraction.dir.path()).or(self.base_folder.as_deref())
+    }
+
+    fn render_main_ui(&mut self, ui: &mut egui::Ui) {
+        let compact = self.settings.compact_layout;
+        if compact {
+            ui.spacing_mut().item_spacing = egui::vec2(6.0, 3.0);
+        } else {
+            ui.vertical_centered(|ui| {
+                ui.add_space(10.0);
+                ui.heading(
+                    RichText::new("Composite HKX Conversion Tool")
+                        .size(24.0)
+                        .color(Color32::LIGHT_BLUE),
+                );
+                ui.add_space(10.0);
+            });
+        }
+
+        self.render_update_banner(ui);
+        if self.tools_need_wine() {
+            ui.label(
+                RichText::new("⚠ The conversion tools are Windows programs. To run them on this system, install Wine and set it under Wine below.")
+                    .color(Color32::from_rgb(255, 180, 80)),
+            );
+        }
+
+        ui.separator();
+
+        egui::Grid::new("main_grid")
+            .num_columns(2)
+            .spacing(if compact { [6.0, 4.0] } else { [10.0, 10.0] })
+            .show(ui, |ui| {
+                ui.label("Preset:");
+                self.render_presets(ui);
+                ui.end_row();
+
+                ui.label("Converter Tool:");
+                self.render_converter_tool_picker(ui, compact);
+                ui.end_row();
+
+                ui.label("Input File Filter:");
+                self.render_input_filter_picker(ui, compact);
+                ui.end_row();
+
+                ui.label("Input Files:");
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("Browse Files").on_hover_text("Add files to the list").clicked() {
+                            self.browse_input_files();
+                        }
+                        if ui.button("Import List").on_hover_text("Add the files listed in a .txt file, one path per line").clicked() {
+                            self.import_file_list();
+                        }
+                        // Shift+click replaces the list for one selection without changing the setting
+                        let replace = self.settings.folder_replaces_list || ui.input(|i| i.modifiers.shift);
+                        let folder_hint = if self.settings.folder_replaces_list {
+                            "Replace the list with the folder's files"
+                        } else {
+                            "Add the folder's files to the list (Shift+click to replace the list)"
+                        };
+                        if ui.button("Select Folder").on_hover_text(folder_hint).clicked() {
+                            self.select_input_folder(false, replace);
+                        }
+                        if ui.button("Select Folder (+ Subfolders)").on_hover_text(folder_hint).clicked() {
+                            self.select_input_folder(true, replace);
+                        }
+                        if ui
+                            .checkbox(&mut self.settings.folder_replaces_list, "Replace list")
+                            .on_hover_text("Clear the list before Select Folder adds a folder's files. Undo brings the old list back.")
+                            .changed()
+                        {
+                            if let Err(e) = self.settings.save() {
+                                eprintln!("Failed to save settings: {}", e);
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.framework_layout, "FNIS/Nemesis layout (animations only)").on_hover_text(
+                            "Select Folder only adds the animations inside 'animations' folders, skipping behaviors, \
+                             character files, Nemesis_Engine and files FNIS generates. Outputs keep the exact folder structure.",
+                        );
+                        if ui
+                            .checkbox(&mut self.settings.follow_links, "Follow folder links")
+                            .on_hover_text(
+                                "Let Select Folder (+ Subfolders) go into symlinked and junctioned folders, which can lead outside \
+                                 the selected folder. Links that loop back are skipped.",
+                            )
+                            .changed()
+                        {
+                            if let Err(e) = self.settings.save() {
+                                eprintln!("Failed to save settings: {}", e);
+                            }
+                        }
+                        if ui
+                            .checkbox(&mut self.settings.include_hidden_files, "Include hidden files")
+                            .on_hover_text(
+                                "Let Select Folder add hidden and system files and look inside hidden folders, dot-folders, \
+                                 __MACOSX and the recycle bin. Files picked or dropped one by one are always added.",
+                            )
+                            .changed()
+                        {
+                            if let Err(e) = self.settings.save() {
+                                eprintln!("Failed to save settings: {}", e);
+                            }
+                        }
+                    });
+                    if let Some(scan) = self.folder_scan.as_ref().filter(|scan| !scan.finished) {
+                        let mut stop = false;
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(format!(
+                                "Scanning {}… {} found",
+                                scan.folder.file_name().unwrap_or_default().to_string_lossy(),
+                                scan.added.len() + scan.held.len()
+                            ));
+                            stop = ui.small_button("Stop").on_hover_text("Stop scanning and keep the files found so far").clicked();
+                        });
+                        if stop {
+                            self.stop_folder_scan(true);
+                        }
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Watch Folder:");
+                self.render_watch_folder(ui);
+                ui.end_row();
+
+                // Skeleton file selection (only show for KF conversion)
+                if self.needs_skeleton() {
+                    ui.label("Skeleton File:");
+                    ui.horizontal(|ui| {
+                        if let Some(ref skeleton_file) = self.skeleton_file {
+                            ui.label(skeleton_file.file_name().unwrap_or_default().to_string_lossy());
+                        } 
+                        // else {
+                        //     ui.label("(required for animation conversion)");
+                        // }
+                        if ui.button("Browse").clicked() {
+                            if let Some(file) = FileDialog::new()
+                                .add_filter("HKX files", &["hkx"])
+                                .pick_file()
+                            {
+                                self.skeleton_file = Some(file);
+                            }
+                        }
+                        if self.skeleton_file.is_some() && ui.button("Clear").clicked() {
+                            self.skeleton_file = None;
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Folder Skeletons:");
+                    ui.vertical(|ui| {
+                        let mut rule_to_remove = None;
+                        for (index, rule) in self.skeleton_rules.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!(
+                                    "{} → {}",
+                                    self.get_relative_path_display(&rule.folder),
+                                    rule.skeleton_file.file_name().unwrap_or_default().to_string_lossy()
+                                ))
+                                .on_hover_text(rule.skeleton_file.to_string_lossy());
+                                if ui.small_button("❌").clicked() {
+                                    rule_to_remove = Some(index);
+                                }
+                            });
+                        }
+                        if let Some(index) = rule_to_remove {
+                            self.skeleton_rules.remove(index);
+                        }
+                        if ui
+                            .button("Add Folder Rule")
+                            .on_hover_text("Use a different skeleton for the inputs inside a folder. The skeleton file above is used for everything else.")
+                            .clicked()
+                        {
+                            self.add_skeleton_rule();
+                        }
+                    });
+                    ui.end_row();
+
+                    // Explain up front why the batch can't run yet
+                    if let Some(problem) = self.skeleton_problem(&self.input_paths) {
+                        ui.label("");
+                        ui.label(RichText::new(format!("⚠ {}", problem)).color(Color32::from_rgb(255, 180, 80)));
+                        ui.end_row();
+                    }
+                }
+
+                ui.label("Output Folder:");
+                self.render_output_folder(ui);
+                ui.end_row();
+
+                ui.label("Folder Structure:");
+                ui.add_enabled_ui(!self.framework_layout && self.active_output_template().is_none(), |ui| {
+                    ui.checkbox(&mut self.flatten_output, "Flatten output (write all files directly into the output folder)")
+                        .on_disabled_hover_text(if self.framework_layout {
+                            "FNIS/Nemesis layout keeps the folder structure"
+                        } else {
+                            "The output template sets the folders"
+                        });
+                });
+                ui.end_row();
+
+                ui.label("Output Template:");
+                self.render_output_template(ui);
+                ui.end_row();
+
+                if let Some(Err(e)) = self.active_output_template().map(|template| validate_output_template(&template)) {
+                    ui.label("");
+                    ui.label(RichText::new(format!("⚠ {:#}", e)).color(Color32::from_rgb(255, 180, 80)));
+                    ui.end_row();
+                }
+
+                ui.label("Filename Case:");
+                ui.horizontal(|ui| {
+                    for case in [FilenameCase::Preserve, FilenameCase::Lowercase, FilenameCase::Uppercase] {
+                        if ui.selectable_label(self.settings.filename_case == case, case.label()).clicked()
+                            && self.settings.filename_case != case
+                        {
+                            self.settings.filename_case = case;
+                            if let Err(e) = self.settings.save() {
+                                eprintln!("Failed to save settings: {}", e);
+                            }
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("Case of output file names. Folder names are kept as they are.");
+                ui.end_row();
+
+                ui.label("Output Suffix:");
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.output_suffix);
+                    ui.label("Separator:");
+                    let mut separator = self.settings.suffix_separator().to_string();
+                    let response = ui
+                        .add(egui::TextEdit::singleline(&mut separator).desired_width(30.0))
+                        .on_hover_text("Put between the file name and the suffix, e.g. _ - or . Leave empty to join them directly.");
+                    if response.changed() {
+                        self.settings.suffix_separator = Some(separator);
+                        if let Err(e) = self.settings.save() {
+                            eprintln!("Failed to save settings: {}", e);
+                        }
+                    }
+                    if let Err(e) = validate_suffix_separator(self.settings.suffix_separator()) {
+                        ui.label(RichText::new(format!("{} (using _)", e)).color(Color32::from_rgb(255, 120, 120)).size(12.0));
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Custom Extension:");
+                ui.horizontal(|ui| {
+                    // The custom extension only applies to the currently selected output format
+                    let mut extension_text = self.custom_extensions.get(&self.output_format).cloned().unwrap_or_default();
+                    if ui.text_edit_singleline(&mut extension_text).changed() {
+                        if extension_text.is_empty() {
+                            self.custom_extensions.remove(&self.output_format);
+                        } else {
+                            self.custom_extensions.insert(self.output_format, extension_text);
+                        }
+                    }
+                    ui.label(
+                        RichText::new(format!("(for {})", self.output_format.label()))
+                            .color(Color32::from_rgb(150, 150, 150))
+                            .size(12.0),
+                    );
+                    // ui.label("(optional - leave empty to use format default)");
+                });
+                ui.end_row();
+
+                let extension_problem = self
+                    .custom_extensions
+                    .get(&self.output_format)
+                    .and_then(|extension| validate_custom_extension(extension).err());
+                if let Some(problem) = extension_problem {
+                    ui.label("");
+                    ui.label(RichText::new(format!("⚠ {}", problem)).color(Color32::from_rgb(255, 180, 80)));
+                    ui.end_row();
+                }
+
+                ui.label("Output Format:");
+                self.render_output_format(ui);
+                ui.end_row();
+
+                ui.label("Temp Folder:");
+                self.render_temp_folder(ui);
+                ui.end_row();
+
+                ui.label("Timeout:");
+                self.render_timeout_setting(ui);
+                ui.end_row();
+
+                ui.label("Max Files:");
+                self.render_max_files_setting(ui);
+                ui.end_row();
+
+                if !cfg!(windows) {
+                    ui.label("Wine:");
+                    self.render_wine_setting(ui);
+                    ui.end_row();
+                }
+
+                if !self.auto_tool && self.converter_tool.supports_version_override() {
+                    ui.label("Target Version:");
+                    self.render_version_override(ui);
+                    ui.end_row();
+                }
+
+                if self.selected_output_formats().contains(&OutputFormat::Xml) {
+                    ui.label("XML Formatting:");
+                    if ui
+                        .checkbox(&mut self.settings.reformat_xml, "Reformat XML outputs with consistent tab indentation")
+                        .on_hover_text("Makes XML from different tools diff cleanly in version control")
+                        .changed()
+                    {
+                        if let Err(e) = self.settings.save() {
+                            eprintln!("Failed to save settings: {}", e);
+                        }
+                    }
+                    ui.end_row();
+                }
+
+                ui.label("On Error:");
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.abort_on_error, false, "Continue")
+                        .on_hover_text("Convert the remaining files and list the failures at the end");
+                    ui.selectable_value(&mut self.abort_on_error, true, "Abort on first error")
+                        .on_hover_text("Stop starting new files as soon as one fails and show its error");
+                });
+                ui.end_row();
+
+                ui.label("Source Files:");
+                let mut delete_sources = self.delete_sources;
+                if ui
+                    .checkbox(
+                        &mut delete_sources,
+                        RichText::new("Delete source files after successful conversion").color(Color32::from_rgb(220, 100, 100)),
+                    )
+                    .on_hover_text("Each input is deleted only once all of its outputs exist and aren't empty, and never when it is its own output")
+                    .changed()
+                {
+                    if delete_sources {
+                        self.confirm_delete_sources = true;
+                    } else {
+                        self.delete_sources = false;
+                    }
+                }
+                ui.end_row();
+
+                ui.label("Verify:");
+                ui.checkbox(&mut self.verify_round_trip, "Round-trip check (convert outputs back and compare with the source)");
+                ui.end_row();
+
+                ui.label("Atomic Writes:");
+                if ui
+                    .checkbox(&mut self.settings.atomic_output, "Write each output to a temporary file and rename it into place when complete")
+                    .on_hover_text("A crash or failed conversion then never leaves a truncated output behind")
+                    .changed()
+                {
+                    if let Err(e) = self.settings.save() {
+                        eprintln!("Failed to save settings: {}", e);
+                    }
+                }
+                ui.end_row();
+
+                ui.label("Batch End:");
+                ui.horizontal(|ui| {
+                    let mut changed = false;
+                    // The chime is played through the Windows audio output only
+                    if cfg!(windows) {
+                        changed |= ui.checkbox(&mut self.settings.chime_on_batch_end, "Play a chime").changed();
+                    }
+                    changed |= ui.checkbox(&mut self.settings.flash_on_batch_end, "Flash the window").changed();
+                    if changed {
+                        if let Err(e) = self.settings.save() {
+                            eprintln!("Failed to save settings: {}", e);
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("Signal the end of a batch, with a lower tone and a red flash when it failed");
+                ui.end_row();
+
+                ui.label("Console Output:");
+                ui.horizontal(|ui| {
+                    for level in [LogLevel::Quiet, LogLevel::Normal, LogLevel::Verbose] {
+                        if ui.selectable_label(self.settings.log_level == level, level.label()).clicked()
+                            && self.settings.log_level != level
+                        {
+                            self.settings.log_level = level;
+                            set_log_level(level);
+                            if let Err(e) = self.settings.save() {
+                                eprintln!("Failed to save settings: {}", e);
+                            }
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("What is printed to the console: errors only, a line per batch, or every command and temporary file");
+                ui.end_row();
+
+                ui.label("Updates:");
+                self.render_update_settings(ui);
+                ui.end_row();
+            });
+
+        ui.add_space(if compact { 4.0 } else { 10.0 });
+
+        // Selected Files section outside the grid for more space
+        ui.horizontal(|ui| {
+            ui.label("Selected Files:");
+            ui.label(format!("{} files selected", self.input_paths.len()));
+            if !self.selected_input_paths.is_empty()
+                && ui.button(format!("Remove Selected ({})", self.selected_input_paths.len())).clicked()
+            {
+                let selected = std::mem::take(&mut self.selected_input_paths);
+                self.push_input_undo(Vec::new());
+                self.input_paths.retain(|path| !selected.contains(path));
+                self.prune_format_overrides();
+            }
+            // Edit a single selected HKX by hand
+            let edit_candidate = match self.selected_input_paths.iter().collect::<Vec<_>>().as_slice() {
+                [path] if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("hkx")) => Some((*path).clone()),
+                _ => None,
+            };
+            if let Some(path) = edit_candidate {
+                if ui
+                    .add_enabled(self.xml_edit.is_none(), egui::Button::new("Edit as XML"))
+                    .on_hover_text("Convert to XML, edit it, then compile it back over the source")
+                    .clicked()
+                {
+                    self.start_xml_edit(path);
+                }
+            }
+            // Peek at a single selected HKX or XML without leaving the app
+            let view_candidate = match self.selected_input_paths.iter().collect::<Vec<_>>().as_slice() {
+                [path] if self.xml_viewer.is_none() && is_xml_viewable(path) => Some((*path).clone()),
+                _ => None,
+            };
+            if let Some(path) = view_candidate {
+                if ui
+                    .button("View XML")
+                    .on_hover_text("Show the file as XML in a side panel, decompiling an HKX in a temporary folder")
+                    .clicked()
+                {
+                    self.open_xml_viewer(path);
+                }
+            }
+            if !self.input_paths.is_empty() {
+                if self.selected_input_paths.len() == self.input_paths.len() {
+                    if ui.button("Select None").clicked() {
+                        self.selected_input_paths.clear();
+                    }
+                } else if ui.button("Select All").clicked() {
+                    self.selected_input_paths = self.input_paths.iter().cloned().collect();
+                }
+            }
+            if ui.button("Clear All").clicked() {
+                self.clear_input_files();
+            }
+            if !self.input_undo.is_empty()
+                && ui.button("Undo").on_hover_text("Restore the list from before the last removal (Ctrl+Z)").clicked()
+            {
+                self.undo_input_change();
+            }
+        });
+        
+        // Show base folder information if set
+        if let Some(base_folder) = self.base_folder.as_ref().filter(|_| !compact) {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("📁 Base folder:").color(Color32::from_rgb(100, 150, 200)).size(12.0));
+                ui.label(RichText::new(base_folder.to_string_lossy()).color(Color32::from_rgb(150, 150, 150)).size(12.0));
+                let outside = self.input_paths.iter().filter(|path| self.is_outside_base_folder(path)).count();
+                if outside > 0 && !self.flattens_output() {
+                    ui.label(
+                        RichText::new(format!("({} file(s) outside it keep no subfolders)", outside))
+                            .color(Color32::from_rgb(200, 160, 90))
+                            .size(12.0),
+                    );
+                }
+            });
+        }
+        
+        // Show drag and drop hint
+        if !compact {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("💡 Tip: You can drag and drop files or folders directly onto this window").color(Color32::from_rgb(100, 100, 100)).size(12.0));
+            });
+        }
+        
+        // Breakdown of the list by extension, flagging types the selected tool can't read
+        if !self.input_paths.is_empty() {
+            let mut extension_counts: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+            for path in &self.input_paths {
+                let extension = path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+                *extension_counts.entry(extension).or_default() += 1;
+            }
+            ui.horizontal_wrapped(|ui| {
+                ui.label(RichText::new("📊").size(12.0));
+                for (extension, count) in &extension_counts {
+                    let name = if extension.is_empty() { "no extension".to_string() } else { extension.to_uppercase() };
+                    let text = RichText::new(format!("{} {}", count, name)).size(12.0);
+                    if self.converter_tool.supports_extension(extension) {
+                        ui.label(text.color(Color32::from_rgb(150, 150, 150)));
+                    } else {
+                        ui.label(text.color(Color32::from_rgb(255, 180, 80)))
+                            .on_hover_text(format!("{} can't convert these files", self.converter_tool.label()));
+                    }
+                }
+            });
+        }
+
+        // Summarize how many files will be converted against the skeleton
+        let skeleton_count = self.input_paths.iter().filter(|path| self.file_needs_skeleton(path)).count();
+        if skeleton_count > 0 {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(format!("🦴 {} file(s) need a skeleton", skeleton_count))
+                        .color(Color32::from_rgb(200, 170, 100))
+                        .size(12.0),
+                );
+            });
+        }
+
+        // Show HCT processing note
+        // if self.converter_tool == ConverterTool::Hct {
+        //     ui.horizontal(|ui| {
+        //         ui.label(RichText::new("ℹ️ HCT files use isolated temp directories for safe concurrent processing").color(Color32::from_rgb(100, 100, 100)).size(12.0));
+        //     });
+        // }
+        
+        // Sortable column headers
+        let mut sort_by = None;
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Sort by:").size(12.0));
+            for (column, label) in [
+                (FileSortColumn::Name, "Name"),
+                (FileSortColumn::Size, "Size"),
+                (FileSortColumn::Format, "Format"),
+            ] {
+                let text = match self.input_sort {
+                    Some((sorted, true)) if sorted == column => format!("{} ⏶", label),
+                    Some((sorted, false)) if sorted == column => format!("{} ⏷", label),
+                    _ => label.to_string(),
+                };
+                if ui.small_button(text).clicked() {
+                    sort_by = Some(column);
+                }
+            }
+            ui.separator();
+            ui.add(egui::TextEdit::singleline(&mut self.input_filter).hint_text("🔍 Filter").desired_width(180.0))
+                .on_hover_text("Only show files whose path contains this text. Hidden files are still converted.");
+            if !self.input_filter.is_empty() {
+                let needle = self.input_filter.to_lowercase();
+                let shown = self.input_paths.iter().filter(|path| self.matches_input_filter(path, &needle)).count();
+                ui.label(RichText::new(format!("{} of {} shown", shown, self.input_paths.len())).size(12.0).color(Color32::from_rgb(150, 150, 150)));
+                if ui.small_button("Clear").clicked() {
+                    self.input_filter.clear();
+                }
+            }
+        });
+        if let Some(column) = sort_by {
+            self.sort_input_paths(column);
+        }
+
+        // Scrollable area for file list - takes remaining available space
+        egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                let mut files_to_remove = Vec::new();
+                let mut selection_changes = Vec::new();
+                // (from, to) index pair when a row is dropped onto another row
+                let mut file_to_move = None;
+                // File picked with "Convert Only This File" in a row's context menu
+                let mut file_to_convert = None;
+                let can_convert = !self.is_converting();
+                // (path, override) when a row's output format is changed; None goes back to the selected formats
+                let mut format_override_change = None;
+                let available_formats = self.available_output_formats();
+                let selected_formats: Vec<&str> = self.selected_output_formats().iter().map(|format| format.label()).collect();
+                let default_label = format!("Default ({})", selected_formats.join(", "));
+                let filter_needle = self.input_filter.to_lowercase();
+                // Read each file's size and format once, the first time it's listed
+                for path in &self.input_paths {
+                    if !self.input_file_info.contains_key(path) {
+                        self.input_file_info.insert(path.clone(), InputFileInfo::read(path));
+                    }
+                }
+                egui::Grid::new("input_file_list")
+                    .num_columns(7)
+                    .spacing([8.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (index, path) in self.input_paths.iter().enumerate() {
+                            // Filtered-out rows keep their index, so removing and reordering still address `input_paths`
+                            if !self.matches_input_filter(path, &filter_needle) {
+                                continue;
+                            }
+                            // Drag handle for reordering
+                            let mut row_rect = ui
+                                .dnd_drag_source(egui::Id::new(("input_file_drag", index)), index, |ui| {
+                                    ui.label(RichText::new("☰").color(Color32::from_rgb(150, 150, 150)));
+                                })
+                                .response
+                                .rect;
+
+                            let mut selected = self.selected_input_paths.contains(path);
+                            let checkbox = ui.checkbox(&mut selected, "");
+                            if checkbox.changed() {
+                                selection_changes.push((path.clone(), selected));
+                            }
+
+                            let remove = ui.small_button("❌");
+                            if remove.clicked() {
+                                files_to_remove.push(index);
+                            }
+
+                            let name = ui
+                                .horizontal(|ui| {
+                                    // Right-click the name for per-file actions
+                                    let info = &self.input_file_info[path];
+                                    let mut name_text = RichText::new(self.get_relative_path_display(path));
+                                    let outside_base_folder = !self.flattens_output() && self.is_outside_base_folder(path);
+                                    if outside_base_folder {
+                                        name_text = name_text.color(Color32::from_rgb(200, 160, 90)).italics();
+                                    }
+                                    let name_label = ui.add(egui::Label::new(name_text).sense(egui::Sense::click()));
+                                    let name_label = if outside_base_folder {
+                                        name_label.on_hover_text(format!(
+                                            "{}\nOutside the base folder, so it's written directly into the output folder",
+                                            path.to_string_lossy()
+                                        ))
+                                    } else {
+                                        name_label
+                                    };
+                                    name_label
+                                        .context_menu(|ui| {
+                                            ui.label(
+                                                RichText::new(format!(
+                                                    "Detected format: {} ({})",
+                                                    info.format,
+                                                    info.size.map(format_bytes).unwrap_or_else(|| "?".to_string())
+                                                ))
+                                                .color(Color32::from_rgb(150, 150, 150)),
+                                            );
+                                            ui.separator();
+                                            if ui.add_enabled(can_convert, egui::Button::new("Convert Only This File")).clicked() {
+                                                file_to_convert = Some(path.clone());
+                                                ui.close_menu();
+                                            }
+                                            if ui.button("Reveal in Explorer").clicked() {
+                                                Self::reveal_file_in_explorer(path);
+                                                ui.close_menu();
+                                            }
+                                            if ui.button("Copy Path").clicked() {
+                                                ui.ctx().copy_text(path.to_string_lossy().to_string());
+                                                ui.close_menu();
+                                            }
+                                            if ui.button("Remove").clicked() {
+                                                files_to_remove.push(index);
+                                                ui.close_menu();
+                                            }
+                                        });
+                                    if self.file_needs_skeleton(path) {
+                                        let skeleton = skeleton_for_input(path, &self.skeleton_rules, self.skeleton_file.as_deref());
+                                        ui.label(RichText::new("🦴").color(Color32::from_rgb(200, 170, 100))).on_hover_text(
+                                            match skeleton {
+                                                Some(skeleton) => format!(
+                                                    "Converted to or from KF using {}",
+                                                    skeleton.file_name().unwrap_or_default().to_string_lossy()
+                                                ),
+                                                None => "Converted to or from KF using the skeleton file".to_string(),
+                                            },
+                                        );
+                                    }
+                                })
+                                .response;
+
+                            let info = &self.input_file_info[path];
+                            let size = ui.label(
+                                RichText::new(info.size.map(format_bytes).unwrap_or_else(|| "?".to_string()))
+                                    .color(Color32::from_rgb(150, 150, 150)),
+                            );
+                            let format = ui.label(RichText::new(&info.format).color(Color32::from_rgb(150, 150, 150)));
+
+                            // Per-file output format, for converting a few files of the batch differently
+                            let current_override = self.format_overrides.get(path).filter(|format| available_formats.contains(format));
+                            let output_format = if available_formats.len() > 1 {
+                                egui::ComboBox::from_id_source(("format_override", index))
+                                    .selected_text(current_override.map_or(default_label.as_str(), |format| format.label()))
+                                    .show_ui(ui, |ui| {
+                                        if ui.selectable_label(current_override.is_none(), default_label.as_str()).clicked() {
+                                            format_override_change = Some((path.clone(), None));
+                                        }
+                                        for format in &available_formats {
+                                            if ui.selectable_label(current_override == Some(format), format.label()).clicked() {
+                                                format_override_change = Some((path.clone(), Some(*format)));
+                                            }
+                                        }
+                                    })
+                                    .response
+                                    .on_hover_text("Output format for this file only")
+                            } else {
+                                ui.label("")
+                            };
+                            for response in [&checkbox, &remove, &name, &size, &format, &output_format] {
+                                row_rect = row_rect.union(response.rect);
+                            }
+                            ui.end_row();
+
+                            // Highlight the drop position while dragging over this row
+                            let row_response = ui.interact(
+                                row_rect,
+                                egui::Id::new(("input_file_row", index)),
+                                egui::Sense::hover(),
+                            );
+                            if let Some(dragged_index) = row_response.dnd_hover_payload::<usize>() {
+                                if *dragged_index != index {
+                                    let y = if *dragged_index < index {
+                                        row_rect.bottom()
+                                    } else {
+                                        row_rect.top()
+                                    };
+                                    ui.painter().hline(
+                                        row_rect.x_range(),
+                                        y,
+                                        egui::Stroke::new(2.0, Color32::from_rgb(0, 150, 255)),
+                                    );
+                                }
+                            }
+                            if let Some(dragged_index) = row_response.dnd_release_payload::<usize>() {
+                                file_to_move = Some((*dragged_index, index));
+                            }
+                        }
+                    });
+                
+                match format_override_change {
+                    Some((path, Some(format))) => {
+                        self.format_overrides.insert(path, format);
+                    }
+                    Some((path, None)) => {
+                        self.format_overrides.remove(&path);
+                    }
+                    None => {}
+                }
+
+                // Apply selection changes after iteration
+                for (path, selected) in selection_changes {
+                    if selected {
+                        self.selected_input_paths.insert(path);
+                    } else {
+                        self.selected_input_paths.remove(&path);
+                    }
+                }
+
+                // Remove files after iteration
+                if !files_to_remove.is_empty() {
+                    self.push_input_undo(Vec::new());
+                }
+                for index in files_to_remove.iter().rev() {
+                    let removed = self.input_paths.remove(*index);
+                    self.selected_input_paths.remove(&removed);
+                    self.format_overrides.remove(&removed);
+                }
 
-        let output_name = if self.output_suffix.is_empty() {
-            format!("{}.{}", file_name, extension)
-        } else {
-            format!("{}_{}.{}", file_name, self.output_suffix, extension)
-        };
+                // Apply a drag-and-drop reorder
+                if let Some((from, to)) = file_to_move {
+                    if from != to && from < self.input_paths.len() && to < self.input_paths.len() {
+                        let path = self.input_paths.remove(from);
+                        self.input_paths.insert(to, path);
+                        // The list no longer follows the sort column
+                        self.input_sort = None;
+                    }
+                }
 
-        Some(output_base.join(relative_path).join(output_name))
+                if let Some(path) = file_to_convert {
+                    self.convert_single_file(path);
+                }
+            });
     }
 
-    fn find_common_parent_dir(&self) -> Option<&Path> {
-        if self.input_paths.is_empty() {
-            return None;
+    /// Sort the input list by `column`, reversing the order when it's already sorted by it
+    fn sort_input_paths(&mut self, column: FileSortColumn) {
+        let ascending = !matches!(self.input_sort, Some((sorted, true)) if sorted == column);
+        for path in &self.input_paths {
+            if !self.input_file_info.contains_key(path) {
+                self.input_file_info.insert(path.clone(), InputFileInfo::read(path));
+            }
         }
 
-        // get all parent directories
-        let parent_dirs: Vec<_> = self
-            .input_paths
+        let info = &self.input_file_info;
+        // Archive files are named within their archive, the rest within the base folder
+        let base_folders: Vec<PathBuf> = self
+            .archive_extractions
             .iter()
-            .filter_map(|path| path.parent())
+            .map(|extraction| extraction.dir.path().to_path_buf())
+            .chain(self.base_folder.clone())
             .collect();
-
-        if parent_dirs.is_empty() {
-            return None;
+        let display_name = |path: &PathBuf| match base_folders.iter().find_map(|base| path.strip_prefix(base).ok()) {
+            Some(relative) => relative.to_string_lossy().to_lowercase(),
+            None => path.file_name().unwrap_or_default().to_string_lossy().to_lowercase(),
+        };
+        match column {
+            FileSortColumn::Name => self.input_paths.sort_by_cached_key(display_name),
+            FileSortColumn::Size => self.input_paths.sort_by_key(|path| info[path].size),
+            FileSortColumn::Format => self.input_paths.sort_by(|a, b| info[a].format.cmp(&info[b].format)),
         }
-
-        // start with the first parent directory
-        let mut common = parent_dirs[0];
-
-        // find the common prefix among all parent directories
-        for dir in &parent_dirs[1..] {
-            while !dir.starts_with(common) {
-                common = common.parent()?;
-            }
+        if !ascending {
+            self.input_paths.reverse();
         }
-
-        Some(common)
+        self.input_sort = Some((column, ascending));
     }
 
-    fn start_conversion(&mut self) {
-        // Validation
-        if self.input_paths.is_empty() {
-            self.conversion_status = ConversionStatus::Error {
-                message: "No input files selected".to_string(),
-            };
-            return;
-        }
-        if self.output_folder.is_none() {
-            self.conversion_status = ConversionStatus::Error {
-                message: "No output folder selected".to_string(),
-            };
-            return;
-        }
-        if self.output_format.requires_skeleton() && self.skeleton_file.is_none() {
-            self.conversion_status = ConversionStatus::Error {
-                message: "Skeleton file is required for KF conversion".to_string(),
-            };
+    /// Show a dismissable notice when a newer release is available
+    fn render_update_banner(&mut self, ui: &mut Ui) {
+        let Some(update) = &self.available_update else {
             return;
-        }
-
-        // Setup channels for progress communication
-        let (progress_tx, progress_rx) = mpsc::unbounded_channel();
-        let (cancel_tx, cancel_rx) = oneshot::channel();
-        
-        self.progress_rx = Some(progress_rx);
-        self.cancel_tx = Some(cancel_tx);
-        self.conversion_status = ConversionStatus::Running {
-            current_file: "Starting...".to_string(),
-            progress: 0,
-            total: self.input_paths.len(),
         };
 
-        // Clone data needed for the async task
-        let input_paths = self.input_paths.clone();
-        let output_folder = self.output_folder.clone().unwrap();
-        let skeleton_file = self.skeleton_file.clone();
-        let output_suffix = self.output_suffix.clone();
-        let output_format = self.output_format;
-        let custom_extension = self.custom_extension.clone();
-        let converter_tool = self.converter_tool;
-        let hkxcmd_path = self.hkxcmd_path.clone();
-        let hkxc_path = self.hkxc_path.clone();
-        let hkxconv_path = self.hkxconv_path.clone();
-        let sse_to_le_hko_path = self.sse_to_le_hko_path.clone();
-        let havok_behavior_post_process_path = self.havok_behavior_post_process_path.clone();
-        let hct_standalone_filter_manager_path = self.hct_standalone_filter_manager_path.clone();
-        let hct_filter_manager_dll_path = self.hct_filter_manager_dll_path.clone();
-        let base_folder = self.base_folder.clone();
+        let mut dismissed = false;
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(format!("A newer version ({}) is available.", update.version))
+                    .color(Color32::from_rgb(150, 200, 255)),
+            );
+            ui.hyperlink_to("Open release page", &update.url);
+            dismissed = ui.small_button("Dismiss").clicked();
+        });
 
-        // Spawn the async conversion task
-        self.tokio_handle.spawn(async move {
-            let result = Self::run_conversion_async(
-                input_paths,
-                output_folder,
-                skeleton_file,
-                output_suffix,
-                output_format,
-                custom_extension,
-                converter_tool,
-                hkxcmd_path,
-                hkxc_path,
-                hkxconv_path,
-                sse_to_le_hko_path,
-                havok_behavior_post_process_path,
-                hct_standalone_filter_manager_path,
-                hct_filter_manager_dll_path,
-                base_folder,
-                progress_tx,
-                cancel_rx,
-            ).await;
+        if dismissed {
+            self.available_update = None;
+        }
+    }
 
-            // The task will complete on its own
-            drop(result);
+    fn render_update_settings(&mut self, ui: &mut Ui) {
+        ui.vertical(|ui| {
+            if ui
+                .checkbox(&mut self.settings.check_for_updates, "Check GitHub for a newer release on startup")
+                .on_hover_text("Off by default. This is the only time the app uses the network.")
+                .changed()
+            {
+                if let Err(e) = self.settings.save() {
+                    eprintln!("Failed to save settings: {}", e);
+                }
+                if self.settings.check_for_updates && self.update_rx.is_none() {
+                    self.start_update_check();
+                }
+            }
+
+            if ui
+                .button(format!("About (version {})", env!("CARGO_PKG_VERSION")))
+                .on_hover_text("App version and the size and SHA-256 of each conversion tool, for bug reports")
+                .clicked()
+            {
+                self.show_about = true;
+            }
         });
     }
 
-    async fn run_conversion_async(
-        input_paths: Vec<PathBuf>,
-        output_folder: PathBuf,
-        skeleton_file: Option<PathBuf>,
-        output_suffix: String,
-        output_format: OutputFormat,
-        custom_extension: Option<String>,
-        converter_tool: ConverterTool,
-        hkxcmd_path: PathBuf,
-        hkxc_path: PathBuf,
-        hkxconv_path: PathBuf,
-        sse_to_le_hko_path: PathBuf,
-        havok_behavior_post_process_path: PathBuf,
-        hct_standalone_filter_manager_path: PathBuf,
-        hct_filter_manager_dll_path: PathBuf,
-        base_folder: Option<PathBuf>,
-        progress_tx: mpsc::UnboundedSender<ConversionProgress>,
-        mut cancel_rx: oneshot::Receiver<()>,
-    ) -> Result<()> {
-        let total_files = input_paths.len();
-        
-        // HCT can now process asynchronously with isolated temp directories
-        println!("Processing {} files with {}", total_files, match converter_tool {
-            ConverterTool::Hct => "HCT (using isolated temp directories)",
-            ConverterTool::HavokBehaviorPostProcess => "HavokBehaviorPostProcess",
-            _ => "concurrent processing"
-        });
-        let mut conversion_tasks = Vec::new();
-        
-        for (index, input_path) in input_paths.iter().enumerate() {
-            // Check for cancellation before starting
-            if cancel_rx.try_recv().is_ok() {
-                let _ = progress_tx.send(ConversionProgress {
-                    current_file: "Cancelled".to_string(),
-                    file_index: index,
-                    total_files,
-                    status: ConversionStatus::Error {
-                        message: "Conversion cancelled by user".to_string(),
-                    },
-                });
-                return Ok(());
-            }
-
-            let output_path = Self::get_output_path_static(
-                input_path,
-                &output_folder,
-                &output_suffix,
-                output_format,
-                &custom_extension,
-                base_folder.as_deref(), // Pass the base folder for proper path calculation
-            ).context("Failed to determine output path")?;
-
-            if let Some(parent) = output_path.parent() {
-                fs::create_dir_all(parent).context("Failed to create output directories")?;
-            }
-
-            println!("Preparing to convert {:?} to {:?}", input_path, output_path);
-
-            // Create a temporary app-like structure for the conversion tool call
-            let temp_app = TempConversionContext {
-                converter_tool,
-                output_format,
-                skeleton_file: skeleton_file.clone(),
-                hkxcmd_path: hkxcmd_path.clone(),
-                hkxc_path: hkxc_path.clone(),
-                hkxconv_path: hkxconv_path.clone(),
-                sse_to_le_hko_path: sse_to_le_hko_path.clone(),
-                havok_behavior_post_process_path: havok_behavior_post_process_path.clone(),
-                hct_standalone_filter_manager_path: hct_standalone_filter_manager_path.clone(),
-                hct_filter_manager_dll_path: hct_filter_manager_dll_path.clone(),
-            };
+    /// Folder the conversion tools run from: the temp extraction folder, or `tools` in portable mode
+    fn tools_dir(&self) -> &Path {
+        self.tool_paths.hkxcmd.parent().unwrap_or(Path::new(""))
+    }
 
-            // Clone needed data for the async task
-            let input_path_clone = input_path.clone();
-            let output_path_clone = output_path.clone();
-            let progress_tx_clone = progress_tx.clone();
-            let file_name = input_path.file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-
-            // Create individual conversion task
-            let conversion_task = tokio::spawn(async move {
-                // Send progress update when starting this file
-                let _ = progress_tx_clone.send(ConversionProgress {
-                    current_file: file_name.clone(),
-                    file_index: index,
-                    total_files,
-                    status: ConversionStatus::Running {
-                        current_file: file_name.clone(),
-                        progress: index,
-                        total: total_files,
-                    },
-                });
+    /// Hash the bundled tools and compare them with the copies in use, the first time they're needed
+    fn ensure_tool_hashes(&mut self) {
+        if self.bundled_tools.is_some() {
+            return;
+        }
+        let tools = bundled_tools();
+        let tools_dir = self.tools_dir().to_path_buf();
+        self.modified_tools = tools
+            .iter()
+            .filter(|tool| !file_sha256(&tools_dir.join(tool.file_name)).is_ok_and(|hash| hash == tool.sha256))
+            .map(|tool| tool.file_name)
+            .collect();
+        self.bundled_tools = Some(tools);
+    }
 
-                println!("Starting conversion of {:?}", input_path_clone);
+    /// Plain-text version and tool list to paste into a bug report
+    fn about_report(&self) -> String {
+        let mut lines = vec![
+            format!("Composite HKX Conversion Tool {}", env!("CARGO_PKG_VERSION")),
+            format!("Tools folder: {}", self.tools_dir().to_string_lossy()),
+        ];
+        for tool in self.bundled_tools.iter().flatten() {
+            let modified = if self.modified_tools.contains(&tool.file_name) { " (in use copy differs)" } else { "" };
+            lines.push(format!("{}  {} bytes  SHA-256 {}{}", tool.file_name, tool.size, tool.sha256, modified));
+        }
+        for (tool, result) in &self.tool_checks {
+            match result {
+                Ok(output) => lines.push(format!("Test {}: OK\n{}", tool.label(), output)),
+                Err(error) => lines.push(format!("Test {}: FAILED\n{}", tool.label(), error)),
+            }
+        }
+        lines.join("\n")
+    }
 
-                // Run the actual conversion
-                let result = temp_app.run_conversion_tool(&input_path_clone, &output_path_clone).await;
+    fn render_about_window(&mut self, ctx: &EguiContext) {
+        if !self.show_about {
+            return;
+        }
+        self.ensure_tool_hashes();
+        self.poll_tool_check();
+        if self.tool_check_rx.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
 
-                match result {
-                    Ok(()) => {
-                        if !output_path_clone.exists() {
-                            let error_msg = format!("Output file was not created: {:?}", output_path_clone);
-                            eprintln!("ERROR: {}", error_msg);
-                            let _ = progress_tx_clone.send(ConversionProgress {
-                                current_file: file_name.clone(),
-                                file_index: index,
-                                total_files,
-                                status: ConversionStatus::Error {
-                                    message: format!("Failed to convert {}", file_name),
-                                },
-                            });
-                            return Err(anyhow::anyhow!(error_msg));
+        let mut open = true;
+        let mut copy = false;
+        let mut test_tools = false;
+        egui::Window::new("About")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(format!("Composite HKX Conversion Tool {}", env!("CARGO_PKG_VERSION"))).strong());
+                ui.label(
+                    RichText::new(format!("Tools folder: {}", self.tools_dir().to_string_lossy()))
+                        .color(Color32::from_rgb(150, 150, 150))
+                        .size(12.0),
+                );
+                ui.add_space(5.0);
+                egui::Grid::new("about_tools").num_columns(4).spacing([10.0, 4.0]).show(ui, |ui| {
+                    for tool in self.bundled_tools.iter().flatten() {
+                        ui.label(RichText::new(tool.file_name).monospace().size(11.0));
+                        ui.label(RichText::new(format_bytes(tool.size)).size(11.0));
+                        ui.label(RichText::new(&tool.sha256).monospace().size(11.0));
+                        if self.modified_tools.contains(&tool.file_name) {
+                            ui.label(RichText::new("⚠ in use copy differs").color(Color32::from_rgb(255, 180, 80)).size(11.0))
+                                .on_hover_text("The file in the tools folder isn't the one bundled with this build");
+                        } else {
+                            ui.label("");
                         }
-
-                        println!("Completed conversion of {:?}", input_path_clone);
-                        let metadata = fs::metadata(&output_path_clone)?;
-                        println!("Output file size: {} bytes", metadata.len());
-                        Ok(())
+                        ui.end_row();
                     }
-                    Err(e) => {
-                        eprintln!("ERROR converting {}: {}", file_name, e);
-                        let _ = progress_tx_clone.send(ConversionProgress {
-                            current_file: file_name.clone(),
-                            file_index: index,
-                            total_files,
-                            status: ConversionStatus::Error {
-                                message: format!("Failed to convert {}", file_name),
-                            },
-                        });
-                        Err(e)
+                });
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    let testing = self.tool_check_rx.is_some();
+                    test_tools = ui
+                        .add_enabled(!testing, egui::Button::new("Test Tools"))
+                        .on_hover_text("Run each tool with --help to check it starts on this system")
+                        .clicked();
+                    if testing {
+                        ui.spinner();
                     }
+                });
+                for (tool, result) in &self.tool_checks {
+                    let (status, color, output) = match result {
+                        Ok(output) => ("✔ OK", Color32::from_rgb(100, 200, 100), output),
+                        Err(error) => ("❌ Failed", Color32::from_rgb(255, 120, 120), error),
+                    };
+                    egui::CollapsingHeader::new(RichText::new(format!("{} {}", status, tool.label())).color(color).size(12.0))
+                        .id_source(("tool_check", tool.label()))
+                        .default_open(result.is_err())
+                        .show(ui, |ui| {
+                            ui.label(RichText::new(output).monospace().size(11.0));
+                        });
                 }
+                ui.add_space(5.0);
+                copy = ui.button("📋 Copy for Bug Report").clicked();
             });
 
-            conversion_tasks.push(conversion_task);
+        if test_tools {
+            self.start_tool_check();
         }
-
-        // Wait for all conversions to complete concurrently
-        let results = join_all(conversion_tasks).await;
-        
-        // Check results and count successes
-        let mut successful_conversions = 0;
-        let mut failed_conversions = 0;
-        for result in results {
-            // Check for cancellation
-            if cancel_rx.try_recv().is_ok() {
-                let _ = progress_tx.send(ConversionProgress {
-                    current_file: "Cancelled".to_string(),
-                    file_index: successful_conversions,
-                    total_files,
-                    status: ConversionStatus::Error {
-                        message: "Conversion cancelled".to_string(),
-                    },
-                });
-                return Ok(());
-            }
-
-            match result {
-                Ok(Ok(())) => {
-                    successful_conversions += 1;
-                }
-                Ok(Err(e)) => {
-                    eprintln!("ERROR: Conversion task failed: {}", e);
-                    failed_conversions += 1;
-                }
-                Err(e) => {
-                    eprintln!("ERROR: Task execution failed: {}", e);
-                    failed_conversions += 1;
-                }
-            }
+        if copy {
+            ctx.copy_text(self.about_report());
         }
+        self.show_about = open;
+    }
 
-        // Send completion message
-        if failed_conversions > 0 {
-            let _ = progress_tx.send(ConversionProgress {
-                current_file: "Completed".to_string(),
-                file_index: successful_conversions,
-                total_files,
-                status: ConversionStatus::Error {
-                    message: format!("Converted {} of {} files ({} failed)", successful_conversions, total_files, failed_conversions),
-                },
-            });
-        } else {
-            let _ = progress_tx.send(ConversionProgress {
-                current_file: "Completed".to_string(),
-                file_index: successful_conversions,
-                total_files,
-                status: ConversionStatus::Completed {
-                    message: format!("Successfully converted {} of {} files", successful_conversions, total_files),
-                },
-            });
+    /// Error message of the last batch with each failed file's full error and the tool output
+    fn error_report(&self) -> String {
+        let mut report = match &self.conversion_status {
+            ConversionStatus::Error { message } => message.clone(),
+            _ => String::new(),
+        };
+        for details in &self.error_details {
+            report.push_str("\n\n");
+            report.push_str(details);
         }
-
-        Ok(())
+        if !self.tool_log.is_empty() {
+            report.push_str("\n\nTool output:\n");
+            report.push_str(&self.tool_log.join("\n"));
+        }
+        report
     }
 
-    // Static helper method for output path calculation
-    fn get_output_path_static(
-        input_path: &Path,
-        output_folder: &Path,
-        output_suffix: &str,
-        output_format: OutputFormat,
-        custom_extension: &Option<String>,
-        base_folder: Option<&Path>,
-    ) -> Option<PathBuf> {
-        let file_name = input_path.file_stem()?.to_str()?;
-        
-        let extension = if let Some(custom_ext) = custom_extension {
-            custom_ext.as_str()
-        } else {
-            output_format.extension()
-        };
+    /// Absolute paths of the inputs that failed in the last batch, one per line
+    fn failed_input_list(&self) -> String {
+        let mut seen = HashSet::new();
+        let paths: Vec<String> = self
+            .failed_jobs
+            .iter()
+            .flat_map(|job| &job.input_paths)
+            .map(|path| ensure_absolute_path(path))
+            .filter(|path| seen.insert(path.clone()))
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        paths.join("\n")
+    }
 
-        // Calculate relative path from base folder to maintain folder structure
-        let relative_path = if let Some(base_folder) = base_folder {
-            // If we have a base folder, calculate relative path from it
-            if let Ok(relative) = input_path.parent().unwrap_or(Path::new("")).strip_prefix(base_folder) {
-                relative.to_path_buf()
-            } else {
-                // Fallback: use the parent directory relative to the input path
-                input_path.parent().unwrap_or(Path::new("")).to_path_buf()
-            }
-        } else {
-            // No base folder, just use the filename
-            PathBuf::new()
-        };
+    fn render_error_details(&mut self, ctx: &EguiContext) {
+        if !self.show_error_details {
+            return;
+        }
 
-        let output_name = if output_suffix.is_empty() {
-            format!("{}.{}", file_name, extension)
-        } else {
-            format!("{}_{}.{}", file_name, output_suffix, extension)
-        };
+        let mut report = self.error_report();
+        let mut open = true;
+        let mut copy = false;
+        egui::Window::new("Error Details")
+            .open(&mut open)
+            .collapsible(false)
+            .default_size([600.0, 400.0])
+            .show(ctx, |ui| {
+                copy = ui.button("📋 Copy to Clipboard").clicked();
+                ui.add_space(5.0);
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    // Read-only, but selectable for copying parts of it
+                    ui.add(
+                        egui::TextEdit::multiline(&mut report.as_str())
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            });
 
-        Some(output_folder.join(relative_path).join(output_name))
+        if copy {
+            ctx.copy_text(std::mem::take(&mut report));
+        }
+        // The details belong to the error being shown
+        self.show_error_details = open && matches!(self.conversion_status, ConversionStatus::Error { .. });
     }
 
-    /// Get relative path for display purposes
-    fn get_relative_path_display(&self, path: &Path) -> String {
-        if let Some(base_folder) = &self.base_folder {
-            if let Ok(relative) = path.strip_prefix(base_folder) {
-                relative.to_string_lossy().to_string()
-            } else {
-                path.file_name().unwrap_or_default().to_string_lossy().to_string()
+    /// Switch to a preset's tool, format, filter, suffix, extension and skeleton
+    fn apply_preset(&mut self, preset: &ConversionPreset) {
+        self.tool_output_formats.insert(self.converter_tool, self.output_format);
+        self.converter_tool = preset.converter_tool;
+        self.auto_tool = false;
+        self.output_format = preset.output_format;
+        self.multi_target = false;
+        self.input_file_extension = preset.input_file_extension;
+        self.output_suffix = preset.output_suffix.clone();
+        match &preset.custom_extension {
+            Some(extension) => {
+                self.custom_extensions.insert(preset.output_format, extension.clone());
+            }
+            None => {
+                self.custom_extensions.remove(&preset.output_format);
             }
-        } else {
-            path.file_name().unwrap_or_default().to_string_lossy().to_string()
         }
+        self.skeleton_file = preset.skeleton_file.clone();
+        self.selected_preset = Some(preset.name.clone());
     }
 
-    fn render_main_ui(&mut self, ui: &mut egui::Ui) {
-        ui.vertical_centered(|ui| {
-            ui.add_space(10.0);
-            ui.heading(
-                RichText::new("Composite HKX Conversion Tool")
-                    .size(24.0)
-                    .color(Color32::LIGHT_BLUE),
-            );
-            ui.add_space(10.0);
-        });
-
-        ui.separator();
+    /// Save the current setup under `name`, replacing a preset with the same name.
+    ///
+    /// Returns false without saving if the name is empty or the custom extension is invalid, which
+    /// the notice explains.
+    fn save_current_as_preset(&mut self, name: &str) -> bool {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return false;
+        }
+        let custom_extension = self.custom_extensions.get(&self.output_format).filter(|ext| !ext.is_empty()).cloned();
+        if let Some(Err(e)) = custom_extension.as_deref().map(validate_custom_extension) {
+            let message = format!("Preset \"{}\" not saved: {}", name, e);
+            eprintln!("{}", message);
+            self.drop_notice = Some((message, std::time::Instant::now()));
+            return false;
+        }
+        let preset = ConversionPreset {
+            name: name.clone(),
+            converter_tool: self.converter_tool,
+            output_format: self.output_format,
+            input_file_extension: self.input_file_extension,
+            output_suffix: self.output_suffix.clone(),
+            custom_extension,
+            skeleton_file: self.skeleton_file.clone(),
+        };
+        match self.settings.presets.iter_mut().find(|existing| existing.name == name) {
+            Some(existing) => *existing = preset,
+            None => self.settings.presets.push(preset),
+        }
+        self.selected_preset = Some(name);
+        if let Err(e) = self.settings.save() {
+            eprintln!("Failed to save settings: {}", e);
+        }
+        true
+    }
 
-        egui::Grid::new("main_grid")
-            .num_columns(2)
-            .spacing([10.0, 10.0])
-            .show(ui, |ui| {
-                ui.label("Converter Tool:");
-                ui.horizontal(|ui| {
-                    for tool in [ConverterTool::HkxCmd, ConverterTool::Hct, ConverterTool::HavokBehaviorPostProcess, ConverterTool::HkxC, ConverterTool::HkxConv] {
-                        let response = ui
-                            .selectable_label(self.converter_tool == tool, tool.label());
-                        
-                        if response.clicked() {
-                            self.converter_tool = tool;
-                            // Reset input file extension if tool doesn't support current filter
-                            if !tool.available_input_extensions().contains(&self.input_file_extension) {
-                                self.input_file_extension = InputFileExtension::Hkx;
-                            }
-                            // Reset output format if tool doesn't support current format
-                            let available_formats = self.available_output_formats();
-                            if !available_formats.contains(&self.output_format) {
-                                if !available_formats.is_empty() {
-                                    self.output_format = available_formats[0];
-                                }
-                            }
-                        }
-                        
-                        // Show tooltip on hover
-                        if response.hovered() {
-                            if let Some(hover_pos) = response.hover_pos() {
-                                self.show_tool_tooltip(ui, tool, hover_pos);
-                            }
+    fn render_presets(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let mut to_apply = None;
+            egui::ComboBox::from_id_source("preset_picker")
+                .selected_text(self.selected_preset.as_deref().unwrap_or("Choose a preset"))
+                .show_ui(ui, |ui| {
+                    if self.settings.presets.is_empty() {
+                        ui.label("No presets saved yet");
+                    }
+                    for preset in &self.settings.presets {
+                        let selected = self.selected_preset.as_deref() == Some(preset.name.as_str());
+                        if ui.selectable_label(selected, &preset.name).clicked() {
+                            to_apply = Some(preset.clone());
                         }
                     }
                 });
-                ui.end_row();
+            if let Some(preset) = to_apply {
+                self.apply_preset(&preset);
+            }
 
-                ui.label("Input File Filter:");
-                ui.horizontal(|ui| {
-                    let available_filters = self.converter_tool.available_input_extensions();
-                    
-                    for filter in available_filters {
-                        if ui
-                            .selectable_label(self.input_file_extension == filter, filter.label_for_tool(self.converter_tool))
-                            .clicked()
-                        {
-                            self.input_file_extension = filter;
+            match &mut self.new_preset_name {
+                Some(name) => {
+                    let response = ui.add(egui::TextEdit::singleline(name).hint_text("Preset name").desired_width(150.0));
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Save").clicked() || submitted {
+                        let name = name.clone();
+                        // Keep the name field open when the preset wasn't saved, so it can be fixed
+                        if self.save_current_as_preset(&name) {
+                            self.new_preset_name = None;
                         }
+                    } else if ui.button("Cancel").clicked() {
+                        self.new_preset_name = None;
                     }
-                    
-                    // Reset to a valid filter if current selection is not available
-                    if (self.converter_tool == ConverterTool::HkxC || self.converter_tool == ConverterTool::HkxConv) && self.input_file_extension == InputFileExtension::Kf {
-                        self.input_file_extension = InputFileExtension::Hkx;
+                }
+                None => {
+                    if ui.button("Save Current as Preset").on_hover_text("Save the tool, format, filter, suffix, extension and skeleton").clicked() {
+                        self.new_preset_name = Some(self.selected_preset.clone().unwrap_or_default());
                     }
-                });
-                ui.end_row();
-
-                ui.label("Input Files:");
-                ui.vertical(|ui| {
-                    ui.horizontal(|ui| {
-                        if ui.button("Browse Files").clicked() {
-                            if let Some(paths) = FileDialog::new().pick_files() {
-                                self.input_paths = paths;
-                                // Clear base folder for individual file selection
-                                self.base_folder = None;
-                                self.update_output_folder();
-                            }
-                        }
-                        if ui.button("Select Folder").clicked() {
-                            if let Some(folder) = FileDialog::new().pick_folder() {
-                                if let Err(e) = self.add_files_from_folder(&folder, false) {
-                                    eprintln!("Error adding files from folder: {}", e);
-                                }
-                                self.update_output_folder();
-                            }
-                        }
-                        if ui.button("Select Folder (+ Subfolders)").clicked() {
-                            if let Some(folder) = FileDialog::new().pick_folder() {
-                                if let Err(e) = self.add_files_from_folder(&folder, true) {
-                                    eprintln!("Error adding files from folders: {}", e);
-                                }
-                                self.update_output_folder();
+                    if let Some(name) = self.selected_preset.clone() {
+                        if ui.button("Delete").on_hover_text(format!("Delete the preset \"{}\"", name)).clicked() {
+                            self.settings.presets.retain(|preset| preset.name != name);
+                            self.selected_preset = None;
+                            if let Err(e) = self.settings.save() {
+                                eprintln!("Failed to save settings: {}", e);
                             }
                         }
-                    });
-                });
-                ui.end_row();
+                    }
+                }
+            }
+        });
+    }
 
-                // Skeleton file selection (only show for KF conversion)
-                if self.output_format.requires_skeleton() {
-                    ui.label("Skeleton File:");
-                    ui.horizontal(|ui| {
-                        if let Some(ref skeleton_file) = self.skeleton_file {
-                            ui.label(skeleton_file.file_name().unwrap_or_default().to_string_lossy());
-                        } 
-                        // else {
-                        //     ui.label("(required for animation conversion)");
-                        // }
-                        if ui.button("Browse").clicked() {
-                            if let Some(file) = FileDialog::new()
-                                .add_filter("HKX files", &["hkx"])
-                                .pick_file()
-                            {
-                                self.skeleton_file = Some(file);
-                            }
-                        }
-                        if self.skeleton_file.is_some() && ui.button("Clear").clicked() {
-                            self.skeleton_file = None;
-                        }
-                    });
-                    ui.end_row();
+    /// Actions that make sense right now, in the order the palette lists them
+    fn palette_actions(&self) -> Vec<PaletteAction> {
+        let mut actions = vec![
+            PaletteAction::BrowseFiles,
+            PaletteAction::ImportList,
+            PaletteAction::SelectFolder,
+            PaletteAction::SelectFolderRecursive,
+        ];
+        if !self.input_paths.is_empty() {
+            actions.push(PaletteAction::ClearAll);
+        }
+        if !self.input_undo.is_empty() {
+            actions.push(PaletteAction::UndoListChange);
+        }
+        actions.extend(ALL_TOOLS.into_iter().filter(|&tool| self.auto_tool || tool != self.converter_tool).map(PaletteAction::SwitchTool));
+        if !self.is_converting() {
+            if self.configuration_problem(&self.input_paths).is_none() {
+                actions.push(PaletteAction::RunConversion);
+                actions.push(PaletteAction::AddToQueue);
+                actions.push(PaletteAction::ExportCommands);
+            }
+            if !self.job_queue.is_empty() {
+                actions.push(PaletteAction::RunQueue);
+            }
+            if !self.failed_jobs.is_empty() {
+                actions.push(PaletteAction::RetryFailed);
+            }
+        }
+        if self.output_folder.is_some() {
+            actions.push(PaletteAction::OpenOutputFolder);
+        }
+        actions.push(PaletteAction::About);
+        actions
+    }
+
+    fn run_palette_action(&mut self, action: PaletteAction) {
+        match action {
+            PaletteAction::BrowseFiles => self.browse_input_files(),
+            PaletteAction::ImportList => self.import_file_list(),
+            PaletteAction::SelectFolder => self.select_input_folder(false, self.settings.folder_replaces_list),
+            PaletteAction::SelectFolderRecursive => self.select_input_folder(true, self.settings.folder_replaces_list),
+            PaletteAction::ClearAll => self.clear_input_files(),
+            PaletteAction::UndoListChange => self.undo_input_change(),
+            PaletteAction::SwitchTool(tool) => self.select_converter_tool(tool),
+            PaletteAction::RunConversion => self.run_conversion(),
+            PaletteAction::AddToQueue => self.add_current_job_to_queue(),
+            PaletteAction::ExportCommands => self.export_commands(),
+            PaletteAction::RunQueue => {
+                self.reset_conversion_state();
+                self.run_job_queue();
+            }
+            PaletteAction::RetryFailed => {
+                self.reset_conversion_state();
+                self.retry_failed_conversions();
+            }
+            PaletteAction::OpenOutputFolder => {
+                if let Some(output_folder) = &self.output_folder {
+                    Self::open_folder_in_explorer(output_folder);
                 }
+            }
+            PaletteAction::About => self.show_about = true,
+        }
+    }
+
+    /// Ctrl+P list of actions, filtered by every word typed
+    fn render_command_palette(&mut self, ctx: &EguiContext) {
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::P)) {
+            self.command_palette = match self.command_palette {
+                Some(_) => None,
+                None => Some(CommandPalette::default()),
+            };
+        }
+        if self.command_palette.is_none() {
+            return;
+        }
 
-                ui.label("Output Folder:");
-                self.render_output_folder(ui);
-                ui.end_row();
+        let actions = self.palette_actions();
+        let Some(palette) = &mut self.command_palette else {
+            return;
+        };
+        let words: Vec<String> = palette.query.split_whitespace().map(str::to_lowercase).collect();
+        let matches: Vec<PaletteAction> = actions
+            .into_iter()
+            .filter(|action| {
+                let label = action.label().to_lowercase();
+                words.iter().all(|word| label.contains(word.as_str()))
+            })
+            .collect();
 
-                ui.label("Output Suffix:");
-                ui.text_edit_singleline(&mut self.output_suffix);
-                ui.end_row();
+        let (up, down, enter, escape) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::ArrowUp),
+                i.key_pressed(egui::Key::ArrowDown),
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        if down {
+            palette.selected += 1;
+        }
+        if up {
+            palette.selected = palette.selected.saturating_sub(1);
+        }
+        palette.selected = palette.selected.min(matches.len().saturating_sub(1));
 
-                ui.label("Custom Extension:");
-                ui.horizontal(|ui| {
-                    let mut extension_text = self.custom_extension.as_ref().cloned().unwrap_or_default();
-                    if ui.text_edit_singleline(&mut extension_text).changed() {
-                        self.custom_extension = if extension_text.is_empty() {
-                            None
-                        } else {
-                            Some(extension_text)
-                        };
+        let mut chosen = enter.then(|| matches.get(palette.selected).copied()).flatten();
+        let mut close = escape || chosen.is_some();
+        egui::Window::new("Command Palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .fixed_size(egui::vec2(360.0, 0.0))
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut palette.query)
+                        .hint_text("Type to filter actions")
+                        .desired_width(f32::INFINITY),
+                );
+                response.request_focus();
+                if response.changed() {
+                    palette.selected = 0;
+                }
+                ui.separator();
+                if matches.is_empty() {
+                    ui.label(RichText::new("No matching actions").color(Color32::GRAY));
+                }
+                for (index, action) in matches.iter().enumerate() {
+                    if ui.selectable_label(index == palette.selected, action.label()).clicked() {
+                        chosen = Some(*action);
+                        close = true;
                     }
-                    // ui.label("(optional - leave empty to use format default)");
-                });
-                ui.end_row();
-
-                ui.label("Output Format:");
-                self.render_output_format(ui);
-                ui.end_row();
+                }
             });
 
-        ui.add_space(10.0);
+        if close {
+            self.command_palette = None;
+        }
+        if let Some(action) = chosen {
+            self.run_palette_action(action);
+        }
+    }
+
+    /// One-line summary of the tool, formats, filter, file count and output folder
+    fn render_status_bar(&mut self, ui: &mut Ui) {
+        let formats: Vec<&str> = self.selected_output_formats().iter().map(|format| format.label()).collect();
+        let file_count = self.input_paths.len();
+        let summary = format!(
+            "Tool: {}  |  Format: {}  |  Filter: {}  |  {} file{}",
+            self.tool_label(),
+            formats.join(", "),
+            self.input_file_extension.label_for_tool(self.converter_tool),
+            file_count,
+            if file_count == 1 { "" } else { "s" },
+        );
 
-        // Selected Files section outside the grid for more space
         ui.horizontal(|ui| {
-            ui.label("Selected Files:");
-            ui.label(format!("{} files selected", self.input_paths.len()));
-            if ui.button("Clear All").clicked() {
-                self.input_paths.clear();
-                self.base_folder = None;
-                // Reset the manually set flag when clearing all files
-                self.output_folder_manually_set = false;
+            ui.label(RichText::new(summary).size(12.0));
+            ui.label(RichText::new("  |  Output:").size(12.0));
+            match &self.output_folder {
+                _ if self.output_next_to_inputs => {
+                    ui.label(RichText::new("next to inputs").size(12.0));
+                }
+                Some(output_folder) => {
+                    let full = output_folder.to_string_lossy();
+                    ui.label(RichText::new(truncate_path_display(&full, STATUS_BAR_PATH_CHARS)).size(12.0))
+                        .on_hover_text(full.to_string());
+                }
+                None => {
+                    ui.label(RichText::new("not set").size(12.0).color(Color32::from_rgb(255, 180, 80)));
+                }
             }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .selectable_label(self.settings.compact_layout, RichText::new("Compact").size(12.0))
+                    .on_hover_text("Smaller layout for small screens: dropdowns instead of button rows, less spacing and fewer hints")
+                    .clicked()
+                {
+                    self.settings.compact_layout = !self.settings.compact_layout;
+                    if let Err(e) = self.settings.save() {
+                        eprintln!("Failed to save settings: {}", e);
+                    }
+                }
+            });
         });
-        
-        // Show base folder information if set
-        if let Some(ref base_folder) = self.base_folder {
+    }
+
+    fn render_version_override(&mut self, ui: &mut Ui) {
+        let (flag, examples) = match self.converter_tool {
+            ConverterTool::HkxCmd => ("-v:", "e.g. WIN32, AMD64, XML"),
+            _ => ("--format", "e.g. win32, amd64, xml"),
+        };
+        ui.vertical(|ui| {
             ui.horizontal(|ui| {
-                ui.label(RichText::new("📁 Base folder:").color(Color32::from_rgb(100, 150, 200)).size(12.0));
-                ui.label(RichText::new(base_folder.to_string_lossy()).color(Color32::from_rgb(150, 150, 150)).size(12.0));
+                ui.add(egui::TextEdit::singleline(&mut self.version_override).hint_text(examples).desired_width(150.0))
+                    .on_hover_text(format!("Passed to {} {} as-is. Leave empty to use the output format.", self.converter_tool.label(), flag));
+                if !self.version_override.is_empty() && ui.button("Clear").clicked() {
+                    self.version_override.clear();
+                }
             });
-        }
-        
-        // Show drag and drop hint
-        ui.horizontal(|ui| {
-            ui.label(RichText::new("💡 Tip: You can drag and drop files or folders directly onto this window").color(Color32::from_rgb(100, 100, 100)).size(12.0));
+            if self.active_version_override().is_some() {
+                ui.label(
+                    RichText::new("⚠ Expert option: replaces the target for every selected output format. Unsupported values make the tool fail.")
+                        .color(Color32::from_rgb(255, 180, 80))
+                        .size(12.0),
+                );
+            }
         });
-        
-        // Show HCT processing note
-        // if self.converter_tool == ConverterTool::Hct {
-        //     ui.horizontal(|ui| {
-        //         ui.label(RichText::new("ℹ️ HCT files use isolated temp directories for safe concurrent processing").color(Color32::from_rgb(100, 100, 100)).size(12.0));
-        //     });
-        // }
-        
-        // Scrollable area for file list - takes remaining available space
-        egui::ScrollArea::vertical()
-            .auto_shrink([false; 2])
-            .show(ui, |ui| {
-                let mut files_to_remove = Vec::new();
-                for (index, path) in self.input_paths.iter().enumerate() {
-                    ui.horizontal(|ui| {
-                        if ui.small_button("❌").clicked() {
-                            files_to_remove.push(index);
-                        }
-                        ui.label(self.get_relative_path_display(path));
-                    });
+    }
+
+    fn render_timeout_setting(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let mut secs = self.settings.conversion_timeout_secs.unwrap_or(DEFAULT_CONVERSION_TIMEOUT_SECS);
+            let response = ui.add(egui::DragValue::new(&mut secs).range(0..=3600).suffix(" s"));
+            ui.label(if secs == 0 { "(no limit)" } else { "per file" });
+            // Save once editing finishes rather than on every drag step
+            if response.changed() {
+                self.settings.conversion_timeout_secs = Some(secs);
+            }
+            if response.lost_focus() || response.drag_stopped() {
+                if let Err(e) = self.settings.save() {
+                    eprintln!("Failed to save settings: {}", e);
                 }
-                
-                // Remove files after iteration
-                for index in files_to_remove.iter().rev() {
-                    self.input_paths.remove(*index);
+            }
+        })
+        .response
+        .on_hover_text("Stop a tool that hangs on a file after this long and move on to the rest of the batch. 0 disables the limit.");
+    }
+
+    fn render_max_files_setting(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let mut max_files = self.settings.max_files.unwrap_or(DEFAULT_MAX_FILES);
+            let response = ui.add(egui::DragValue::new(&mut max_files).range(0..=1_000_000).speed(10.0));
+            ui.label(if max_files == 0 { "(no limit)" } else { "per folder scan" });
+            // Save once editing finishes rather than on every drag step
+            if response.changed() {
+                self.settings.max_files = Some(max_files);
+            }
+            if response.lost_focus() || response.drag_stopped() {
+                if let Err(e) = self.settings.save() {
+                    eprintln!("Failed to save settings: {}", e);
                 }
+            }
+        })
+        .response
+        .on_hover_text("Ask before 'Select Folder (+ Subfolders)' adds more files than this. 0 disables the check.");
+    }
+
+    /// Output template field, with where the first listed file would go
+    fn render_output_template(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let placeholders: Vec<String> = OUTPUT_TEMPLATE_PLACEHOLDERS.iter().map(|name| format!("{{{}}}", name)).collect();
+            let response = ui
+                .add(egui::TextEdit::singleline(&mut self.settings.output_template).hint_text(DEFAULT_OUTPUT_TEMPLATE))
+                .on_hover_text(format!(
+                    "Folders and file name of each output, relative to the output folder. Leave empty for the usual layout.\nPlaceholders: {}",
+                    placeholders.join(" ")
+                ));
+            if response.lost_focus() {
+                if let Err(e) = self.settings.save() {
+                    eprintln!("Failed to save settings: {}", e);
+                }
+            }
+
+            let example = self.active_output_template().zip(self.input_paths.first()).and_then(|(template, input_path)| {
+                let layout = OutputLayout {
+                    output_suffix: &self.output_suffix,
+                    suffix_separator: self.settings.suffix_separator(),
+                    base_folder: self.base_folder_for(input_path),
+                    filename_case: self.settings.filename_case,
+                    ..OutputLayout::new(Path::new(""), &self.custom_extensions)
+                };
+                get_templated_output_path(input_path, &template, self.output_format, self.converter_tool, &layout)
             });
+            if let Some(example) = example {
+                ui.label(
+                    RichText::new(format!("e.g. {}", example.to_string_lossy()))
+                        .color(Color32::from_rgb(150, 150, 150))
+                        .size(12.0),
+                );
+            }
+        });
+    }
+
+    fn render_wine_setting(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let label = match &self.settings.wine {
+                Some(wine) => wine.to_string_lossy().to_string(),
+                None => "Not set".to_string(),
+            };
+            ui.label(label);
+
+            let mut changed = false;
+            if ui.button("Use wine").on_hover_text("Run the tools with the wine command on your PATH").clicked() {
+                self.settings.wine = Some(PathBuf::from("wine"));
+                changed = true;
+            }
+            if ui.button("Browse").clicked() {
+                if let Some(file) = FileDialog::new().pick_file() {
+                    self.settings.wine = Some(file);
+                    changed = true;
+                }
+            }
+            if self.settings.wine.is_some() && ui.button("Reset").clicked() {
+                self.settings.wine = None;
+                changed = true;
+            }
+            if changed {
+                if let Err(e) = self.settings.save() {
+                    eprintln!("Failed to save settings: {}", e);
+                }
+            }
+        })
+        .response
+        .on_hover_text("Program that runs the Windows conversion tools on this system, such as wine or a Proton/CrossOver wrapper");
+    }
+
+    fn render_temp_folder(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let label = match &self.settings.temp_dir {
+                Some(temp_dir) => temp_dir.to_string_lossy().to_string(),
+                None => "System default".to_string(),
+            };
+            ui.label(label);
+
+            let mut changed = false;
+            if ui.button("Browse").clicked() {
+                if let Some(folder) = FileDialog::new().pick_folder() {
+                    self.settings.temp_dir = Some(folder);
+                    changed = true;
+                }
+            }
+            if self.settings.temp_dir.is_some() && ui.button("Reset").clicked() {
+                self.settings.temp_dir = None;
+                changed = true;
+            }
+            if changed {
+                if let Err(e) = self.settings.save() {
+                    eprintln!("Failed to save settings: {}", e);
+                }
+            }
+        })
+        .response
+        .on_hover_text("Where HCT and verification temp files are created. Tool extraction uses this folder from the next launch.");
     }
 
     fn render_output_folder(&mut self, ui: &mut Ui) {
         ui.vertical(|ui| {
+            let folder_names: Vec<&str> = self.selected_output_formats().iter().map(|format| format.folder_name()).collect();
+            ui.checkbox(&mut self.output_next_to_inputs, "Next to inputs")
+                .on_hover_text(format!(
+                    "Write each file's outputs into a {} folder beside it, instead of the output folder",
+                    folder_names.join("/")
+                ));
+            if self.output_next_to_inputs {
+                return;
+            }
+
             if let Some(ref output_folder) = self.output_folder {
                 ui.label(output_folder.to_string_lossy());
                 // Show indicator if manually set
@@ -1755,42 +6055,457 @@ impl HkxToolsApp {
         });
     }
 
-    fn render_output_format(&mut self, ui: &mut Ui) {
-        ui.horizontal(|ui| {
-            let available_formats = self.available_output_formats();
-            
-            for format in available_formats {
-                if ui
-                    .selectable_label(self.output_format == format, format.label())
-                    .clicked()
-                {
-                    self.output_format = format;
-                }
-            }
-            
-            // Reset to a valid format if current selection is not available
-            let available_formats = self.available_output_formats();
-            if !available_formats.contains(&self.output_format) {
-                if !available_formats.is_empty() {
-                    self.output_format = available_formats[0];
+    /// Converter tool buttons, or a dropdown in the compact layout
+    fn render_converter_tool_picker(&mut self, ui: &mut Ui, compact: bool) {
+        let (auto_tool, converter_tool) = (self.auto_tool, self.converter_tool);
+        let mut selected_tool = None;
+        let mut tool_option = |ui: &mut Ui, tool: ConverterTool| {
+            let response = ui.selectable_label(!auto_tool && converter_tool == tool, tool.label());
+            if response.clicked() {
+                selected_tool = Some(tool);
+            }
+            // Show tooltip on hover
+            response.on_hover_ui(|ui| Self::show_tool_tooltip(ui, tool));
+        };
+        let mut auto_selected = false;
+        let mut auto_option = |ui: &mut Ui| {
+            if ui.selectable_label(auto_tool, AUTO_TOOL_LABEL).on_hover_text(AUTO_TOOL_HELP).clicked() {
+                auto_selected = true;
+            }
+        };
+        if compact {
+            egui::ComboBox::from_id_source("converter_tool_picker")
+                .selected_text(self.tool_label())
+                .show_ui(ui, |ui| {
+                    auto_option(ui);
+                    ui.separator();
+                    for (index, (category, tools)) in TOOL_CATEGORIES.into_iter().enumerate() {
+                        if index > 0 {
+                            ui.separator();
+                        }
+                        ui.label(RichText::new(category).small().weak());
+                        for &tool in tools {
+                            tool_option(ui, tool);
+                        }
+                    }
+                });
+        } else {
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label(RichText::new("Per File").small().weak());
+                        auto_option(ui);
+                    });
+                });
+                for (category, tools) in TOOL_CATEGORIES {
+                    ui.group(|ui| {
+                        ui.vertical(|ui| {
+                            ui.label(RichText::new(category).small().weak());
+                            ui.horizontal(|ui| {
+                                for &tool in tools {
+                                    tool_option(ui, tool);
+                                }
+                            });
+                        });
+                    });
+                }
+            });
+        }
+        if let Some(tool) = selected_tool {
+            self.select_converter_tool(tool);
+        }
+        if auto_selected {
+            self.select_auto_tool();
+        }
+    }
+
+    /// Input filter buttons, or a dropdown in the compact layout
+    fn render_input_filter_picker(&mut self, ui: &mut Ui, compact: bool) {
+        let available_filters = self.converter_tool.available_input_extensions();
+        if compact {
+            egui::ComboBox::from_id_source("input_filter_picker")
+                .selected_text(self.input_file_extension.label_for_tool(self.converter_tool))
+                .show_ui(ui, |ui| {
+                    for filter in available_filters {
+                        ui.selectable_value(&mut self.input_file_extension, filter, filter.label_for_tool(self.converter_tool));
+                    }
+                });
+        } else {
+            ui.horizontal(|ui| {
+                for filter in available_filters {
+                    if ui
+                        .selectable_label(self.input_file_extension == filter, filter.label_for_tool(self.converter_tool))
+                        .clicked()
+                    {
+                        self.input_file_extension = filter;
+                    }
+                }
+            });
+        }
+
+        // Reset to a valid filter if current selection is not available
+        if (self.converter_tool == ConverterTool::HkxC || self.converter_tool == ConverterTool::HkxConv) && self.input_file_extension == InputFileExtension::Kf {
+            self.input_file_extension = InputFileExtension::Hkx;
+        }
+    }
+
+    fn render_output_format(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let available_formats = self.available_output_formats();
+            
+            if self.multi_target && available_formats.len() > 1 {
+                // Pick any number of formats; each gets its own output subfolder
+                for format in available_formats {
+                    let mut selected = self.multi_target_formats.contains(&format);
+                    if ui.checkbox(&mut selected, format.label()).changed() {
+                        if selected {
+                            self.multi_target_formats.push(format);
+                        } else {
+                            self.multi_target_formats.retain(|f| *f != format);
+                        }
+                    }
+                }
+            } else if self.settings.compact_layout {
+                egui::ComboBox::from_id_source("output_format_picker")
+                    .selected_text(self.output_format.label())
+                    .show_ui(ui, |ui| {
+                        for format in available_formats {
+                            ui.selectable_value(&mut self.output_format, format, format.label());
+                        }
+                    });
+            } else {
+                for format in available_formats {
+                    if ui
+                        .selectable_label(self.output_format == format, format.label())
+                        .clicked()
+                    {
+                        self.output_format = format;
+                    }
+                }
+            }
+
+            if self.available_output_formats().len() > 1 {
+                ui.separator();
+                if ui.checkbox(&mut self.multi_target, "Multiple").changed()
+                    && self.multi_target
+                    && self.multi_target_formats.is_empty()
+                {
+                    self.multi_target_formats.push(self.output_format);
+                }
+            }
+            
+            // Reset to a valid format if current selection is not available
+            let available_formats = self.available_output_formats();
+            if !available_formats.contains(&self.output_format) {
+                if !available_formats.is_empty() {
+                    self.output_format = available_formats[0];
+                }
+            }
+            
+            // Reset to a valid filter if current selection is not available
+            if !self.converter_tool.available_input_extensions().contains(&self.input_file_extension) {
+                self.input_file_extension = InputFileExtension::Hkx;
+            }
+        });
+    }
+
+    /// Remember the current window size and position (ignored while minimized or maximized)
+    fn track_window_geometry(&mut self, ctx: &EguiContext) {
+        let (inner_rect, outer_rect, pixels_per_point, minimized, maximized) = ctx.input(|i| {
+            let viewport = i.viewport();
+            (
+                viewport.inner_rect,
+                viewport.outer_rect,
+                viewport.native_pixels_per_point,
+                viewport.minimized.unwrap_or(false),
+                viewport.maximized.unwrap_or(false),
+            )
+        });
+
+        if minimized || maximized {
+            return;
+        }
+        if let Some(inner_rect) = inner_rect {
+            self.settings.window_size = Some(inner_rect.size());
+        }
+        if let Some(outer_rect) = outer_rect {
+            self.settings.window_position = Some(outer_rect.min);
+            self.settings.window_pixels_per_point = pixels_per_point;
+        }
+    }
+
+    /// Modal listing inputs that are empty or locked, offering to convert the rest
+    fn render_unreadable_inputs(&mut self, ctx: &EguiContext) {
+        let Some((_, unreadable)) = &self.pending_unreadable_inputs else {
+            return;
+        };
+
+        let mut choice = None;
+        egui::Window::new("Unreadable Input Files")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "⚠ {} input file{} can't be converted:",
+                        unreadable.len(),
+                        if unreadable.len() == 1 { "" } else { "s" }
+                    ))
+                    .color(Color32::from_rgb(255, 180, 80))
+                    .strong(),
+                );
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for (_, problem) in unreadable {
+                        ui.label(problem);
+                    }
+                });
+                ui.label("Locked files are usually held open by a mod manager or another tool. Close it and run again, or convert the other files now.");
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Skip These Files").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+
+        match choice {
+            Some(true) => {
+                if let Some((mut jobs, unreadable)) = self.pending_unreadable_inputs.take() {
+                    let skipped: HashSet<PathBuf> = unreadable.into_iter().map(|(input, _)| input).collect();
+                    for job in &mut jobs {
+                        job.input_paths.retain(|input| !skipped.contains(input));
+                    }
+                    jobs.retain(|job| !job.input_paths.is_empty());
+                    self.start_jobs(jobs, false, false);
+                }
+            }
+            Some(false) => self.pending_unreadable_inputs = None,
+            None => {}
+        }
+    }
+
+    /// Modal asking whether to continue a batch that would overwrite source files
+    fn render_overwrite_confirmation(&mut self, ctx: &EguiContext) {
+        let Some((_, overwritten)) = &self.pending_overwrite_confirmation else {
+            return;
+        };
+        let overwritten = *overwritten;
+
+        let mut proceed = false;
+        let mut cancel = false;
+        egui::Window::new("Overwrite Source Files?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "⚠ This conversion would overwrite {} source file{}.",
+                        overwritten,
+                        if overwritten == 1 { "" } else { "s" }
+                    ))
+                    .color(Color32::from_rgb(255, 180, 80))
+                    .strong(),
+                );
+                ui.label("The output folder, file names and extension match the input files. Set an output suffix or pick a different output folder to keep the originals.");
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Overwrite").clicked() {
+                        proceed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if proceed {
+            if let Some((jobs, _)) = self.pending_overwrite_confirmation.take() {
+                self.start_jobs(jobs, true, false);
+            }
+        } else if cancel {
+            self.pending_overwrite_confirmation = None;
+        }
+    }
+
+    fn render_large_folder_confirmation(&mut self, ctx: &EguiContext) {
+        let Some(scan) = self.folder_scan.as_ref().filter(|scan| !scan.confirmed && !scan.held.is_empty()) else {
+            return;
+        };
+        let found = scan.added.len() + scan.held.len();
+
+        let mut choice = None;
+        egui::Window::new("Add Many Files?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "⚠ Found {} matching files in {}{}",
+                        found,
+                        scan.folder.display(),
+                        if scan.finished { "" } else { " so far" }
+                    ))
+                    .color(Color32::from_rgb(255, 180, 80))
+                    .strong(),
+                );
+                ui.label(format!(
+                    "That's more than the limit of {} (Max Files). Add them all?",
+                    self.settings.max_files().unwrap_or_default()
+                ));
+                ui.label(RichText::new("Cancel removes the files this folder added.").color(Color32::from_rgb(150, 150, 150)));
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Add All").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+
+        match choice {
+            Some(true) => self.accept_held_scan_files(),
+            Some(false) => self.stop_folder_scan(false),
+            None => {}
+        }
+    }
+
+    fn render_delete_sources_confirmation(&mut self, ctx: &EguiContext) {
+        if !self.confirm_delete_sources {
+            return;
+        }
+
+        let mut choice = None;
+        egui::Window::new("Delete Source Files?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new("⚠ Source files will be permanently deleted after they are converted.")
+                        .color(Color32::from_rgb(255, 180, 80))
+                        .strong(),
+                );
+                ui.label("A file is only deleted once every output made from it exists and isn't empty. Files that failed, were cancelled or didn't pass round-trip verification are kept.");
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Delete Sources After Converting").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+
+        if let Some(enable) = choice {
+            self.delete_sources = enable;
+            self.confirm_delete_sources = false;
+        }
+    }
+
+    fn render_resume_confirmation(&mut self, ctx: &EguiContext) {
+        let Some((jobs, completed)) = &self.pending_resume_confirmation else {
+            return;
+        };
+        let total: usize = jobs.iter().map(ConversionJob::output_count).sum();
+        let completed_count = completed.len();
+
+        let mut choice = None;
+        egui::Window::new("Resume Previous Batch?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "{} of {} files were already converted by an earlier run of this batch that didn't finish.",
+                        completed_count, total
+                    ))
+                    .strong(),
+                );
+                ui.label("Skip them to convert only the rest, or convert everything again.");
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button(format!("Skip {} Completed", completed_count)).clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Convert All").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+
+        if let Some(skip_completed) = choice {
+            if let Some((mut jobs, completed)) = self.pending_resume_confirmation.take() {
+                if skip_completed {
+                    for job in &mut jobs {
+                        job.skip_outputs = completed.clone();
+                    }
                 }
+                self.start_jobs(jobs, true, true);
             }
-            
-            // Reset to a valid filter if current selection is not available
-            if !self.converter_tool.available_input_extensions().contains(&self.input_file_extension) {
-                self.input_file_extension = InputFileExtension::Hkx;
-            }
-        });
+        }
     }
 
     fn handle_conversion(&mut self, ui: &mut Ui) {
         // Check for progress updates
         if let Some(progress_rx) = &mut self.progress_rx {
             while let Ok(progress) = progress_rx.try_recv() {
-                self.conversion_status = progress.status;
+                let paused = self.pause_gate.as_ref().is_some_and(|gate| gate.is_paused());
+                let cancelling = matches!(self.conversion_status, ConversionStatus::Cancelling { .. });
+                self.conversion_status = match progress.status {
+                    // Keep showing the cancel until the batch has stopped, then show how it ended
+                    ConversionStatus::Running { progress, total, .. } if cancelling => ConversionStatus::Cancelling { progress, total },
+                    status if cancelling => {
+                        self.cancel_outcome = Some(status);
+                        self.conversion_status.clone()
+                    }
+                    // Files that were already converting keep reporting while the batch is paused
+                    ConversionStatus::Running { progress, total, .. } if paused => ConversionStatus::Paused { progress, total },
+                    status => status,
+                };
+                // Watch batches run unattended, so their results go to the log too
+                let watch_line = match &self.conversion_status {
+                    ConversionStatus::Completed { message, .. } | ConversionStatus::Error { message } if self.watch_folder.is_some() => {
+                        Some(format!("[watch] {}", message))
+                    }
+                    _ => None,
+                };
+                for line in progress.log_line.into_iter().chain(watch_line) {
+                    self.tool_log.push(line);
+                    // Keep the log bounded for very chatty tools
+                    if self.tool_log.len() > MAX_TOOL_LOG_LINES {
+                        let excess = self.tool_log.len() - MAX_TOOL_LOG_LINES;
+                        self.tool_log.drain(..excess);
+                    }
+                }
+                if !progress.failed_jobs.is_empty() {
+                    self.failed_jobs = progress.failed_jobs;
+                }
+                if !progress.error_details.is_empty() {
+                    self.error_details = progress.error_details;
+                }
+                if !progress.completed_outputs.is_empty() {
+                    self.completed_outputs = progress.completed_outputs;
+                    // Outputs can replace listed inputs, so re-read sizes and formats
+                    self.input_file_info.clear();
+                }
                 // Request repaint to update UI immediately
                 ui.ctx().request_repaint();
             }
+            // Every file of a cancelled batch has stopped once the async side drops its senders
+            if matches!(self.conversion_status, ConversionStatus::Cancelling { .. }) && progress_rx.is_closed() && progress_rx.is_empty() {
+                self.conversion_status = self.cancel_outcome.take().unwrap_or(ConversionStatus::Idle);
+                ui.ctx().request_repaint();
+            }
         }
 
         // Clone the current status to avoid borrow checker issues
@@ -1808,18 +6523,76 @@ impl HkxToolsApp {
                             .color(Color32::from_rgb(100, 150, 255))
                     );
                     
-                    // Progress bar
+                    if *total <= SPINNER_PROGRESS_MAX_FILES {
+                        // A per-file bar would sit still for the whole of a large file, so show that the tool is working
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(format!("{}/{} done", progress, total));
+                        });
+                        let tail_start = self.tool_log.len().saturating_sub(SPINNER_LOG_TAIL_LINES);
+                        for line in &self.tool_log[tail_start..] {
+                            ui.label(RichText::new(line.trim_end()).monospace().size(11.0).color(Color32::from_rgb(150, 150, 150)));
+                        }
+                    } else {
+                        // Progress bar
+                        let progress_fraction = if *total > 0 { *progress as f32 / *total as f32 } else { 0.0 };
+                        let progress_bar = egui::ProgressBar::new(progress_fraction)
+                            .text(format!("{}/{}", progress, total))
+                            .desired_height(20.0);
+                        ui.add(progress_bar);
+                    }
+                    if let Some(worker_counts) = &self.worker_counts {
+                        ui.label(RichText::new(worker_counts.summary()).size(12.0).color(Color32::from_rgb(150, 150, 150)));
+                    }
+                });
+                
+                // Request continuous repaints while running
+                ui.ctx().request_repaint();
+            }
+            ConversionStatus::Paused { progress, total } => {
+                ui.add_space(20.0);
+
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        RichText::new("Paused (files already converting will finish)")
+                            .size(14.0)
+                            .color(Color32::from_rgb(230, 190, 90))
+                    );
+
                     let progress_fraction = if *total > 0 { *progress as f32 / *total as f32 } else { 0.0 };
                     let progress_bar = egui::ProgressBar::new(progress_fraction)
                         .text(format!("{}/{}", progress, total))
                         .desired_height(20.0);
                     ui.add(progress_bar);
                 });
-                
-                // Request continuous repaints while running
+
+                // Keep picking up progress from the files still finishing
                 ui.ctx().request_repaint();
             }
-            ConversionStatus::Completed { message } => {
+            ConversionStatus::Cancelling { progress, total } => {
+                ui.add_space(20.0);
+
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        RichText::new("Cancelling… waiting for the files already converting to finish")
+                            .size(14.0)
+                            .color(Color32::from_rgb(230, 190, 90))
+                    );
+
+                    let progress_fraction = if *total > 0 { *progress as f32 / *total as f32 } else { 0.0 };
+                    let progress_bar = egui::ProgressBar::new(progress_fraction)
+                        .text(format!("{}/{}", progress, total))
+                        .desired_height(20.0);
+                    ui.add(progress_bar);
+                    if let Some(worker_counts) = &self.worker_counts {
+                        ui.label(RichText::new(worker_counts.summary()).size(12.0).color(Color32::from_rgb(150, 150, 150)));
+                    }
+                });
+
+                // Notice when the last file stops, without waiting for input
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+            }
+            ConversionStatus::Completed { message, stats } => {
                 ui.add_space(20.0);
 
                 ui.vertical_centered(|ui| {
@@ -1829,6 +6602,14 @@ impl HkxToolsApp {
                             .color(Color32::from_rgb(100, 200, 100))
                             .strong()
                     );
+                    ui.label(
+                        RichText::new(stats.summary())
+                            .size(12.0)
+                            .color(Color32::from_rgb(150, 150, 150))
+                    );
+                    if ui.small_button("Dismiss").on_hover_text("Clear this message; files and settings are kept").clicked() {
+                        self.conversion_status = ConversionStatus::Idle;
+                    }
                 });
             }
             ConversionStatus::Error { message } => {
@@ -1841,12 +6622,116 @@ impl HkxToolsApp {
                             .color(Color32::from_rgb(255, 120, 120))
                             .strong()
                     );
+                    ui.horizontal(|ui| {
+                        if ui.small_button("Details").on_hover_text("Show the full error text to copy into a bug report").clicked() {
+                            self.show_error_details = true;
+                        }
+                        if !self.failed_jobs.is_empty()
+                            && ui
+                                .small_button("📋 Copy Failed Files")
+                                .on_hover_text("Copy the full path of each input that failed, one per line")
+                                .clicked()
+                        {
+                            ui.ctx().copy_text(self.failed_input_list());
+                        }
+                        if ui.small_button("Dismiss").on_hover_text("Clear this message; files and settings are kept").clicked() {
+                            self.conversion_status = ConversionStatus::Idle;
+                        }
+                    });
                 });
             }
             ConversionStatus::Idle => {
                 // No status message when idle
             }
         }
+
+        // Collapsible results pane listing each converted file
+        if !self.completed_outputs.is_empty() {
+            ui.add_space(5.0);
+            egui::CollapsingHeader::new(format!("Converted Files ({})", self.completed_outputs.len()))
+                .id_source("converted_files_results")
+                .show(ui, |ui| {
+                    if ui
+                        .small_button("📋 Copy SHA-256 List")
+                        .on_hover_text("Copy each output's hash and path, in sha256sum format, to compare runs across machines")
+                        .clicked()
+                    {
+                        let lines: Vec<String> = self
+                            .completed_outputs
+                            .iter()
+                            .filter_map(|output| {
+                                output.sha256.as_ref().map(|hash| format!("{}  {}", hash, output.path.to_string_lossy()))
+                            })
+                            .collect();
+                        ui.ctx().copy_text(lines.join("\n"));
+                    }
+                    egui::ScrollArea::vertical()
+                        .id_source("converted_files_scroll")
+                        .max_height(150.0)
+                        .auto_shrink([false, true])
+                        .show(ui, |ui| {
+                            for output in &self.completed_outputs {
+                                ui.horizontal(|ui| {
+                                    ui.label(RichText::new(format!("#{}", output.index + 1)).size(11.0).color(Color32::GRAY));
+                                    if ui.small_button("📂").on_hover_text("Show in file explorer").clicked() {
+                                        Self::reveal_file_in_explorer(&output.path);
+                                    }
+                                    let display = self
+                                        .output_folder
+                                        .as_ref()
+                                        .and_then(|folder| output.path.strip_prefix(folder).ok())
+                                        .unwrap_or(&output.path);
+                                    ui.label(RichText::new(display.to_string_lossy()).size(12.0));
+                                    if let Some(hash) = &output.sha256 {
+                                        ui.label(RichText::new(&hash[..12]).monospace().size(11.0).color(Color32::GRAY))
+                                            .on_hover_text(format!("SHA-256 {}", hash));
+                                    }
+                                });
+                            }
+                        });
+                });
+        }
+
+        // Queued jobs, in the order they will run
+        if !self.job_queue.is_empty() {
+            ui.add_space(5.0);
+            let mut job_to_remove = None;
+            egui::CollapsingHeader::new(format!("Job Queue ({})", self.job_queue.len()))
+                .id_source("job_queue")
+                .default_open(true)
+                .show(ui, |ui| {
+                    for (index, job) in self.job_queue.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("❌").on_hover_text("Remove job").clicked() {
+                                job_to_remove = Some(index);
+                            }
+                            ui.label(RichText::new(format!("{}. {}", index + 1, job.label())).size(12.0))
+                                .on_hover_text(job.output_folder.to_string_lossy().to_string());
+                        });
+                    }
+                });
+            if let Some(index) = job_to_remove {
+                self.job_queue.remove(index);
+            }
+        }
+
+        // Collapsible pane with live tool output
+        if !self.tool_log.is_empty() {
+            ui.add_space(5.0);
+            egui::CollapsingHeader::new(format!("Tool Output ({} lines)", self.tool_log.len()))
+                .id_source("tool_output_log")
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(150.0)
+                        .stick_to_bottom(true)
+                        .auto_shrink([false, true])
+                        .show(ui, |ui| {
+                            for line in &self.tool_log {
+                                ui.label(RichText::new(line).monospace().size(11.0));
+                            }
+                        });
+                });
+        }
                 
         // Big prominent button at the bottom
         ui.vertical_centered(|ui| {
@@ -1855,6 +6740,12 @@ impl HkxToolsApp {
                     if matches!(current_status, ConversionStatus::Idle) {
                         ui.add_space(20.0);
                     }
+                    // Catch combinations the tools would reject before any work starts
+                    let configuration_problem = self.configuration_problem(&self.input_paths);
+                    if let Some(problem) = &configuration_problem {
+                        ui.label(RichText::new(format!("⚠ {}", problem)).color(Color32::from_rgb(255, 180, 80)));
+                        ui.add_space(5.0);
+                    }
 
                     let button = egui::Button::new(
                         RichText::new("🚀 RUN CONVERSION")
@@ -1864,15 +6755,87 @@ impl HkxToolsApp {
                     .min_size(egui::Vec2::new(ui.available_width() - 20.0, 50.0))
                     .fill(Color32::from_rgb(70, 130, 220));
                     
-                    if ui.add(button).clicked() {
-                        // Reset status before starting new conversion
-                        self.conversion_status = ConversionStatus::Idle;
-                        self.progress_rx = None;
-                        self.cancel_tx = None;
-                        self.start_conversion();
+                    let run_response = ui.add_enabled(configuration_problem.is_none(), button);
+                    let run_response = match &configuration_problem {
+                        Some(problem) => run_response.on_disabled_hover_text(problem),
+                        None => run_response,
+                    };
+                    if run_response.clicked() {
+                        self.run_conversion();
+                    }
+
+                    // Queue the current setup to run later alongside other tools and formats
+                    ui.add_space(5.0);
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(configuration_problem.is_none(), egui::Button::new("➕ Add to Queue"))
+                            .on_hover_text("Save the current tool, formats and files as a job")
+                            .clicked()
+                        {
+                            self.add_current_job_to_queue();
+                        }
+                        if ui
+                            .add_enabled(configuration_problem.is_none(), egui::Button::new("📜 Export Commands"))
+                            .on_hover_text("Save the tool command lines for every file as a script, to run or inspect by hand")
+                            .clicked()
+                        {
+                            self.export_commands();
+                        }
+                        if !self.job_queue.is_empty() {
+                            if ui.button(format!("▶ Run Queue ({} jobs)", self.job_queue.len())).clicked() {
+                                self.reset_conversion_state();
+                                self.run_job_queue();
+                            }
+                            if ui.button("Clear Queue").clicked() {
+                                self.job_queue.clear();
+                            }
+                        }
+                    });
+
+                    // Offer to re-run only the files that failed in the last batch
+                    let failed_count: usize = self.failed_jobs.iter().map(|job| job.input_paths.len()).sum();
+                    if failed_count > 0 {
+                        ui.add_space(5.0);
+                        let retry_button = egui::Button::new(
+                            RichText::new(format!("🔁 RETRY FAILED ({})", failed_count))
+                                .size(14.0)
+                                .strong()
+                        )
+                        .min_size(egui::Vec2::new(ui.available_width() - 20.0, 30.0))
+                        .fill(Color32::from_rgb(200, 140, 60));
+
+                        if ui.add(retry_button).clicked() {
+                            self.reset_conversion_state();
+                            self.retry_failed_conversions();
+                        }
                     }
                 }
-                ConversionStatus::Running { .. } => {
+                ConversionStatus::Running { progress, total, .. } | ConversionStatus::Paused { progress, total } => {
+                    let paused = matches!(current_status, ConversionStatus::Paused { .. });
+                    let pause_button = egui::Button::new(
+                        RichText::new(if paused { "▶ RESUME" } else { "⏸ PAUSE" })
+                            .size(16.0)
+                            .strong()
+                    )
+                    .min_size(egui::Vec2::new(ui.available_width() - 20.0, 35.0))
+                    .fill(Color32::from_rgb(180, 140, 60));
+
+                    if ui.add(pause_button).clicked() {
+                        if let Some(pause_gate) = &self.pause_gate {
+                            pause_gate.set_paused(!paused);
+                            self.conversion_status = if paused {
+                                ConversionStatus::Running {
+                                    current_file: "Resuming...".to_string(),
+                                    progress,
+                                    total,
+                                }
+                            } else {
+                                ConversionStatus::Paused { progress, total }
+                            };
+                        }
+                    }
+
+                    ui.add_space(5.0);
                     let button = egui::Button::new(
                         RichText::new("⏹ CANCEL CONVERSION")
                             .size(16.0)
@@ -1885,9 +6848,22 @@ impl HkxToolsApp {
                         if let Some(cancel_tx) = self.cancel_tx.take() {
                             let _ = cancel_tx.send(());
                         }
-                        self.conversion_status = ConversionStatus::Idle;
+                        // Release files waiting at the pause gate so they don't start
+                        if let Some(pause_gate) = self.pause_gate.take() {
+                            pause_gate.cancel();
+                        }
+                        self.conversion_status = ConversionStatus::Cancelling { progress, total };
                     }
                 }
+                ConversionStatus::Cancelling { .. } => {
+                    let button = egui::Button::new(
+                        RichText::new("⏹ CANCELLING…")
+                            .size(16.0)
+                            .strong()
+                    )
+                    .min_size(egui::Vec2::new(ui.available_width() - 20.0, 45.0));
+                    ui.add_enabled(false, button);
+                }
             }
         });
         
@@ -1897,6 +6873,15 @@ impl HkxToolsApp {
 
 impl eframe::App for HkxToolsApp {
     fn update(&mut self, ctx: &EguiContext, _frame: &mut Frame) {
+        // Track window geometry so it can be restored on next launch
+        self.track_window_geometry(ctx);
+
+        self.poll_update_check();
+        if self.update_rx.is_some() {
+            // Keep polling while the check runs, even without user input
+            ctx.request_repaint_after(std::time::Duration::from_millis(250));
+        }
+
         // Check if files are being hovered over the window
         let files_being_hovered = ctx.input(|i| i.raw.hovered_files.len() > 0);
         let hovered_files_count = ctx.input(|i| i.raw.hovered_files.len());
@@ -1907,6 +6892,30 @@ impl eframe::App for HkxToolsApp {
             self.handle_dropped_files(dropped_files);
         }
 
+        // Ctrl+Z restores the input list, unless a text field has focus and handles it itself
+        if ctx.memory(|m| m.focused().is_none()) && ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z)) {
+            self.undo_input_change();
+        }
+
+        // Ctrl+V adds pasted file paths, unless a text field has focus and takes the text
+        if ctx.memory(|m| m.focused().is_none()) {
+            let pasted = ctx.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Paste(text) => Some(text.clone()),
+                    _ => None,
+                })
+            });
+            if let Some(text) = pasted {
+                self.add_pasted_paths(&text);
+            }
+        }
+        self.read_new_input_file_info();
+
+        // Summary of the current setup, always visible
+        egui::TopBottomPanel::top("status_bar").show(ctx, |ui| {
+            self.render_status_bar(ui);
+        });
+
         // Bottom panel for conversion button (always at bottom)
         egui::TopBottomPanel::bottom("conversion_panel")
             .resizable(false)
@@ -1914,67 +6923,405 @@ impl eframe::App for HkxToolsApp {
                 self.handle_conversion(ui);
             });
 
+        // Side panels go before the central panel, which takes the space left
+        self.render_xml_viewer(ctx);
+
         // Main content in the center
         egui::CentralPanel::default().show(ctx, |ui| {
             self.render_main_ui(ui);
         });
 
+        // Confirmation modal for batches that would overwrite source files
+        self.render_unreadable_inputs(ctx);
+        self.render_overwrite_confirmation(ctx);
+        self.render_resume_confirmation(ctx);
+        self.render_delete_sources_confirmation(ctx);
+        self.render_large_folder_confirmation(ctx);
+        self.render_error_details(ctx);
+
+        self.poll_xml_edit();
+        self.render_xml_edit_window(ctx);
+
+        self.poll_watch_folder(ctx);
+        self.poll_folder_scan(ctx);
+        self.poll_archive_extractions(ctx);
+        self.poll_auto_run(ctx);
+        self.update_window_progress(ctx);
+        self.render_batch_end_flash(ctx);
+        self.render_command_palette(ctx);
+        self.render_about_window(ctx);
+        self.render_drop_notice(ctx);
+
         // Show drag and drop overlay when files are being hovered
         if files_being_hovered {
             self.render_drag_drop_overlay(ctx, hovered_files_count);
         }
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Err(e) = self.settings.save() {
+            eprintln!("Failed to save settings: {}", e);
+        }
+    }
+}
+
+
+
+/// Whether the XML viewer can show `path`: an HKX it can decompile, or an XML file
+fn is_xml_viewable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("hkx") || ext.eq_ignore_ascii_case("xml"))
+}
+
+/// Highlighted, unwrapped layout of `text` for the XML viewer, with the search `matches` marked
+/// and the one at `current_match` marked more strongly
+fn xml_layout_job(
+    text: &str,
+    matches: &[std::ops::Range<usize>],
+    current_match: usize,
+    visuals: &egui::Visuals,
+) -> egui::text::LayoutJob {
+    let color = |span: XmlSpan| match (span, visuals.dark_mode) {
+        (XmlSpan::Text, _) => visuals.text_color(),
+        (XmlSpan::Markup, _) => Color32::from_rgb(150, 150, 150),
+        (XmlSpan::TagName, true) => Color32::from_rgb(100, 150, 255),
+        (XmlSpan::TagName, false) => Color32::from_rgb(30, 80, 190),
+        (XmlSpan::AttributeName, true) => Color32::from_rgb(150, 200, 240),
+        (XmlSpan::AttributeName, false) => Color32::from_rgb(40, 120, 160),
+        (XmlSpan::AttributeValue, true) => Color32::from_rgb(220, 160, 110),
+        (XmlSpan::AttributeValue, false) => Color32::from_rgb(170, 80, 20),
+        (XmlSpan::Comment, _) => Color32::from_rgb(100, 160, 100),
+    };
+    let font_id = egui::FontId::monospace(12.0);
+    let mut job = egui::text::LayoutJob::default();
+    job.wrap.max_width = f32::INFINITY;
+
+    let mut matches = matches.iter().enumerate().peekable();
+    for (range, span) in xml_spans(text) {
+        let mut start = range.start;
+        while start < range.end {
+            while matches.next_if(|(_, found)| found.end <= start).is_some() {}
+            let (end, background) = match matches.peek() {
+                Some((index, found)) if found.start <= start => {
+                    let alpha = if *index == current_match { 170 } else { 70 };
+                    (found.end.min(range.end), Color32::from_rgba_unmultiplied(255, 190, 0, alpha))
+                }
+                Some((_, found)) => (found.start.min(range.end), Color32::TRANSPARENT),
+                None => (range.end, Color32::TRANSPARENT),
+            };
+            job.append(
+                &text[start..end],
+                0.0,
+                egui::TextFormat {
+                    font_id: font_id.clone(),
+                    color: color(span),
+                    background,
+                    ..Default::default()
+                },
+            );
+            start = end;
+        }
+    }
+    job
+}
+
+/// Check if a path is a text file listing input paths
+fn is_file_list(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"))
+}
+
+/// Decode a text file written by another tool: UTF-8 or UTF-16 with a BOM (as PowerShell writes),
+/// plain UTF-8, or the Windows ANSI code page as a last resort
+fn decode_text(bytes: &[u8]) -> String {
+    if let Some((encoding, bom_length)) = encoding_rs::Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return text.into_owned();
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => encoding_rs::WINDOWS_1252.decode(bytes).0.into_owned(),
+    }
+}
+
+/// Existing files and folders named by a drop that carries text rather than a file reference:
+/// one absolute path or `file://` URL per line. Other URLs and relative paths are ignored.
+fn dropped_text_paths(dropped_file: &egui::DroppedFile) -> Vec<PathBuf> {
+    let text = match &dropped_file.bytes {
+        Some(bytes) => decode_text(bytes),
+        None => dropped_file.name.clone(),
+    };
+    text_paths(&text)
+}
+
+/// Existing absolute paths or `file://` URLs in `text`, one per line and optionally quoted
+fn text_paths(text: &str) -> Vec<PathBuf> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_matches('"');
+            let path = match line.get(..7) {
+                Some(scheme) if scheme.eq_ignore_ascii_case("file://") => file_url_path(&line[7..]),
+                _ => PathBuf::from(line),
+            };
+            // Relative paths stay relative, and are dropped
+            let path = normalize_listed_path(&path.to_string_lossy(), Path::new(""));
+            (path.is_absolute() && path.exists()).then_some(path)
+        })
+        .collect()
+}
+
+/// Path of a `file://` URL, given the part after the scheme (`/C:/Mods/walk%20fast.hkx`)
+fn file_url_path(url_path: &str) -> PathBuf {
+    // Percent-decode byte by byte, since an escaped character can span several bytes
+    let bytes = url_path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| url_path.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    let path = String::from_utf8_lossy(&decoded).into_owned();
+    // "localhost" is the only host a local file URL can have
+    let path = path.strip_prefix("localhost").unwrap_or(&path);
+    // Windows URLs put a slash before the drive letter
+    if cfg!(windows) && path.get(2..3) == Some(":") {
+        return PathBuf::from(&path[1..]);
+    }
+    PathBuf::from(path)
+}
+
+/// Play a short two-tone chime in the background: rising when a batch succeeded, falling when it failed
+#[cfg(windows)]
+fn play_chime(failed: bool) {
+    use rodio::Source;
+
+    std::thread::spawn(move || {
+        // A missing audio device isn't worth reporting; the chime is only a convenience
+        let Ok((_stream, handle)) = rodio::OutputStream::try_default() else {
+            return;
+        };
+        let Ok(sink) = rodio::Sink::try_new(&handle) else {
+            return;
+        };
+        let tones: [f32; 2] = if failed { [440.0, 330.0] } else { [660.0, 880.0] };
+        for frequency in tones {
+            sink.append(
+                rodio::source::SineWave::new(frequency)
+                    .take_duration(std::time::Duration::from_millis(150))
+                    .amplify(0.2),
+            );
+        }
+        sink.sleep_until_end();
+    });
+}
+
+#[cfg(not(windows))]
+fn play_chime(_failed: bool) {}
+
+/// Remove temp directories left behind by crashed or killed sessions. Folders another running
+/// instance still holds are kept, however old, since a long batch can keep one for days.
+fn sweep_stale_temp_dirs(base: &Path) {
+    let Ok(entries) = fs::read_dir(base) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !TEMP_DIR_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) || !path.is_dir() {
+            continue;
+        }
+
+        let is_stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > STALE_TEMP_DIR_AGE);
+        if !is_stale || temp_dir_in_use(&path) {
+            continue;
+        }
+
+        match fs::remove_dir_all(&path) {
+            Ok(()) => log_debug!("Removed stale temp directory: {:?}", path),
+            Err(e) => eprintln!("Failed to remove stale temp directory {:?}: {}", path, e),
+        }
+    }
 }
 
+/// Report a fatal error on stderr and in a message box
+fn show_fatal_error(message: &str) {
+    eprintln!("ERROR: {}", message);
+    rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Error)
+        .set_title("Composite HKX Conversion Tool")
+        .set_description(message)
+        .set_buttons(rfd::MessageButtons::Ok)
+        .show();
+}
+
+/// Write every panic to a crash_*.log next to the settings file, for attaching to bug reports, and
+/// tell the user where it is when the panic takes the app down
+fn install_crash_reporter() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
 
+        let thread = std::thread::current();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let report = format!(
+            "Composite HKX Conversion Tool {} crashed\nOS: {} {}\nTime: {} (seconds since 1970)\nThread: {}\n\n{}\n\nBacktrace:\n{}\n",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            timestamp,
+            thread.name().unwrap_or("unnamed"),
+            info,
+            std::backtrace::Backtrace::force_capture()
+        );
+        // Next to the settings file, or the temp folder when the app's folder is read-only
+        let file_name = format!("crash_{}.log", timestamp);
+        let config_dir = AppSettings::get_settings_file_path().parent().map(Path::to_path_buf).unwrap_or_default();
+        let crash_log = [config_dir.join(&file_name), std::env::temp_dir().join(&file_name)]
+            .into_iter()
+            .find(|path| fs::write(path, &report).is_ok());
+
+        // Release builds abort on any panic; otherwise only a panic on the UI thread ends the app
+        if cfg!(panic = "abort") || thread.name() == Some("main") {
+            show_fatal_error(&match crash_log {
+                Some(path) => format!(
+                    "The app ran into an unexpected error and has to close.\n\nA crash report was saved to {}. Please attach it when reporting the problem.",
+                    ensure_absolute_path(&path).display()
+                ),
+                None => format!("The app ran into an unexpected error and has to close.\n\n{}", info),
+            });
+        }
+    }));
+}
 
 #[tokio::main]
 async fn main() -> Result<(), eframe::Error> {
+    install_crash_reporter();
+    let cli_args = CliArgs::parse();
+
     // Create a tokio runtime handle for the GUI
     let tokio_handle = tokio::runtime::Handle::current();
 
+    // Restore window size and position from the previous session
+    let settings = AppSettings::load().unwrap_or_default();
+    // Keep stdout to the JSON summary when a script asked for one
+    set_log_level(if cli_args.json { LogLevel::Quiet } else { settings.log_level });
+
+    // Clean up temp folders leaked by previous sessions that didn't exit cleanly
+    sweep_stale_temp_dirs(&std::env::temp_dir());
+    if let Some(temp_dir) = &settings.temp_dir {
+        sweep_stale_temp_dirs(temp_dir);
+    }
+
+    // Portable mode: use the tools in a `tools` folder next to the executable when it has all of them
+    let portable_tools_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe_path| exe_path.parent().map(|exe_dir| exe_dir.join("tools")))
+        .filter(|dir| dir.is_dir());
+    if let Some(tools_dir) = portable_tools_dir {
+        match ToolPaths::from_dir(&tools_dir) {
+            Ok(tool_paths) => {
+                log_info!("Portable mode: using the tools in {:?}", tools_dir);
+                return run_app(settings, tool_paths, tokio_handle, None, cli_args);
+            }
+            Err(e) => eprintln!("Not using the tools folder, extracting the bundled tools instead: {:#}", e),
+        }
+    }
+
+    // Reuse the tools extracted on earlier launches, only rewriting the ones that changed. Writing
+    // fails when another version of the app is running the same tools, so fall back to a fresh folder.
+    let cache_dir = settings.temp_dir.clone().unwrap_or_else(std::env::temp_dir).join(TOOLS_CACHE_FOLDER);
+    match ToolPaths::extract_cached(&cache_dir) {
+        Ok(tool_paths) => {
+            log_info!("Using the bundled tools in {:?}", cache_dir);
+            return run_app(settings, tool_paths, tokio_handle, None, cli_args);
+        }
+        Err(e) => eprintln!("Not using the tools cache, extracting the bundled tools to a new folder instead: {:#}", e),
+    }
+
     // Write hkxcmd.exe, hkxc.exe, hkxconv.exe, and HCT .hko file to a temporary location
-    let temp_dir = tempfile::Builder::new()
-        .prefix("hkxtools_")
-        .tempdir()
-        .unwrap();
-    
-    let hkxcmd_path = temp_dir.path().join("hkxcmd.exe");
-    let hkxc_path = temp_dir.path().join("hkxc.exe");
-    let hkxconv_path = temp_dir.path().join("hkxconv.exe");
-    let sse_to_le_hko_path = temp_dir.path().join("_SSEtoLE.hko");
-    let havok_behavior_post_process_path = temp_dir.path().join("HavokBehaviorPostProcess.exe");
-    let hct_standalone_filter_manager_path = temp_dir.path().join("hctStandAloneFilterManager.exe");
-    let hct_filter_manager_dll_path = temp_dir.path().join("hctFilterManager.dll");
-    
-    fs::write(&hkxcmd_path, HKXCMD_EXE).unwrap();
-    fs::write(&hkxc_path, HKXC_EXE).unwrap();
-    fs::write(&hkxconv_path, HKXCONV_EXE).unwrap();
-    fs::write(&sse_to_le_hko_path, SSE_TO_LE_HKO).unwrap();
-    fs::write(&havok_behavior_post_process_path, HAVOK_BEHAVIOR_POST_PROCESS_EXE).unwrap();
-    fs::write(&hct_standalone_filter_manager_path, HCT_STANDALONE_FILTER_MANAGER_EXE).unwrap();
-    fs::write(&hct_filter_manager_dll_path, HCT_FILTER_MANAGER_DLL).unwrap();
-
-    println!("Extracted hkxcmd.exe to: {:?}", hkxcmd_path);
-    println!("Extracted hkxc.exe to: {:?}", hkxc_path);
-    println!("Extracted hkxconv.exe to: {:?}", hkxconv_path);
-    println!("Extracted _SSEtoLE.hko to: {:?}", sse_to_le_hko_path);
-    println!("Extracted HavokBehaviorPostProcess.exe to: {:?}", havok_behavior_post_process_path);
-    println!("Extracted hctStandAloneFilterManager.exe to: {:?}", hct_standalone_filter_manager_path);
-    println!("Extracted hctFilterManager.dll to: {:?}", hct_filter_manager_dll_path);
-
-    // Window width and height
+    let temp_dir = match create_temp_dir("hkxtools_", settings.temp_dir.as_deref()) {
+        Ok(temp_dir) => temp_dir,
+        Err(e) => {
+            show_fatal_error(&format!(
+                "Failed to create a temporary folder for the bundled conversion tools in {:?}.\n\n{}\n\nCheck that the folder exists and is writable, or remove temp_dir from {:?} to use the system TEMP folder.",
+                settings.temp_dir.clone().unwrap_or_else(std::env::temp_dir),
+                e,
+                AppSettings::get_settings_file_path()
+            ));
+            std::process::exit(1);
+        }
+    };
+
+    // Any extraction failure is fatal, since the app can't convert without its tools
+    let tool_paths = match ToolPaths::extract_to(temp_dir.path()) {
+        Ok(tool_paths) => tool_paths,
+        Err(e) => {
+            show_fatal_error(&format!(
+                "Failed to extract the bundled conversion tools.\n\n{:#}\n\nAntivirus software often quarantines these game-modding tools. Try adding an exclusion for this application and your TEMP folder, then restart.",
+                e
+            ));
+            std::process::exit(1);
+        }
+    };
+
+    run_app(settings, tool_paths, tokio_handle, Some(temp_dir), cli_args)
+}
+
+/// Open the main window, keeping the extracted tools (if any) until it closes.
+///
+/// With --exit-after the process exits with the conversion's status once the window closes.
+fn run_app(
+    settings: AppSettings,
+    tool_paths: ToolPaths,
+    tokio_handle: tokio::runtime::Handle,
+    temp_dir: Option<OwnedTempDir>,
+    cli_args: CliArgs,
+) -> Result<(), eframe::Error> {
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([600.0, 600.0]),
+        viewport: settings.initial_viewport(),
         ..Default::default()
     };
     
     // Keep temp_dir alive for the entire application lifetime
-    let _temp_dir_guard = temp_dir;
-    
-    eframe::run_native(
-        "Composite HKX Conversion GUI",
+    let temp_dir_guard = temp_dir;
+
+    let exit_after = cli_args.exit_after;
+    let exit_code = Arc::new(AtomicI32::new(0));
+    let app_exit_code = exit_code.clone();
+    let result = eframe::run_native(
+        WINDOW_TITLE,
         options,
-        Box::new(move |_cc| Ok(Box::new(HkxToolsApp::new(hkxcmd_path, hkxc_path, hkxconv_path, sse_to_le_hko_path, havok_behavior_post_process_path, hct_standalone_filter_manager_path, hct_filter_manager_dll_path, tokio_handle)))),
-    )
+        Box::new(move |_cc| Ok(Box::new(HkxToolsApp::new(tool_paths, tokio_handle).with_cli_args(cli_args, app_exit_code)))),
+    );
+
+    // Exiting skips destructors, so remove the extracted tools first
+    drop(temp_dir_guard);
+    if exit_after && result.is_ok() {
+        std::process::exit(exit_code.load(Ordering::SeqCst));
+    }
+    result
 }
@@ -0,0 +1,217 @@
+use composite_hkx_conversion::{is_archive, Archive};
+use std::io::Write;
+use std::path::Path;
+
+/// Folder, name and contents of a file to put in a test archive
+type ArchivedFile<'a> = (&'a str, &'a str, &'a [u8]);
+
+type Compressor = fn(&[u8]) -> Vec<u8>;
+
+/// Build a BSA holding `files`, compressing each of them with `compress` when given
+fn bsa(version: u32, flags: u32, files: &[ArchivedFile], compress: Option<Compressor>) -> Vec<u8> {
+    let mut folders: Vec<&str> = files.iter().map(|(folder, _, _)| *folder).collect();
+    folders.dedup();
+    let folder_record_len = if version == 105 { 24 } else { 16 };
+    let file_names: Vec<u8> = files.iter().flat_map(|(_, name, _)| name.bytes().chain([0])).collect();
+
+    let mut header = Vec::new();
+    header.extend(b"BSA\0");
+    for value in [version, 36, flags, folders.len() as u32, files.len() as u32, 0, file_names.len() as u32, 0] {
+        header.extend(value.to_le_bytes());
+    }
+
+    let mut folder_records = Vec::new();
+    let mut file_records = Vec::new();
+    let mut data = Vec::new();
+    let data_start = 36
+        + folders.len() * folder_record_len
+        + folders.iter().map(|folder| folder.len() + 2).sum::<usize>()
+        + files.len() * 16
+        + file_names.len();
+    for folder in &folders {
+        let folder_files: Vec<_> = files.iter().filter(|(file_folder, _, _)| file_folder == folder).collect();
+        folder_records.extend(0u64.to_le_bytes());
+        folder_records.extend((folder_files.len() as u32).to_le_bytes());
+        if version == 105 {
+            folder_records.extend([0; 12]);
+        } else {
+            folder_records.extend(0u32.to_le_bytes());
+        }
+        file_records.push(folder.len() as u8 + 1);
+        file_records.extend(folder.bytes().chain([0]));
+        for (_, name, contents) in folder_files {
+            let offset = data_start + data.len();
+            let start = data.len();
+            if flags & 0x100 != 0 {
+                let full_name = format!("{}\\{}", folder, name);
+                data.push(full_name.len() as u8);
+                data.extend(full_name.bytes());
+            }
+            match compress {
+                Some(compress) => {
+                    data.extend((contents.len() as u32).to_le_bytes());
+                    data.extend(compress(contents));
+                }
+                None => data.extend(*contents),
+            }
+            file_records.extend(0u64.to_le_bytes());
+            file_records.extend(((data.len() - start) as u32).to_le_bytes());
+            file_records.extend((offset as u32).to_le_bytes());
+        }
+    }
+    [header, folder_records, file_records, file_names, data].concat()
+}
+
+fn lz4_frame(contents: &[u8]) -> Vec<u8> {
+    let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+    encoder.write_all(contents).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zlib(contents: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(contents).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn write_archive(dir: &Path, name: &str, bytes: &[u8]) -> std::path::PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+fn entry_paths(archive: &Archive) -> Vec<&str> {
+    archive.entries().iter().map(|entry| entry.path.as_str()).collect()
+}
+
+#[test]
+fn archives_are_recognized_by_extension() {
+    assert!(is_archive(Path::new("/mods/Animations.bsa")));
+    assert!(is_archive(Path::new("/mods/Animations - Main.BA2")));
+    assert!(!is_archive(Path::new("/mods/walk.hkx")));
+}
+
+#[test]
+fn uncompressed_skyrim_le_bsa() {
+    let dir = tempfile::tempdir().unwrap();
+    let files: &[ArchivedFile] = &[
+        ("meshes\\actors\\character\\animations", "walk.hkx", b"walk data"),
+        ("meshes\\actors\\character\\animations", "run.hkx", b"run data"),
+        ("scripts", "readme.txt", b"text"),
+    ];
+    let path = write_archive(dir.path(), "Anims.bsa", &bsa(104, 0x3, files, None));
+
+    let archive = Archive::open(&path).unwrap();
+    assert_eq!(
+        entry_paths(&archive),
+        ["meshes/actors/character/animations/walk.hkx", "meshes/actors/character/animations/run.hkx", "scripts/readme.txt"]
+    );
+    assert_eq!(archive.read(&archive.entries()[1]).unwrap(), b"run data");
+
+    let extracted = archive.extract(&archive.entries()[0], &dir.path().join("out")).unwrap();
+    assert_eq!(extracted, dir.path().join("out/meshes/actors/character/animations/walk.hkx"));
+    assert_eq!(std::fs::read(extracted).unwrap(), b"walk data");
+}
+
+#[test]
+fn compressed_skyrim_se_bsa_with_embedded_names() {
+    let dir = tempfile::tempdir().unwrap();
+    let contents = b"<hkpackfile>repeated repeated repeated repeated</hkpackfile>".repeat(20);
+    let files: &[ArchivedFile] = &[("meshes\\behaviors", "0_master.xml", &contents)];
+    let path = write_archive(dir.path(), "Behaviors.bsa", &bsa(105, 0x3 | 0x4 | 0x100, files, Some(lz4_frame)));
+
+    let archive = Archive::open(&path).unwrap();
+    assert_eq!(entry_paths(&archive), ["meshes/behaviors/0_master.xml"]);
+    assert_eq!(archive.read(&archive.entries()[0]).unwrap(), contents);
+}
+
+#[test]
+fn compressed_bsa_uses_zlib_before_skyrim_se() {
+    let dir = tempfile::tempdir().unwrap();
+    let files: &[ArchivedFile] = &[("meshes", "walk.hkx", b"zlib compressed walk")];
+    let path = write_archive(dir.path(), "Anims.bsa", &bsa(104, 0x3 | 0x4, files, Some(zlib)));
+
+    let archive = Archive::open(&path).unwrap();
+    assert_eq!(archive.read(&archive.entries()[0]).unwrap(), b"zlib compressed walk");
+}
+
+#[test]
+fn general_ba2_with_stored_and_compressed_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let stored = b"stored walk".to_vec();
+    let compressed = zlib(b"compressed run");
+    let names = ["Meshes\\Walk.hkx", "Meshes\\Run.hkx"];
+    let data_start = 24 + 2 * 36;
+
+    let mut bytes = Vec::new();
+    bytes.extend(b"BTDX");
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(b"GNRL");
+    bytes.extend(2u32.to_le_bytes());
+    bytes.extend(((data_start + stored.len() + compressed.len()) as u64).to_le_bytes());
+    for (offset, packed_size, unpacked_size) in [
+        (data_start, 0, stored.len()),
+        (data_start + stored.len(), compressed.len(), b"compressed run".len()),
+    ] {
+        bytes.extend([0; 16]);
+        bytes.extend((offset as u64).to_le_bytes());
+        bytes.extend((packed_size as u32).to_le_bytes());
+        bytes.extend((unpacked_size as u32).to_le_bytes());
+        bytes.extend(0xBAAD_F00Du32.to_le_bytes());
+    }
+    bytes.extend(&stored);
+    bytes.extend(&compressed);
+    for name in names {
+        bytes.extend((name.len() as u16).to_le_bytes());
+        bytes.extend(name.bytes());
+    }
+    let path = write_archive(dir.path(), "Anims - Main.ba2", &bytes);
+
+    let archive = Archive::open(&path).unwrap();
+    assert_eq!(entry_paths(&archive), ["Meshes/Walk.hkx", "Meshes/Run.hkx"]);
+    assert_eq!(archive.read(&archive.entries()[0]).unwrap(), b"stored walk");
+    assert_eq!(archive.read(&archive.entries()[1]).unwrap(), b"compressed run");
+}
+
+#[test]
+fn extracted_paths_stay_inside_the_folder() {
+    let dir = tempfile::tempdir().unwrap();
+    let files: &[ArchivedFile] = &[("..\\..\\outside", "walk.hkx", b"walk")];
+    let path = write_archive(dir.path(), "Evil.bsa", &bsa(104, 0x3, files, None));
+
+    let archive = Archive::open(&path).unwrap();
+    let extracted = archive.extract(&archive.entries()[0], &dir.path().join("out")).unwrap();
+    assert_eq!(extracted, dir.path().join("out/outside/walk.hkx"));
+}
+
+#[test]
+fn other_files_are_not_archives() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_archive(dir.path(), "Fake.bsa", b"not an archive at all");
+    let error = Archive::open(&path).unwrap_err();
+    assert!(format!("{:#}", error).contains("Not a BSA or BA2 archive"), "unexpected error: {:#}", error);
+}
+
+#[test]
+fn header_counts_larger_than_the_archive_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut bytes = bsa(104, 0x3, &[("meshes", "walk.hkx", b"walk")], None);
+    // Folder count
+    bytes[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+    let path = write_archive(dir.path(), "Corrupt.bsa", &bytes);
+    let error = Archive::open(&path).unwrap_err();
+    assert!(format!("{:#}", error).contains("more than fit"), "unexpected error: {:#}", error);
+}
+
+#[test]
+fn unpacked_size_beyond_any_compression_ratio_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut bytes = bsa(104, 0x3 | 0x4, &[("meshes", "walk.hkx", b"zlib compressed walk")], Some(zlib));
+    // The file's data starts with its unpacked size
+    let data_start = bytes.len() - zlib(b"zlib compressed walk").len() - 4;
+    bytes[data_start..data_start + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    let path = write_archive(dir.path(), "Corrupt.bsa", &bytes);
+    let archive = Archive::open(&path).unwrap();
+    let error = archive.read(&archive.entries()[0]).unwrap_err();
+    assert!(error.to_string().contains("claims to unpack"), "unexpected error: {:#}", error);
+}
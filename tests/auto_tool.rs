@@ -0,0 +1,60 @@
+use composite_hkx_conversion::{auto_converter_tool, auto_converter_tool_for_format, is_behavior_file, ConverterTool, OutputFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Write an HKX packfile header with the given pointer size at `relative` under `dir`
+fn hkx(dir: &Path, relative: &str, pointer_size: u8) -> PathBuf {
+    let path = dir.join(relative);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let mut header = [0u8; 17];
+    header[16] = pointer_size;
+    fs::write(&path, header).unwrap();
+    path
+}
+
+#[test]
+fn kf_input_and_output_use_hkxcmd() {
+    assert_eq!(auto_converter_tool(Path::new("/mods/walk.kf"), OutputFormat::Kf), ConverterTool::HkxCmd);
+    assert_eq!(auto_converter_tool(Path::new("/mods/walk.xml"), OutputFormat::Kf), ConverterTool::HkxCmd);
+}
+
+#[test]
+fn se_behaviors_use_hkxconv() {
+    let dir = tempfile::tempdir().unwrap();
+    let behavior = hkx(dir.path(), "character/behaviors/0_master.hkx", 8);
+    assert_eq!(auto_converter_tool(&behavior, OutputFormat::Xml), ConverterTool::HkxConv);
+    let behavior_xml = dir.path().join("character/behaviors/0_master.xml");
+    assert_eq!(auto_converter_tool(&behavior_xml, OutputFormat::SkyrimSE), ConverterTool::HkxConv);
+}
+
+#[test]
+fn le_behaviors_and_le_output_use_hkxc() {
+    let dir = tempfile::tempdir().unwrap();
+    let le_behavior = hkx(dir.path(), "character/behaviors/0_master.hkx", 4);
+    assert_eq!(auto_converter_tool(&le_behavior, OutputFormat::SkyrimSE), ConverterTool::HkxC);
+    let behavior_xml = dir.path().join("character/behaviors/0_master.xml");
+    assert_eq!(auto_converter_tool(&behavior_xml, OutputFormat::SkyrimLE), ConverterTool::HkxC);
+}
+
+#[test]
+fn animations_use_hkxc() {
+    let dir = tempfile::tempdir().unwrap();
+    let animation = hkx(dir.path(), "character/animations/walk.hkx", 4);
+    assert_eq!(auto_converter_tool(&animation, OutputFormat::SkyrimSE), ConverterTool::HkxC);
+    assert_eq!(auto_converter_tool(&animation, OutputFormat::Xml), ConverterTool::HkxC);
+}
+
+#[test]
+fn fnis_behaviors_are_recognized_by_name() {
+    assert!(is_behavior_file(Path::new("/mods/animations/FNIS_MyMod_Behavior.hkx")));
+    assert!(is_behavior_file(Path::new("/mods/character/Behaviors Wolf/wolfbehavior.hkx")));
+    assert!(!is_behavior_file(Path::new("/mods/character/animations/walk.hkx")));
+}
+
+#[test]
+fn already_detected_format_is_used_without_reading_the_file() {
+    // The file doesn't exist, so only the given format can make it an SE behavior
+    let behavior = Path::new("/missing/behaviors/0_master.hkx");
+    assert_eq!(auto_converter_tool_for_format(behavior, Some(OutputFormat::SkyrimSE), OutputFormat::Xml), ConverterTool::HkxConv);
+    assert_eq!(auto_converter_tool_for_format(behavior, Some(OutputFormat::SkyrimLE), OutputFormat::Xml), ConverterTool::HkxC);
+}
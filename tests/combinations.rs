@@ -0,0 +1,62 @@
+use composite_hkx_conversion::{Converter, ConverterTool, InputFileExtension, OutputFormat, ToolPaths};
+use std::path::Path;
+
+#[test]
+fn setting_keys_round_trip() {
+    for tool in [ConverterTool::HkxCmd, ConverterTool::Hct, ConverterTool::HavokBehaviorPostProcess, ConverterTool::HkxC, ConverterTool::HkxConv] {
+        assert_eq!(ConverterTool::from_key(tool.key()), Some(tool));
+        for format in tool.available_output_formats() {
+            assert_eq!(OutputFormat::from_key(format.key()), Some(format));
+        }
+        for filter in tool.available_input_extensions() {
+            assert_eq!(InputFileExtension::from_key(filter.key()), Some(filter));
+        }
+    }
+    assert_eq!(ConverterTool::from_key("HkxCmd"), None);
+}
+
+#[test]
+fn supported_combination_is_accepted() {
+    assert!(ConverterTool::HkxC.check_conversion(Path::new("walk.hkx"), OutputFormat::Xml).is_ok());
+    assert!(ConverterTool::HkxCmd.check_conversion(Path::new("walk.kf"), OutputFormat::Kf).is_ok());
+    assert!(ConverterTool::Hct.check_conversion(Path::new("walk.hkt"), OutputFormat::SkyrimLE).is_ok());
+}
+
+#[test]
+fn kf_output_with_hkxc_is_rejected() {
+    let error = ConverterTool::HkxC.check_conversion(Path::new("walk.hkx"), OutputFormat::Kf).unwrap_err();
+    assert_eq!(error.to_string(), "hkxc can't convert to KF");
+}
+
+#[test]
+fn unreadable_input_is_rejected() {
+    let error = ConverterTool::HavokBehaviorPostProcess
+        .check_conversion(Path::new("behavior.xml"), OutputFormat::SkyrimSE)
+        .unwrap_err();
+    assert_eq!(error.to_string(), "HavokBehaviorPostProcess can't read XML files");
+}
+
+#[test]
+fn kf_input_needs_kf_output_format() {
+    let error = ConverterTool::HkxCmd.check_conversion(Path::new("walk.kf"), OutputFormat::SkyrimSE).unwrap_err();
+    assert!(error.to_string().contains("KF output format"), "unexpected error: {}", error);
+}
+
+#[test]
+fn hkxconv_only_offers_se_hkx() {
+    assert!(ConverterTool::HkxConv.check_conversion(Path::new("behavior.xml"), OutputFormat::SkyrimSE).is_ok());
+    let error = ConverterTool::HkxConv.check_conversion(Path::new("behavior.xml"), OutputFormat::SkyrimLE).unwrap_err();
+    assert_eq!(error.to_string(), "hkxconv can't convert to Skyrim LE");
+}
+
+#[tokio::test]
+async fn hkxconv_refuses_le_output_instead_of_writing_se() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("behavior.xml");
+    std::fs::write(&input, b"<hkpackfile/>").unwrap();
+    let error = Converter::new(ConverterTool::HkxConv, OutputFormat::SkyrimLE, ToolPaths::default())
+        .convert(&input, &dir.path().join("behavior.hkx"))
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", error).contains("only write SE HKX"), "unexpected error: {:#}", error);
+}
@@ -0,0 +1,78 @@
+use composite_hkx_conversion::{command_script, Converter, ConverterTool, OutputFormat, ToolPaths};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+fn tool_paths() -> ToolPaths {
+    ToolPaths {
+        hkxcmd: PathBuf::from("/tools/hkxcmd.exe"),
+        hkxc: PathBuf::from("/tools/hkxc.exe"),
+        hkxconv: PathBuf::from("/tools/hkxconv.exe"),
+        sse_to_le_hko: PathBuf::from("/tools/_SSEtoLE.hko"),
+        havok_behavior_post_process: PathBuf::from("/tools/HavokBehaviorPostProcess.exe"),
+        hct_standalone_filter_manager: PathBuf::from("/tools/hctStandAloneFilterManager.exe"),
+        hct_filter_manager_dll: PathBuf::from("/tools/hctFilterManager.dll"),
+    }
+}
+
+fn command_lines(converter: Converter, windows: bool) -> Vec<Vec<String>> {
+    converter
+        .command_lines(Path::new("/mods/walk.hkx"), Path::new("/out/walk.xml"), windows)
+        .unwrap()
+        .into_iter()
+        .map(|line| line.into_iter().map(|arg| arg.to_string_lossy().into_owned()).collect())
+        .collect()
+}
+
+#[test]
+fn hkxc_command_runs_through_wine() {
+    let converter = Converter::new(ConverterTool::HkxC, OutputFormat::Xml, tool_paths()).with_wine(Some(PathBuf::from("/usr/bin/wine")));
+    assert_eq!(
+        command_lines(converter, false),
+        [["/usr/bin/wine", "/tools/hkxc.exe", "convert", "--input", "/mods/walk.hkx", "--output", "/out/walk.xml", "--format", "xml"]]
+    );
+}
+
+#[test]
+fn havok_behavior_post_process_copies_the_input_first() {
+    let converter = Converter::new(ConverterTool::HavokBehaviorPostProcess, OutputFormat::SkyrimSE, tool_paths());
+    assert_eq!(
+        command_lines(converter, true),
+        [
+            vec!["copy", "/Y", "/mods/walk.hkx", "/out/walk.xml"],
+            vec!["/tools/HavokBehaviorPostProcess.exe", "--platformAmd64", "/out/walk.xml", "/out/walk.xml"],
+        ]
+    );
+}
+
+#[test]
+fn hct_output_is_moved_from_next_to_the_preset() {
+    let converter = Converter::new(ConverterTool::Hct, OutputFormat::SkyrimLE, tool_paths());
+    assert_eq!(
+        command_lines(converter, false),
+        [
+            vec!["/tools/hctStandAloneFilterManager.exe", "/mods/walk.hkx", "-s", "/tools/_SSEtoLE.hko"],
+            vec!["mv", "/tools/filename.hkx", "/out/walk.xml"],
+        ]
+    );
+}
+
+#[test]
+fn unsupported_format_has_no_command() {
+    let converter = Converter::new(ConverterTool::HkxConv, OutputFormat::SkyrimLE, tool_paths());
+    assert!(converter.command_lines(Path::new("/mods/walk.hkx"), Path::new("/out/walk.hkx"), false).is_err());
+}
+
+#[test]
+fn shell_script_quotes_spaces_and_apostrophes() {
+    let line: Vec<OsString> = ["/tools/hkxc.exe", "convert", "/Mod's Anims/walk 1.hkx"].into_iter().map(OsString::from).collect();
+    assert_eq!(command_script(&[line], false), "#!/bin/sh\nset -e\n/tools/hkxc.exe convert '/Mod'\\''s Anims/walk 1.hkx'\n");
+}
+
+#[test]
+fn batch_file_quotes_spaces_and_doubles_percent_signs() {
+    let line: Vec<OsString> = [r"C:\Tools\hkxc.exe", "convert", r"C:\100% Anims\walk 1.hkx"].into_iter().map(OsString::from).collect();
+    assert_eq!(
+        command_script(&[line], true),
+        "@echo off\r\nchcp 65001 >nul\r\nC:\\Tools\\hkxc.exe convert \"C:\\100%% Anims\\walk 1.hkx\" || exit /b 1\r\n"
+    );
+}
@@ -0,0 +1,29 @@
+//! Stand-in tools shared by the integration tests
+#![cfg(unix)]
+#![allow(dead_code)]
+
+use composite_hkx_conversion::ToolPaths;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// Writes an executable `#!/bin/sh` script running `body` to `path`
+pub fn fake_tool(path: &Path, body: &str) -> PathBuf {
+    fs::write(path, format!("#!/bin/sh\n{}", body)).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_path_buf()
+}
+
+/// Tool paths whose hkxc is a script running `body`, called as `hkxc convert --input IN --output OUT --format F`
+pub fn fake_hkxc(dir: &Path, body: &str) -> ToolPaths {
+    ToolPaths { hkxc: fake_tool(&dir.join("hkxc"), body), ..ToolPaths::default() }
+}
+
+/// Tool paths with a Windows hkxc.exe, and a stand-in for wine running `body`.
+/// Wine gets hkxc.exe's path as its first argument, then hkxc's own arguments.
+pub fn wine_hkxc(dir: &Path, body: &str) -> (ToolPaths, PathBuf) {
+    let hkxc = dir.join("hkxc.exe");
+    fs::write(&hkxc, b"MZ").unwrap();
+    let wine = fake_tool(&dir.join("wine"), body);
+    (ToolPaths { hkxc, ..ToolPaths::default() }, wine)
+}
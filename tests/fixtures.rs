@@ -0,0 +1,98 @@
+use composite_hkx_conversion::{get_output_path, reformat_xml, ConverterTool, OutputFormat, OutputLayout};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A packfile with only an empty root level container, the smallest file every tool accepts
+fn xml_fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("root_level_container.xml")
+}
+
+/// Where a fixture conversion writes: a folder per format and the tool as suffix
+fn fixture_output(input: &Path, dir: &Path, tool: ConverterTool, format: OutputFormat) -> PathBuf {
+    let (output_folder, suffix, custom_extensions) = (dir.join(format.folder_name()), tool.label().to_lowercase(), HashMap::new());
+    let layout = OutputLayout { output_suffix: &suffix, ..OutputLayout::new(&output_folder, &custom_extensions) };
+    get_output_path(input, format, &layout).unwrap()
+}
+
+#[test]
+fn xml_fixture_is_a_formatted_packfile() {
+    let contents = std::fs::read(xml_fixture()).unwrap();
+    assert!(String::from_utf8_lossy(&contents).contains("hkRootLevelContainer"));
+    assert_eq!(reformat_xml(&contents).unwrap(), contents);
+}
+
+#[test]
+fn xml_fixture_is_detected_as_xml() {
+    assert_eq!(OutputFormat::detect_from_file(&xml_fixture()), Some(OutputFormat::Xml));
+}
+
+#[test]
+fn fixture_outputs_go_to_a_folder_per_format_named_after_the_tool() {
+    let output = fixture_output(&xml_fixture(), Path::new("/out"), ConverterTool::HkxC, OutputFormat::SkyrimLE);
+    assert_eq!(output, PathBuf::from("/out/SkyrimLE/root_level_container_hkxc.hkx"));
+}
+
+#[cfg(windows)]
+mod tools {
+    use super::*;
+    use composite_hkx_conversion::{Converter, ToolPaths};
+
+    /// Tools run against the fixtures. KF needs a skeleton and an animation, HCT needs the Havok
+    /// Content Tools runtime and HavokBehaviorPostProcess needs a behavior graph, so they aren't covered.
+    const FIXTURE_TOOLS: &[ConverterTool] = &[ConverterTool::HkxCmd, ConverterTool::HkxC, ConverterTool::HkxConv];
+
+    /// Formats the fixtures are converted to
+    const FIXTURE_FORMATS: &[OutputFormat] = &[OutputFormat::SkyrimSE, OutputFormat::SkyrimLE, OutputFormat::Xml];
+
+    /// Tool and format combinations to run for an input, skipping same-format copies
+    fn fixture_combinations(input: &Path) -> Vec<(ConverterTool, OutputFormat)> {
+        let input_is_xml = input.extension().is_some_and(|ext| ext == "xml");
+        FIXTURE_TOOLS
+            .iter()
+            .flat_map(|&tool| FIXTURE_FORMATS.iter().map(move |&format| (tool, format)))
+            .filter(|&(tool, format)| tool.check_conversion(input, format).is_ok())
+            .filter(|&(_, format)| !(input_is_xml && format == OutputFormat::Xml))
+            .collect()
+    }
+
+    /// Convert `input` with every fixture combination, checking each output exists and isn't empty
+    /// and is detected as the format it was converted to
+    async fn convert_all(tool_paths: &ToolPaths, input: &Path, output_dir: &Path) -> Vec<PathBuf> {
+        let mut outputs = Vec::new();
+        for (tool, format) in fixture_combinations(input) {
+            let output = fixture_output(input, output_dir, tool, format);
+            std::fs::create_dir_all(output.parent().unwrap()).unwrap();
+            Converter::new(tool, format, tool_paths.clone())
+                .convert(input, &output)
+                .await
+                .unwrap_or_else(|e| panic!("{} → {} failed: {:#}", tool.label(), format.label(), e));
+            let len = std::fs::metadata(&output).map(|metadata| metadata.len()).unwrap_or(0);
+            assert!(len > 0, "{} → {} wrote no output to {:?}", tool.label(), format.label(), output);
+            assert_eq!(OutputFormat::detect_from_file(&output), Some(format), "{} → {} wrote another format", tool.label(), format.label());
+            outputs.push(output);
+        }
+        outputs
+    }
+
+    #[tokio::test]
+    async fn xml_fixture_converts_with_every_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool_paths = ToolPaths::extract_to(&dir.path().join("tools")).unwrap();
+        let outputs = convert_all(&tool_paths, &xml_fixture(), &dir.path().join("out")).await;
+        assert!(!outputs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn hkx_made_from_fixture_converts_with_every_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool_paths = ToolPaths::extract_to(&dir.path().join("tools")).unwrap();
+        let hkx = dir.path().join("root_level_container.hkx");
+        Converter::new(ConverterTool::HkxC, OutputFormat::SkyrimSE, tool_paths.clone())
+            .convert(&xml_fixture(), &hkx)
+            .await
+            .unwrap();
+
+        let outputs = convert_all(&tool_paths, &hkx, &dir.path().join("out")).await;
+        assert!(outputs.iter().any(|output| output.extension().is_some_and(|ext| ext == "xml")));
+    }
+}
@@ -0,0 +1,41 @@
+use composite_hkx_conversion::is_framework_animation;
+use std::path::Path;
+
+fn is_animation(path: &str) -> bool {
+    is_framework_animation(Path::new(path), Path::new("/mods/MyMod"))
+}
+
+#[test]
+fn files_in_animations_folders_are_animations() {
+    assert!(is_animation("/mods/MyMod/meshes/actors/character/animations/MyMod/walk.hkx"));
+    assert!(is_animation("/mods/MyMod/meshes/actors/wolf/Animations/MyMod/howl.hkx"));
+}
+
+#[test]
+fn files_outside_animations_folders_are_skipped() {
+    assert!(!is_animation("/mods/MyMod/meshes/actors/character/walk.hkx"));
+    assert!(!is_animation("/mods/MyMod/meshes/armor/boots.hkx"));
+}
+
+#[test]
+fn behavior_and_character_files_are_skipped() {
+    assert!(!is_animation("/mods/MyMod/meshes/actors/character/behaviors/0_master.hkx"));
+    assert!(!is_animation("/mods/MyMod/meshes/actors/character/characters/defaultmale.hkx"));
+    assert!(!is_animation("/mods/MyMod/meshes/actors/character/character assets/skeleton.hkx"));
+    assert!(!is_animation("/mods/MyMod/Nemesis_Engine/mod/mymd/animations/walk.hkx"));
+}
+
+#[test]
+fn fnis_and_nemesis_files_are_skipped() {
+    assert!(!is_animation("/mods/MyMod/meshes/actors/character/animations/MyMod/FNIS_MyMod_List.txt"));
+    assert!(!is_animation("/mods/MyMod/meshes/actors/character/animations/MyMod/fnis_mymod_behavior.hkx"));
+}
+
+#[test]
+fn root_inside_animations_folder() {
+    let root = Path::new("/mods/MyMod/meshes/actors/character/animations/MyMod");
+    assert!(is_framework_animation(&root.join("walk.hkx"), root));
+    // Folders above the root aren't checked for framework folders
+    let root = Path::new("/mods/Behaviors/meshes");
+    assert!(is_framework_animation(&root.join("actors/character/animations/walk.hkx"), root));
+}
@@ -0,0 +1,83 @@
+#[cfg(unix)]
+mod common;
+
+use composite_hkx_conversion::{Converter, ConverterTool, OutputFormat, ToolPaths};
+use std::fs;
+use std::path::Path;
+
+/// Tool paths pointing into `dir`, with only the HCT executable present
+fn tool_paths_without_dll(dir: &Path) -> ToolPaths {
+    let executable = dir.join("hctStandAloneFilterManager.exe");
+    fs::write(&executable, b"").unwrap();
+    ToolPaths {
+        hct_standalone_filter_manager: executable,
+        hct_filter_manager_dll: dir.join("hctFilterManager.dll"),
+        sse_to_le_hko: dir.join("_SSEtoLE.hko"),
+        ..ToolPaths::default()
+    }
+}
+
+#[tokio::test]
+async fn missing_filter_manager_dll_is_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let converter = Converter::new(ConverterTool::Hct, OutputFormat::SkyrimLE, tool_paths_without_dll(dir.path()))
+        .with_temp_dir(Some(dir.path().join("temp")));
+
+    let error = converter
+        .convert(&dir.path().join("input.hkx"), &dir.path().join("output.hkx"))
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("hctFilterManager.dll is missing"),
+        "unexpected error: {:#}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn missing_hct_executable_is_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut tool_paths = tool_paths_without_dll(dir.path());
+    fs::remove_file(&tool_paths.hct_standalone_filter_manager).unwrap();
+    tool_paths.hct_standalone_filter_manager = dir.path().join("missing.exe");
+    let converter = Converter::new(ConverterTool::Hct, OutputFormat::SkyrimLE, tool_paths)
+        .with_temp_dir(Some(dir.path().join("temp")));
+
+    let error = converter
+        .convert(&dir.path().join("input.hkx"), &dir.path().join("output.hkx"))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("HCT executable is missing"), "unexpected error: {:#}", error);
+}
+
+/// Concurrent HCT conversions must each get their own working folder, since HCT always
+/// writes `filename.hkx` there
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_conversions_do_not_share_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let tool_paths = tool_paths_without_dll(dir.path());
+    // Stand-in for HCT: write the input to the fixed output name in the working folder
+    common::fake_tool(&tool_paths.hct_standalone_filter_manager, "sleep 0.2\ncp \"$1\" filename.hkx\n");
+    fs::write(&tool_paths.hct_filter_manager_dll, b"").unwrap();
+    fs::write(&tool_paths.sse_to_le_hko, b"").unwrap();
+
+    let tasks: Vec<_> = (0..8)
+        .map(|index| {
+            let input = dir.path().join(format!("input_{}.hkx", index));
+            let output = dir.path().join(format!("output_{}.hkx", index));
+            fs::write(&input, format!("animation {}", index)).unwrap();
+            let converter = Converter::new(ConverterTool::Hct, OutputFormat::SkyrimLE, tool_paths.clone())
+                .with_temp_dir(Some(dir.path().join("temp")));
+            tokio::spawn(async move { converter.convert(&input, &output).await })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    for index in 0..8 {
+        let output = fs::read_to_string(dir.path().join(format!("output_{}.hkx", index))).unwrap();
+        assert_eq!(output, format!("animation {}", index));
+    }
+}
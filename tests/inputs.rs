@@ -0,0 +1,26 @@
+use composite_hkx_conversion::check_input_readable;
+use std::fs;
+
+#[test]
+fn non_empty_input_is_readable() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("walk.hkx");
+    fs::write(&path, b"hkx").unwrap();
+    assert!(check_input_readable(&path).is_ok());
+}
+
+#[test]
+fn empty_input_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("walk.hkx");
+    fs::write(&path, b"").unwrap();
+    let error = check_input_readable(&path).unwrap_err();
+    assert!(error.to_string().contains("is empty (0 bytes)"), "unexpected error: {:#}", error);
+}
+
+#[test]
+fn missing_input_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let error = check_input_readable(&dir.path().join("walk.hkx")).unwrap_err();
+    assert!(error.to_string().contains("can't be found"), "unexpected error: {:#}", error);
+}
@@ -0,0 +1,17 @@
+use composite_hkx_conversion::{log_level, set_log_level, LogLevel};
+
+#[test]
+fn log_level_keys_round_trip() {
+    for level in [LogLevel::Quiet, LogLevel::Normal, LogLevel::Verbose] {
+        assert_eq!(LogLevel::from_key(level.key()), Some(level));
+    }
+    assert_eq!(LogLevel::from_key("loud"), None);
+}
+
+#[test]
+fn set_log_level_is_read_back() {
+    assert_eq!(log_level(), LogLevel::Normal);
+    set_log_level(LogLevel::Verbose);
+    assert_eq!(log_level(), LogLevel::Verbose);
+    assert!(log_level() > LogLevel::Quiet);
+}
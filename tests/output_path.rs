@@ -0,0 +1,272 @@
+use composite_hkx_conversion::{
+    get_output_path, get_templated_output_path, validate_custom_extension, validate_output_template, validate_suffix_separator, ConverterTool,
+    FilenameCase, OutputFormat, OutputLayout,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+fn output_path(
+    input: &str,
+    output_folder: &str,
+    suffix: &str,
+    format: OutputFormat,
+    custom_extensions: &HashMap<OutputFormat, String>,
+    base_folder: Option<&str>,
+    flatten_output: bool,
+) -> Option<PathBuf> {
+    let layout = OutputLayout {
+        output_suffix: suffix,
+        base_folder: base_folder.map(Path::new),
+        flatten_output,
+        ..OutputLayout::new(Path::new(output_folder), custom_extensions)
+    };
+    get_output_path(Path::new(input), format, &layout)
+}
+
+#[test]
+fn single_file_without_base_folder() {
+    let path = output_path("/mods/anims/walk.hkx", "/out", "", OutputFormat::Xml, &HashMap::new(), None, false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.xml")));
+}
+
+#[test]
+fn nested_base_folder_keeps_subfolders() {
+    let path = output_path(
+        "/mods/anims/male/combat/attack.hkx",
+        "/out",
+        "",
+        OutputFormat::SkyrimSE,
+        &HashMap::new(),
+        Some("/mods/anims"),
+        false,
+    );
+    assert_eq!(path, Some(PathBuf::from("/out/male/combat/attack.hkx")));
+}
+
+#[test]
+fn input_directly_in_base_folder() {
+    let path = output_path("/mods/anims/walk.hkx", "/out", "", OutputFormat::Xml, &HashMap::new(), Some("/mods/anims"), false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.xml")));
+}
+
+#[test]
+fn base_folder_with_trailing_separator() {
+    let path = output_path("/mods/anims/male/walk.hkx", "/out", "", OutputFormat::Xml, &HashMap::new(), Some("/mods/anims/"), false);
+    assert_eq!(path, Some(PathBuf::from("/out/male/walk.xml")));
+}
+
+#[test]
+fn input_outside_base_folder_goes_to_output_folder() {
+    let path = output_path("/other/place/walk.hkx", "/out", "", OutputFormat::Xml, &HashMap::new(), Some("/mods/anims"), false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.xml")));
+}
+
+#[test]
+fn base_folder_matches_whole_components_only() {
+    // "/mods/anims2" starts with the text "/mods/anims" but isn't inside it
+    let path = output_path("/mods/anims2/walk.hkx", "/out", "", OutputFormat::Xml, &HashMap::new(), Some("/mods/anims"), false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.xml")));
+}
+
+#[test]
+fn relative_input_inside_absolute_base_folder() {
+    let base_folder = std::env::current_dir().unwrap().join("anims");
+    let custom_extensions = HashMap::new();
+    let layout = OutputLayout { base_folder: Some(&base_folder), ..OutputLayout::new(Path::new("/out"), &custom_extensions) };
+    let path = get_output_path(Path::new("anims/male/walk.hkx"), OutputFormat::Xml, &layout);
+    assert_eq!(path, Some(PathBuf::from("/out/male/walk.xml")));
+}
+
+#[test]
+fn input_without_parent_folder() {
+    let path = output_path("walk.hkx", "/out", "", OutputFormat::Xml, &HashMap::new(), Some("."), false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.xml")));
+}
+
+#[test]
+fn flatten_output_ignores_base_folder() {
+    let path = output_path(
+        "/mods/anims/male/combat/attack.hkx",
+        "/out",
+        "",
+        OutputFormat::Xml,
+        &HashMap::new(),
+        Some("/mods/anims"),
+        true,
+    );
+    assert_eq!(path, Some(PathBuf::from("/out/attack.xml")));
+}
+
+#[test]
+fn empty_suffix_is_not_appended() {
+    let path = output_path("/mods/walk.hkx", "/out", "", OutputFormat::SkyrimLE, &HashMap::new(), None, false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.hkx")));
+}
+
+#[test]
+fn suffix_is_appended_with_underscore() {
+    let path = output_path("/mods/walk.hkx", "/out", "le", OutputFormat::SkyrimLE, &HashMap::new(), None, false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk_le.hkx")));
+}
+
+#[test]
+fn custom_extension_for_output_format() {
+    let custom_extensions = HashMap::from([(OutputFormat::Xml, "txt".to_string())]);
+    let path = output_path("/mods/walk.hkx", "/out", "", OutputFormat::Xml, &custom_extensions, None, false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.txt")));
+}
+
+#[test]
+fn custom_extension_for_other_format_is_ignored() {
+    let custom_extensions = HashMap::from([(OutputFormat::Xml, "txt".to_string())]);
+    let path = output_path("/mods/walk.xml", "/out", "", OutputFormat::SkyrimSE, &custom_extensions, None, false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.hkx")));
+}
+
+#[test]
+fn custom_extension_leading_dot_is_ignored() {
+    let custom_extensions = HashMap::from([(OutputFormat::Xml, ".txt".to_string())]);
+    let path = output_path("/mods/walk.hkx", "/out", "", OutputFormat::Xml, &custom_extensions, None, false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.txt")));
+}
+
+#[test]
+fn custom_extension_of_only_a_dot_uses_default() {
+    let custom_extensions = HashMap::from([(OutputFormat::Kf, ".".to_string())]);
+    let path = output_path("/mods/walk.hkx", "/out", "", OutputFormat::Kf, &custom_extensions, None, false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.kf")));
+}
+
+#[test]
+fn custom_extension_with_separator_uses_default() {
+    let custom_extensions = HashMap::from([(OutputFormat::Xml, ".hkx/".to_string())]);
+    let path = output_path("/mods/walk.hkx", "/out", "", OutputFormat::Xml, &custom_extensions, None, false);
+    assert_eq!(path, Some(PathBuf::from("/out/walk.xml")));
+}
+
+#[test]
+fn custom_extension_validation() {
+    for valid in ["txt", ".hkx", "hkx.bak"] {
+        assert!(validate_custom_extension(valid).is_ok(), "{:?} was rejected", valid);
+    }
+    for invalid in ["hkx/", "..\\hkx", "h kx", "hkx?", "hkx.", "a:b"] {
+        assert!(validate_custom_extension(invalid).is_err(), "{:?} was accepted", invalid);
+    }
+}
+
+#[test]
+fn lowercase_applies_to_file_name_only() {
+    let custom_extensions = HashMap::new();
+    let layout = OutputLayout {
+        output_suffix: "LE",
+        base_folder: Some(Path::new("/Mods/Anims")),
+        filename_case: FilenameCase::Lowercase,
+        ..OutputLayout::new(Path::new("/Out"), &custom_extensions)
+    };
+    let path = get_output_path(Path::new("/Mods/Anims/Male/WalkForward.HKX"), OutputFormat::Xml, &layout);
+    assert_eq!(path, Some(PathBuf::from("/Out/Male/walkforward_le.xml")));
+}
+
+#[test]
+fn uppercase_applies_to_stem_and_extension() {
+    let custom_extensions = HashMap::new();
+    let layout = OutputLayout { filename_case: FilenameCase::Uppercase, ..OutputLayout::new(Path::new("/out"), &custom_extensions) };
+    let path = get_output_path(Path::new("/mods/walk.hkx"), OutputFormat::SkyrimSE, &layout);
+    assert_eq!(path, Some(PathBuf::from("/out/WALK.HKX")));
+}
+
+#[test]
+fn suffix_separator_joins_the_suffix() {
+    let custom_extensions = HashMap::new();
+    let separated = |suffix_separator: &str| {
+        let layout = OutputLayout { output_suffix: "se", suffix_separator, ..OutputLayout::new(Path::new("/out"), &custom_extensions) };
+        get_output_path(Path::new("/mods/walk.hkx"), OutputFormat::Xml, &layout)
+    };
+    assert_eq!(separated("-"), Some(PathBuf::from("/out/walk-se.xml")));
+    assert_eq!(separated(""), Some(PathBuf::from("/out/walkse.xml")));
+    // Separators that would leave the file name fall back to the underscore
+    assert_eq!(separated("/"), Some(PathBuf::from("/out/walk_se.xml")));
+}
+
+#[test]
+fn suffix_separator_validation() {
+    for valid in ["", "_", "-", ".", " ", "__"] {
+        assert!(validate_suffix_separator(valid).is_ok(), "{:?} was rejected", valid);
+    }
+    for invalid in ["/", "\\", ":", "a?", "\n"] {
+        assert!(validate_suffix_separator(invalid).is_err(), "{:?} was accepted", invalid);
+    }
+}
+
+fn templated_output_path(input: &str, template: &str, suffix: &str, base_folder: Option<&str>) -> Option<PathBuf> {
+    let custom_extensions = HashMap::new();
+    let layout = OutputLayout {
+        output_suffix: suffix,
+        base_folder: base_folder.map(Path::new),
+        ..OutputLayout::new(Path::new("/out"), &custom_extensions)
+    };
+    get_templated_output_path(Path::new(input), template, OutputFormat::SkyrimSE, ConverterTool::HkxC, &layout)
+}
+
+#[test]
+fn template_placeholders_are_replaced() {
+    let path = templated_output_path("/mods/anims/male/walk.xml", "{format}/{tool}/{reldir}/{stem}{suffix}.{ext}", "se", Some("/mods/anims"));
+    assert_eq!(path, Some(PathBuf::from("/out/SkyrimSE/hkxc/male/walk_se.hkx")));
+}
+
+#[test]
+fn template_drops_folders_of_blank_placeholders() {
+    let path = templated_output_path("/mods/anims/walk.xml", "{reldir}/{stem}{suffix}.{ext}", "", Some("/mods/anims"));
+    assert_eq!(path, Some(PathBuf::from("/out/walk.hkx")));
+}
+
+#[test]
+fn template_case_applies_to_file_name_only() {
+    let custom_extensions = HashMap::new();
+    let layout = OutputLayout { filename_case: FilenameCase::Uppercase, ..OutputLayout::new(Path::new("/out"), &custom_extensions) };
+    let path = get_templated_output_path(Path::new("/mods/Walk.xml"), "{format}/{stem}.{ext}", OutputFormat::Xml, ConverterTool::HkxC, &layout);
+    assert_eq!(path, Some(PathBuf::from("/out/XML/WALK.XML")));
+}
+
+#[test]
+fn output_template_validation() {
+    for valid in ["{stem}", "{format}/{reldir}/{stem}{suffix}.{ext}", r"meshes\{reldir}\{stem}.hkx"] {
+        assert!(validate_output_template(valid).is_ok(), "{:?} was rejected", valid);
+    }
+    for invalid in ["{name}.{ext}", "{format}/walk.hkx", "{stem", "{stem}}", "/{stem}", "../{stem}", "{stem}/", "{stem}?.hkx"] {
+        assert!(validate_output_template(invalid).is_err(), "{:?} was accepted", invalid);
+    }
+}
+
+#[test]
+fn invalid_template_has_no_output_path() {
+    assert_eq!(templated_output_path("/mods/walk.xml", "../{stem}.{ext}", "", None), None);
+}
+
+#[test]
+fn input_without_file_name() {
+    let path = output_path("/", "/out", "", OutputFormat::Xml, &HashMap::new(), None, false);
+    assert_eq!(path, None);
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_drive_letter_base_folder_is_case_insensitive() {
+    let path = output_path(
+        r"C:\Mods\Anims\male\walk.hkx",
+        r"D:\out",
+        "",
+        OutputFormat::Xml,
+        &HashMap::new(),
+        Some(r"c:\mods\anims"),
+        false,
+    );
+    assert_eq!(path, Some(PathBuf::from(r"D:\out\male\walk.xml")));
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_input_on_other_drive_goes_to_output_folder() {
+    let path = output_path(r"E:\anims\male\walk.hkx", r"D:\out", "", OutputFormat::Xml, &HashMap::new(), Some(r"C:\anims"), false);
+    assert_eq!(path, Some(PathBuf::from(r"D:\out\walk.xml")));
+}
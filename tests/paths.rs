@@ -0,0 +1,165 @@
+use composite_hkx_conversion::{
+    check_writable_folder, create_temp_dir, ensure_absolute_path, file_sha256, get_output_path, is_hidden_file, normalize_listed_path, ConverterTool,
+    temp_dir_in_use, OutputFormat, OutputLayout, TEMP_DIR_LOCK_FILE,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn absolute_path_with_spaces_is_unchanged() {
+    let path = Path::new("/My Mods/Animation Pack/walk forward.hkx");
+    assert_eq!(ensure_absolute_path(path), path);
+}
+
+#[test]
+fn absolute_path_with_unicode_is_unchanged() {
+    let path = Path::new("/Mods/Анимации/步行/ダッシュ.hkx");
+    assert_eq!(ensure_absolute_path(path), path);
+}
+
+#[test]
+fn relative_path_is_joined_with_current_dir() {
+    let cwd = std::env::current_dir().unwrap();
+    assert_eq!(ensure_absolute_path(Path::new("Ánims/walk.hkx")), cwd.join("Ánims").join("walk.hkx"));
+}
+
+#[test]
+fn trailing_separator_is_removed() {
+    assert_eq!(ensure_absolute_path(Path::new("/Mods/Anim Pack/")), PathBuf::from("/Mods/Anim Pack"));
+}
+
+#[test]
+fn current_dir_components_are_removed() {
+    assert_eq!(ensure_absolute_path(Path::new("/Mods/./Anims/./walk.hkx")), PathBuf::from("/Mods/Anims/walk.hkx"));
+}
+
+#[test]
+fn parent_dir_components_are_resolved() {
+    assert_eq!(ensure_absolute_path(Path::new("/Mods/Other/../Anims/walk.hkx")), PathBuf::from("/Mods/Anims/walk.hkx"));
+}
+
+#[test]
+fn parent_dir_never_goes_above_root() {
+    assert_eq!(ensure_absolute_path(Path::new("/../../walk.hkx")), PathBuf::from("/walk.hkx"));
+}
+
+#[test]
+fn relative_path_with_leading_dash_stays_a_path() {
+    // Tools would read a bare "-walk.hkx" as an option
+    let path = ensure_absolute_path(Path::new("-walk.hkx"));
+    assert!(path.is_absolute());
+    assert_eq!(path.file_name().unwrap(), "-walk.hkx");
+}
+
+#[test]
+fn output_path_keeps_unicode_and_spaces() {
+    let custom_extensions = HashMap::new();
+    let layout = OutputLayout {
+        output_suffix: "sé",
+        base_folder: Some(Path::new("/Mods/Пак анимаций/")),
+        ..OutputLayout::new(Path::new("/Out Folder/Ñ"), &custom_extensions)
+    };
+    let path = get_output_path(Path::new("/Mods/Пак анимаций/男性 戦闘/攻撃 1.hkx"), OutputFormat::Xml, &layout);
+    assert_eq!(path, Some(PathBuf::from("/Out Folder/Ñ/男性 戦闘/攻撃 1_sé.xml")));
+}
+
+#[test]
+fn only_unicode_aware_tools_take_non_ascii_paths_directly() {
+    assert!(ConverterTool::HkxC.supports_unicode_paths());
+    assert!(ConverterTool::HkxConv.supports_unicode_paths());
+    assert!(!ConverterTool::HkxCmd.supports_unicode_paths());
+    assert!(!ConverterTool::Hct.supports_unicode_paths());
+    assert!(!ConverterTool::HavokBehaviorPostProcess.supports_unicode_paths());
+}
+
+#[test]
+fn missing_output_folder_is_created_and_left_empty() {
+    let dir = tempfile::tempdir().unwrap();
+    let output_folder = dir.path().join("Out Folder").join("Ñ");
+    check_writable_folder(&output_folder).unwrap();
+    assert!(output_folder.is_dir());
+    assert_eq!(std::fs::read_dir(&output_folder).unwrap().count(), 0);
+}
+
+#[test]
+fn output_folder_that_cannot_be_created_is_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("not a folder");
+    std::fs::write(&file, b"").unwrap();
+    let error = check_writable_folder(&file.join("out")).unwrap_err();
+    assert!(error.to_string().contains("couldn't be created"), "unexpected error: {:#}", error);
+}
+
+#[test]
+fn output_hash_is_sha256_of_contents() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("walk.hkx");
+    std::fs::write(&path, b"abc").unwrap();
+    assert_eq!(file_sha256(&path).unwrap(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+}
+
+#[test]
+fn dot_files_and_clutter_folders_are_hidden() {
+    assert!(is_hidden_file(Path::new("/mods/anims/._walk.hkx")));
+    assert!(is_hidden_file(Path::new("/mods/.git")));
+    assert!(is_hidden_file(Path::new("/mods/__MACOSX")));
+    assert!(!is_hidden_file(Path::new("/mods/anims/walk.hkx")));
+    assert!(!is_hidden_file(Path::new("/mods/anims")));
+}
+
+#[cfg(unix)]
+#[test]
+fn listed_path_with_mixed_separators_is_normalized() {
+    assert_eq!(normalize_listed_path(r"/mods\anims/male\walk.hkx", Path::new("/lists")), PathBuf::from("/mods/anims/male/walk.hkx"));
+}
+
+#[cfg(unix)]
+#[test]
+fn relative_listed_path_keeps_subfolders_below_base_folder() {
+    let input = normalize_listed_path(r"male\combat/attack.hkx", Path::new("/mods/anims"));
+    assert_eq!(input, PathBuf::from("/mods/anims/male/combat/attack.hkx"));
+    let custom_extensions = HashMap::new();
+    let layout = OutputLayout { base_folder: Some(Path::new("/mods/anims")), ..OutputLayout::new(Path::new("/out"), &custom_extensions) };
+    let path = get_output_path(&input, OutputFormat::Xml, &layout);
+    assert_eq!(path, Some(PathBuf::from("/out/male/combat/attack.xml")));
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_listed_path_gets_separators_and_case_from_disk() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("Anims").join("Male")).unwrap();
+    std::fs::write(dir.path().join("Anims").join("Male").join("Walk.hkx"), b"").unwrap();
+    let path = normalize_listed_path("anims/male\\WALK.HKX", dir.path());
+    assert!(path.ends_with(r"Anims\Male\Walk.hkx"), "unexpected path: {:?}", path);
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_drive_path_is_normalized_without_verbatim_prefix() {
+    let path = ensure_absolute_path(Path::new(r"C:\Mods\Ánims\.\male\..\walk.hkx"));
+    assert_eq!(path, PathBuf::from(r"C:\Mods\Ánims\walk.hkx"));
+    assert!(!path.to_string_lossy().starts_with(r"\\?\"));
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_parent_dir_never_goes_above_drive() {
+    assert_eq!(ensure_absolute_path(Path::new(r"C:\..\walk.hkx")), PathBuf::from(r"C:\walk.hkx"));
+}
+
+#[test]
+fn temp_dir_is_in_use_until_dropped() {
+    let base = tempfile::tempdir().unwrap();
+    let dir = create_temp_dir("hkxtools_", Some(base.path())).unwrap();
+    let path = dir.path().to_path_buf();
+    assert!(temp_dir_in_use(&path));
+    drop(dir);
+    assert!(!path.exists());
+
+    // A crashed owner leaves its lock file unlocked
+    let orphan = base.path().join("hkxtools_orphan");
+    std::fs::create_dir(&orphan).unwrap();
+    std::fs::write(orphan.join(TEMP_DIR_LOCK_FILE), b"").unwrap();
+    assert!(!temp_dir_in_use(&orphan));
+}
@@ -0,0 +1,79 @@
+use composite_hkx_conversion::{skeleton_for_input, validate_skeleton_file, SkeletonRule};
+use std::fs;
+use std::path::Path;
+
+/// Minimal packfile header with the given pointer size
+fn packfile_header(pointer_size: u8) -> Vec<u8> {
+    let mut header = vec![0u8; 64];
+    header[16] = pointer_size;
+    header
+}
+
+#[test]
+fn le_and_se_skeletons_are_accepted() {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, pointer_size) in [("skeleton_le.hkx", 4), ("skeleton_se.hkx", 8)] {
+        let path = dir.path().join(name);
+        fs::write(&path, packfile_header(pointer_size)).unwrap();
+        assert!(validate_skeleton_file(&path).is_ok(), "{} was rejected", name);
+    }
+}
+
+#[test]
+fn missing_skeleton_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let error = validate_skeleton_file(&dir.path().join("skeleton.hkx")).unwrap_err();
+    assert!(error.to_string().contains("does not exist"), "unexpected error: {:#}", error);
+}
+
+#[test]
+fn non_hkx_skeleton_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let truncated = dir.path().join("truncated.hkx");
+    fs::write(&truncated, b"not a packfile").unwrap();
+    let error = validate_skeleton_file(&truncated).unwrap_err();
+    assert!(error.to_string().contains("is not a valid HKX file"), "unexpected error: {:#}", error);
+}
+
+#[test]
+fn skeleton_without_hkx_extension_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let xml = dir.path().join("skeleton.xml");
+    fs::write(&xml, b"<hkpackfile/>").unwrap();
+    // A real packfile under another name is still refused
+    let renamed = dir.path().join("skeleton.hkx.bak");
+    fs::write(&renamed, packfile_header(8)).unwrap();
+
+    for path in [xml, renamed] {
+        let error = validate_skeleton_file(&path).unwrap_err();
+        assert!(error.to_string().contains("is not an .hkx file"), "unexpected error: {:#}", error);
+    }
+}
+
+fn rule(folder: &str, skeleton_file: &str) -> SkeletonRule {
+    SkeletonRule {
+        folder: folder.into(),
+        skeleton_file: skeleton_file.into(),
+    }
+}
+
+#[test]
+fn deepest_matching_folder_rule_wins() {
+    let rules = [
+        rule("/mods/anims", "/skeletons/character.hkx"),
+        rule("/mods/anims/creatures/wolf", "/skeletons/wolf.hkx"),
+    ];
+    let skeleton = skeleton_for_input(Path::new("/mods/anims/creatures/wolf/run.hkx"), &rules, None);
+    assert_eq!(skeleton, Some(Path::new("/skeletons/wolf.hkx")));
+    let skeleton = skeleton_for_input(Path::new("/mods/anims/male/walk.hkx"), &rules, None);
+    assert_eq!(skeleton, Some(Path::new("/skeletons/character.hkx")));
+}
+
+#[test]
+fn unmatched_input_falls_back_to_global_skeleton() {
+    // "/mods/wolf2" starts with the text "/mods/wolf" but isn't inside it
+    let rules = [rule("/mods/wolf", "/skeletons/wolf.hkx")];
+    let fallback = Path::new("/skeletons/character.hkx");
+    assert_eq!(skeleton_for_input(Path::new("/mods/wolf2/run.hkx"), &rules, Some(fallback)), Some(fallback));
+    assert_eq!(skeleton_for_input(Path::new("/mods/wolf2/run.hkx"), &rules, None), None);
+}
@@ -0,0 +1,26 @@
+#![cfg(unix)]
+
+mod common;
+
+use composite_hkx_conversion::{Converter, ConverterTool, OutputFormat, ToolTimedOut};
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn hung_tool_is_killed_after_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let tool_paths = common::fake_hkxc(dir.path(), "sleep 30\n");
+
+    let started_at = Instant::now();
+    let error = Converter::new(ConverterTool::HkxC, OutputFormat::Xml, tool_paths)
+        .with_timeout(Some(Duration::from_millis(200)))
+        .convert(&dir.path().join("input.hkx"), &dir.path().join("output.xml"))
+        .await
+        .unwrap_err();
+
+    assert!(started_at.elapsed() < Duration::from_secs(10), "tool was not stopped");
+    assert!(
+        error.chain().any(|cause| cause.is::<ToolTimedOut>()),
+        "unexpected error: {:#}",
+        error
+    );
+}
@@ -0,0 +1,219 @@
+#[cfg(unix)]
+mod common;
+
+use composite_hkx_conversion::{bundled_tools, overwrite_file, partial_output_path, Converter, ConverterTool, OutputFormat, ToolPaths};
+use std::fs;
+
+#[test]
+fn complete_tools_folder_is_used_in_place() {
+    let dir = tempfile::tempdir().unwrap();
+    for tool in bundled_tools() {
+        fs::write(dir.path().join(tool.file_name), b"").unwrap();
+    }
+    let tool_paths = ToolPaths::from_dir(dir.path()).unwrap();
+    assert_eq!(tool_paths.executable(ConverterTool::HkxC), dir.path().join("hkxc.exe"));
+    assert_eq!(tool_paths.sse_to_le_hko, dir.path().join("_SSEtoLE.hko"));
+}
+
+#[test]
+fn cached_tools_are_only_rewritten_when_changed() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache_dir = dir.path().join("cache");
+    let tool_paths = ToolPaths::extract_cached(&cache_dir).unwrap();
+    let bundled_hkxc = fs::read(&tool_paths.hkxc).unwrap();
+
+    // Backdate an intact tool to see whether it gets written again
+    let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+    fs::File::options().write(true).open(&tool_paths.hkxcmd).unwrap().set_modified(old).unwrap();
+    fs::write(&tool_paths.hkxc, b"damaged").unwrap();
+
+    ToolPaths::extract_cached(&cache_dir).unwrap();
+    assert_eq!(fs::metadata(&tool_paths.hkxcmd).unwrap().modified().unwrap(), old);
+    assert_eq!(fs::read(&tool_paths.hkxc).unwrap(), bundled_hkxc);
+}
+
+#[test]
+fn incomplete_tools_folder_names_missing_files() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("hkxcmd.exe"), b"").unwrap();
+    let error = ToolPaths::from_dir(dir.path()).unwrap_err();
+    assert!(error.to_string().contains("hkxc.exe"), "unexpected error: {:#}", error);
+    assert!(!error.to_string().contains("hkxcmd.exe"), "unexpected error: {:#}", error);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn windows_tool_without_wine_says_why_it_cannot_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let hkxc = dir.path().join("hkxc.exe");
+    fs::write(&hkxc, b"MZ").unwrap();
+    let tool_paths = ToolPaths { hkxc, ..ToolPaths::default() };
+    let error = Converter::new(ConverterTool::HkxC, OutputFormat::Xml, tool_paths)
+        .convert(&dir.path().join("walk.hkx"), &dir.path().join("walk.xml"))
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", error).contains("require Windows or Wine"), "unexpected error: {:#}", error);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn wine_runs_the_tool_executable() {
+    let dir = tempfile::tempdir().unwrap();
+    // Stand-in for wine: record the command line it was given
+    let (tool_paths, wine) =
+        common::wine_hkxc(dir.path(), &format!("echo \"$@\" > {:?}\n", dir.path().join("args.txt")));
+    let hkxc = tool_paths.hkxc.clone();
+    let _ = Converter::new(ConverterTool::HkxC, OutputFormat::Xml, tool_paths)
+        .with_wine(Some(wine))
+        .convert(&dir.path().join("walk.hkx"), &dir.path().join("walk.xml"))
+        .await;
+    let args = fs::read_to_string(dir.path().join("args.txt")).unwrap();
+    assert!(args.starts_with(&format!("{} convert", hkxc.display())), "unexpected arguments: {}", args);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn tool_check_reports_the_help_output() {
+    let dir = tempfile::tempdir().unwrap();
+    // Stand-in for wine that prints the arguments it was given and exits like a usage message
+    let (tool_paths, wine) = common::wine_hkxc(dir.path(), "echo \"usage: $2\"\nexit 2\n");
+    let output = Converter::new(ConverterTool::HkxC, OutputFormat::Xml, tool_paths)
+        .with_wine(Some(wine))
+        .check_tool()
+        .await
+        .unwrap();
+    assert_eq!(output, "Exit code 2\nusage: --help");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn tool_check_fails_when_the_tool_cannot_start() {
+    let dir = tempfile::tempdir().unwrap();
+    let tool_paths = ToolPaths { hkxconv: dir.path().join("hkxconv.exe"), ..ToolPaths::default() };
+    let result = Converter::new(ConverterTool::HkxConv, OutputFormat::Xml, tool_paths)
+        .with_wine(Some(dir.path().join("no-wine")))
+        .check_tool()
+        .await;
+    assert!(result.is_err());
+}
+
+/// hkxc.exe and a stand-in for wine that writes "converted" to hkxc's output, exiting with `exit_code`
+#[cfg(unix)]
+fn fake_hkxc(dir: &std::path::Path, exit_code: i32) -> (ToolPaths, std::path::PathBuf) {
+    // Arguments: hkxc.exe convert --input <input> --output <output> --format <format>
+    common::wine_hkxc(dir, &format!("echo converted > \"$6\"\nexit {}\n", exit_code))
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn atomic_output_is_renamed_into_place() {
+    let dir = tempfile::tempdir().unwrap();
+    let (tool_paths, wine) = fake_hkxc(dir.path(), 0);
+    let (input, output) = (dir.path().join("walk.hkx"), dir.path().join("walk.xml"));
+    fs::write(&input, b"hkx").unwrap();
+    fs::write(&output, b"old").unwrap();
+
+    Converter::new(ConverterTool::HkxC, OutputFormat::Xml, tool_paths)
+        .with_wine(Some(wine))
+        .with_atomic_output(true)
+        .convert(&input, &output)
+        .await
+        .unwrap();
+    assert_eq!(fs::read_to_string(&output).unwrap(), "converted\n");
+    assert!(!partial_output_path(&output).exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn failed_atomic_conversion_keeps_the_old_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let (tool_paths, wine) = fake_hkxc(dir.path(), 1);
+    let (input, output) = (dir.path().join("walk.hkx"), dir.path().join("walk.xml"));
+    fs::write(&input, b"hkx").unwrap();
+    fs::write(&output, b"old").unwrap();
+
+    let result = Converter::new(ConverterTool::HkxC, OutputFormat::Xml, tool_paths)
+        .with_wine(Some(wine))
+        .with_atomic_output(true)
+        .convert(&input, &output)
+        .await;
+    assert!(result.is_err());
+    assert_eq!(fs::read_to_string(&output).unwrap(), "old");
+    assert!(!partial_output_path(&output).exists());
+}
+
+#[test]
+fn partial_output_keeps_the_extension() {
+    assert_eq!(
+        partial_output_path(std::path::Path::new("/out/walk.hkx")),
+        std::path::PathBuf::from("/out/~walk.partial.hkx")
+    );
+}
+
+/// Marks `path` read-only, like files from a protected install
+fn set_read_only(path: &std::path::Path) {
+    let mut permissions = fs::metadata(path).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(path, permissions).unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn read_only_source_gives_a_writable_post_processed_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let hbpp = dir.path().join("HavokBehaviorPostProcess.exe");
+    fs::write(&hbpp, b"MZ").unwrap();
+    // Arguments: HavokBehaviorPostProcess.exe --platformAmd64 <output> <output>, modified in place
+    let wine = common::fake_tool(&dir.path().join("wine"), "echo processed >> \"$4\"\n");
+    let (input, output) = (dir.path().join("walk.hkx"), dir.path().join("out.hkx"));
+    fs::write(&input, b"hkx").unwrap();
+    set_read_only(&input);
+
+    let tool_paths = ToolPaths { havok_behavior_post_process: hbpp, ..ToolPaths::default() };
+    Converter::new(ConverterTool::HavokBehaviorPostProcess, OutputFormat::SkyrimSE, tool_paths)
+        .with_wine(Some(wine))
+        .convert(&input, &output)
+        .await
+        .unwrap();
+    assert!(!fs::metadata(&output).unwrap().permissions().readonly());
+    assert!(fs::metadata(&input).unwrap().permissions().readonly());
+}
+
+#[cfg(windows)]
+#[tokio::test]
+async fn windows_read_only_source_gives_a_writable_post_processed_output() {
+    let dir = tempfile::tempdir().unwrap();
+    // Batch file standing in for the tool, appending to the output it's given like a real in-place edit
+    let hbpp = dir.path().join("HavokBehaviorPostProcess.bat");
+    fs::write(&hbpp, "@echo off\r\necho processed>> %3\r\n").unwrap();
+    let (input, output) = (dir.path().join("walk.hkx"), dir.path().join("out.hkx"));
+    fs::write(&input, b"hkx").unwrap();
+    set_read_only(&input);
+
+    let tool_paths = ToolPaths { havok_behavior_post_process: hbpp, ..ToolPaths::default() };
+    Converter::new(ConverterTool::HavokBehaviorPostProcess, OutputFormat::SkyrimSE, tool_paths)
+        .convert(&input, &output)
+        .await
+        .unwrap();
+    assert!(!fs::metadata(&output).unwrap().permissions().readonly());
+    assert!(fs::read_to_string(&output).unwrap().contains("processed"));
+    assert!(fs::metadata(&input).unwrap().permissions().readonly());
+    // Let the temp folder be removed
+    let mut permissions = fs::metadata(&input).unwrap().permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(&input, permissions).unwrap();
+}
+
+#[test]
+fn overwritten_read_only_file_stays_read_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let (recompiled, source) = (dir.path().join("recompiled.hkx"), dir.path().join("walk.hkx"));
+    fs::write(&recompiled, b"new").unwrap();
+    fs::write(&source, b"old").unwrap();
+    set_read_only(&source);
+
+    overwrite_file(&recompiled, &source).unwrap();
+    assert_eq!(fs::read(&source).unwrap(), b"new");
+    assert!(fs::metadata(&source).unwrap().permissions().readonly());
+}
@@ -0,0 +1,71 @@
+use composite_hkx_conversion::{bundled_tools, is_newer_version, parse_latest_release, ReleaseInfo};
+use std::collections::HashSet;
+
+#[test]
+fn newer_patch_minor_and_major_versions() {
+    assert!(is_newer_version("0.1.1", "0.1.0"));
+    assert!(is_newer_version("0.2.0", "0.1.9"));
+    assert!(is_newer_version("1.0.0", "0.9.9"));
+}
+
+#[test]
+fn same_or_older_version_is_not_newer() {
+    assert!(!is_newer_version("0.1.0", "0.1.0"));
+    assert!(!is_newer_version("0.1.0", "0.2.0"));
+}
+
+#[test]
+fn version_numbers_compare_numerically() {
+    assert!(is_newer_version("0.10.0", "0.9.0"));
+}
+
+#[test]
+fn leading_v_and_suffixes_are_ignored() {
+    assert!(is_newer_version("v0.2.0", "0.1.0"));
+    assert!(!is_newer_version("V0.1.0-beta", "0.1.0"));
+    assert!(!is_newer_version("0.1.0+build5", "0.1.0"));
+}
+
+#[test]
+fn missing_version_parts_count_as_zero() {
+    assert!(!is_newer_version("0.2", "0.2.0"));
+    assert!(is_newer_version("0.2.1", "0.2"));
+}
+
+#[test]
+fn parse_release_tag() {
+    let body = r#"{"url":"https://api.github.com/x","tag_name": "v0.3.0","name":"Release 0.3.0","author":{"html_url":"https://github.com/someone"}}"#;
+    assert_eq!(
+        parse_latest_release(body),
+        Some(ReleaseInfo {
+            version: "v0.3.0".to_string(),
+            url: "https://github.com/beefclot/composite-hkxtools/releases/tag/v0.3.0".to_string(),
+        })
+    );
+}
+
+#[test]
+fn parse_release_tag_after_escaped_quotes() {
+    let body = r#"{"name":"The \"tag_name\": \"v9\" release","body":"x","tag_name":"v0.4.0"}"#;
+    assert_eq!(parse_latest_release(body).map(|release| release.version), Some("v0.4.0".to_string()));
+}
+
+#[test]
+fn parse_release_without_tag() {
+    assert_eq!(parse_latest_release(r#"{"message":"Not Found"}"#), None);
+    assert_eq!(parse_latest_release(r#"{"tag_name":""}"#), None);
+}
+
+#[test]
+fn bundled_tools_have_unique_names_and_sha256_hashes() {
+    let tools = bundled_tools();
+    assert_eq!(tools.len(), 7);
+
+    let names: HashSet<_> = tools.iter().map(|tool| tool.file_name).collect();
+    assert_eq!(names.len(), tools.len());
+    for tool in &tools {
+        assert!(tool.size > 0, "{} is empty", tool.file_name);
+        assert_eq!(tool.sha256.len(), 64);
+        assert!(tool.sha256.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+    }
+}
@@ -0,0 +1,38 @@
+#![cfg(unix)]
+
+mod common;
+
+use composite_hkx_conversion::{Converter, ConverterTool, OutputFormat, ToolPaths};
+use std::fs;
+use std::path::Path;
+
+/// Stand-in hkxc that writes its arguments to the output file
+fn argument_echoing_hkxc(dir: &Path) -> ToolPaths {
+    common::fake_hkxc(dir, "echo \"$@\" > \"$5\"\n")
+}
+
+async fn hkxc_arguments(version_override: Option<&str>) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("output.hkx");
+    Converter::new(ConverterTool::HkxC, OutputFormat::SkyrimSE, argument_echoing_hkxc(dir.path()))
+        .with_version_override(version_override.map(str::to_string))
+        .convert(&dir.path().join("input.hkx"), &output)
+        .await
+        .unwrap();
+    fs::read_to_string(output).unwrap()
+}
+
+#[tokio::test]
+async fn output_format_maps_to_format_argument() {
+    assert!(hkxc_arguments(None).await.trim_end().ends_with("--format amd64"));
+}
+
+#[tokio::test]
+async fn version_override_replaces_format_argument() {
+    assert!(hkxc_arguments(Some(" hkx_2010 ")).await.trim_end().ends_with("--format hkx_2010"));
+}
+
+#[tokio::test]
+async fn blank_version_override_is_ignored() {
+    assert!(hkxc_arguments(Some("  ")).await.trim_end().ends_with("--format amd64"));
+}
@@ -0,0 +1,94 @@
+use composite_hkx_conversion::{reformat_xml, xml_spans, XmlSpan};
+
+fn reformat(xml: &str) -> String {
+    String::from_utf8(reformat_xml(xml.as_bytes()).unwrap()).unwrap()
+}
+
+#[test]
+fn elements_are_indented_with_tabs() {
+    let xml = r##"<?xml version="1.0" encoding="ascii"?>
+<hkpackfile classversion="8"><hksection name="__data__">
+    <hkobject name="#0001" class="hkRootLevelContainer"><hkparam name="namedVariants" numelements="0"></hkparam></hkobject>
+</hksection></hkpackfile>"##;
+    let expected = "<?xml version=\"1.0\" encoding=\"ascii\"?>
+<hkpackfile classversion=\"8\">
+\t<hksection name=\"__data__\">
+\t\t<hkobject name=\"#0001\" class=\"hkRootLevelContainer\">
+\t\t\t<hkparam name=\"namedVariants\" numelements=\"0\"></hkparam>
+\t\t</hkobject>
+\t</hksection>
+</hkpackfile>
+";
+    assert_eq!(reformat(xml), expected);
+}
+
+#[test]
+fn multi_line_values_get_one_entry_per_line() {
+    let xml = "<hkobject><hkparam name=\"translations\" numelements=\"2\">(0.0 1.0 2.0)\n      (3.0 4.0 5.0)   </hkparam></hkobject>";
+    let expected = "<hkobject>
+\t<hkparam name=\"translations\" numelements=\"2\">
+\t\t(0.0 1.0 2.0)
+\t\t(3.0 4.0 5.0)
+\t</hkparam>
+</hkobject>
+";
+    assert_eq!(reformat(xml), expected);
+}
+
+#[test]
+fn formatting_is_stable() {
+    let xml = "<a>\n<b>one</b><c>x\ny</c><!-- note --></a>";
+    let once = reformat(xml);
+    assert_eq!(reformat(&once), once);
+}
+
+#[test]
+fn escaped_text_is_kept() {
+    assert_eq!(reformat("<a>&lt;1 &amp; 2&gt;</a>"), "<a>&lt;1 &amp; 2&gt;</a>\n");
+}
+
+#[test]
+fn malformed_xml_is_rejected() {
+    assert!(reformat_xml(b"<a><b></a>").is_err());
+}
+
+fn spans(xml: &str) -> Vec<(&str, XmlSpan)> {
+    xml_spans(xml).into_iter().map(|(range, span)| (&xml[range], span)).collect()
+}
+
+#[test]
+fn xml_spans_split_tags_attributes_and_text() {
+    use XmlSpan::*;
+    assert_eq!(
+        spans(r#"<hkparam name="duration">1.5</hkparam>"#),
+        [
+            ("<", Markup),
+            ("hkparam", TagName),
+            (" ", Markup),
+            ("name", AttributeName),
+            ("=", Markup),
+            ("\"duration\"", AttributeValue),
+            (">", Markup),
+            ("1.5", Text),
+            ("</", Markup),
+            ("hkparam", TagName),
+            (">", Markup),
+        ]
+    );
+}
+
+#[test]
+fn xml_spans_cover_comments_declarations_and_unclosed_markup() {
+    let xml = "<?xml version=\"1.0\"?>\n<!-- a <b> c -->\t<hkobject class='x' /><broken attr=\"open";
+    let spans = xml_spans(xml);
+    // Every byte is covered once, in order
+    assert_eq!(spans.first().unwrap().0.start, 0);
+    assert_eq!(spans.last().unwrap().0.end, xml.len());
+    assert!(spans.windows(2).all(|pair| pair[0].0.end == pair[1].0.start));
+    let spans: Vec<(&str, XmlSpan)> = spans.into_iter().map(|(range, span)| (&xml[range], span)).collect();
+    assert!(spans.contains(&("xml", XmlSpan::TagName)));
+    assert!(spans.contains(&("<!-- a <b> c -->", XmlSpan::Comment)));
+    assert!(spans.contains(&("'x'", XmlSpan::AttributeValue)));
+    // An unclosed value runs to the end
+    assert_eq!(spans.last(), Some(&("\"open", XmlSpan::AttributeValue)));
+}